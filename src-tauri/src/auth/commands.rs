@@ -1,11 +1,12 @@
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 
 use super::client::OidcClient;
 pub use super::client::UserInfo;
 use super::hub_client::HubAuthError;
 use super::server::CallbackServer;
 use super::storage::TokenStorage;
+use crate::control_server::{ControlServer, LauncherMessage};
 use crate::error::{CommandError, CommandResult};
 
 use super::hub_client::HubClient;
@@ -137,10 +138,18 @@ pub async fn logout(app: AppHandle) -> CommandResult<AuthState> {
 
     let auth_state = AuthState::logged_out();
     app.emit("auth-state-changed", &auth_state).ok();
+    notify_game(&app, &LauncherMessage::AuthExpired);
 
     Ok(auth_state)
 }
 
+/// Let running games know about a change to the user's login.
+fn notify_game(app: &AppHandle, message: &LauncherMessage) {
+    if let Some(control_server) = app.try_state::<ControlServer>() {
+        control_server.broadcast(message);
+    }
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn get_auth_state() -> CommandResult<AuthState> {
@@ -398,12 +407,14 @@ pub async fn background_refresh_task(app: AppHandle) {
                 match refresh_tokens_internal(token_to_use).await {
                     Ok(auth_state) => {
                         app.emit("auth-state-changed", &auth_state).ok();
+                        notify_game(&app, &LauncherMessage::AuthRefreshed);
                     }
                     Err(e) => {
                         tracing::warn!("Background refresh failed: {}", e);
                         TokenStorage::clear_tokens().ok();
                         app.emit("auth-state-changed", &AuthState::logged_out())
                             .ok();
+                        notify_game(&app, &LauncherMessage::AuthExpired);
                     }
                 }
             }
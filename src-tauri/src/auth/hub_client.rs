@@ -135,6 +135,7 @@ impl HubClient {
         token: &str,
         server_id: &str,
         hwid: Option<&str>,
+        legacy_hwid: Option<&str>,
     ) -> Result<String, HubAuthError> {
         let client = Self::from_config()?;
 
@@ -145,6 +146,7 @@ impl HubClient {
             .json(&serde_json::json!({
                 "server_id": server_id,
                 "hwid": hwid,
+                "legacy_hwid": legacy_hwid,
             }))
             .send()
             .await
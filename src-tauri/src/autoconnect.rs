@@ -311,7 +311,7 @@ mod implementation {
             tokio::time::sleep(std::time::Duration::from_millis(500)).await;
         }
 
-        let Some(relay_host) = relay_state.get_selected_host().await else {
+        let Some(relay_host) = relay_state.connect_host().await else {
             tracing::error!("No relay selected after pinging");
             emit_status(
                 &handle,
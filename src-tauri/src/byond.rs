@@ -6,14 +6,14 @@ use std::io;
 #[cfg(target_os = "linux")]
 use std::path::Path;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tauri::{AppHandle, Manager};
 
 use crate::auth::TokenStorage;
+use crate::connection::{ConnectionAttempt, ConnectionManager, ConnectionStage};
 use crate::error::{CommandError, CommandResult};
 use crate::relays::RelayState;
-use crate::servers::ServerState;
+use crate::servers::{Server, ServerState};
 use crate::settings::{load_settings, AuthMode};
 
 #[cfg(any(target_os = "windows", target_os = "linux"))]
@@ -30,8 +30,6 @@ use crate::wine;
 #[cfg(feature = "steam")]
 use crate::steam::{authenticate_with_steam, SteamState};
 
-static CONNECTING: AtomicBool = AtomicBool::new(false);
-
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub enum AccessMethod {
@@ -60,6 +58,17 @@ impl AccessMethod {
     fn should_exchange_hub_ticket(&self) -> bool {
         matches!(self, Self::SessionToken { .. })
     }
+
+    /// Short name of how the user authenticated, for play history.
+    pub fn auth_mode(&self) -> &'static str {
+        match self {
+            Self::HubTicket(_) => "hub",
+            Self::SessionToken { .. } => "oidc",
+            Self::Steam(_) => "steam",
+            Self::Byond => "byond",
+            Self::None => "none",
+        }
+    }
 }
 
 pub struct ConnectionRequest {
@@ -309,7 +318,7 @@ pub struct ConnectionResult {
 
 use crate::servers::EngineRequirements;
 
-fn parse_byond_version(v: &str) -> Option<(u32, u32)> {
+pub fn parse_byond_version(v: &str) -> Option<(u32, u32)> {
     let parts: Vec<&str> = v.split('.').collect();
     if parts.len() != 2 {
         return None;
@@ -325,6 +334,24 @@ fn version_cmp(a: &str, b: &str) -> Option<std::cmp::Ordering> {
     Some(a.cmp(&b))
 }
 
+/// Describe how `version` violates the engine constraints, if it does.
+fn engine_violation(version: &str, engine: &EngineRequirements) -> Option<String> {
+    if engine.blacklisted_versions.iter().any(|v| v == version) {
+        return Some(format!("{version} is blacklisted by the server"));
+    }
+    if let Some(ref min) = engine.min_version {
+        if version_cmp(version, min) == Some(std::cmp::Ordering::Less) {
+            return Some(format!("{version} is older than the minimum version {min}"));
+        }
+    }
+    if let Some(ref max) = engine.max_version {
+        if version_cmp(version, max) == Some(std::cmp::Ordering::Greater) {
+            return Some(format!("{version} is newer than the maximum version {max}"));
+        }
+    }
+    None
+}
+
 /// Select the best BYOND version to use given engine constraints.
 /// Returns the version string to use (may need to be installed).
 pub fn select_byond_version(
@@ -355,22 +382,7 @@ pub fn select_byond_version(
     // Filter installed versions by constraints
     let mut valid: Vec<&String> = installed
         .into_iter()
-        .filter(|v| {
-            if engine.blacklisted_versions.contains(v) {
-                return false;
-            }
-            if let Some(ref min) = engine.min_version {
-                if version_cmp(v, min) == Some(std::cmp::Ordering::Less) {
-                    return false;
-                }
-            }
-            if let Some(ref max) = engine.max_version {
-                if version_cmp(v, max) == Some(std::cmp::Ordering::Greater) {
-                    return false;
-                }
-            }
-            true
-        })
+        .filter(|v| engine_violation(v, engine).is_none())
         .collect();
 
     // Sort by version descending, pick highest
@@ -402,6 +414,67 @@ pub fn select_byond_version(
     })
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct ByondVersionOverrideWarning {
+    pub server_name: String,
+    pub version: String,
+    pub reason: String,
+}
+
+/// Swap in the user's BYOND version override for `server_name`, if one is set.
+/// Emits `byond-version-override-warning` when the forced version falls outside
+/// the server's engine requirements; the override is still honoured.
+async fn apply_version_override(app: &AppHandle, server_name: &str, selected: String) -> String {
+    use tauri::Emitter;
+
+    let settings = match load_settings(app) {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::warn!("Failed to load settings for BYOND version override: {}", e);
+            return selected;
+        }
+    };
+
+    let Some(forced) = settings.byond_version_override_for(server_name) else {
+        return selected;
+    };
+
+    let engine = match app.try_state::<Arc<ServerState>>() {
+        Some(server_state) => server_state
+            .get_servers()
+            .await
+            .into_iter()
+            .find(|s| s.name == server_name)
+            .and_then(|s| s.engine),
+        None => None,
+    };
+
+    if let Some(reason) = engine.as_ref().and_then(|e| engine_violation(forced, e)) {
+        tracing::warn!(
+            "BYOND version override for {} violates engine requirements: {}",
+            server_name,
+            reason
+        );
+        app.emit(
+            "byond-version-override-warning",
+            ByondVersionOverrideWarning {
+                server_name: server_name.to_string(),
+                version: forced.to_string(),
+                reason,
+            },
+        )
+        .ok();
+    }
+
+    tracing::info!(
+        "Using BYOND version override {} for {} (selected {})",
+        forced,
+        server_name,
+        selected
+    );
+    forced.to_string()
+}
+
 /// Build a BYOND connection URL with optional auth and launcher ports.
 pub fn build_connect_url(
     host: &str,
@@ -409,6 +482,7 @@ pub fn build_connect_url(
     access_method: &AccessMethod,
     launcher_port: Option<&str>,
     launcher_key: Option<&str>,
+    launcher_secret: Option<&str>,
     websocket_port: Option<&str>,
 ) -> String {
     let mut query_params = Vec::new();
@@ -424,6 +498,10 @@ pub fn build_connect_url(
         query_params.push(format!("launcher_key={key}"));
     }
 
+    if let Some(secret) = launcher_secret {
+        query_params.push(format!("launcher_secret={secret}"));
+    }
+
     if let Some(port) = websocket_port {
         query_params.push(format!("websocket_port={port}"));
     }
@@ -481,6 +559,10 @@ fn get_byond_pager_path(app: &AppHandle, version: &str) -> CommandResult<PathBuf
     Ok(version_dir.join("byond").join("bin").join("byond.exe"))
 }
 
+fn dreamseeker_installed(app: &AppHandle, version: &str) -> bool {
+    get_dreamseeker_path(app, version).is_ok_and(|p| p.exists())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn check_byond_version(
@@ -702,7 +784,7 @@ pub async fn install_byond_version(
 
         if dx_installer.exists() {
             tracing::info!("Running BYOND's bundled DirectX installer via Wine");
-            match wine::launch_with_wine(&app, &dx_installer, &["/silent"], &[]) {
+            match wine::launch_with_wine(&app, &dx_installer, &["/silent"], &[], None) {
                 Ok(mut child) => {
                     // Wait for installer to complete (with timeout)
                     let timeout = tokio::time::Duration::from_secs(60);
@@ -743,38 +825,65 @@ pub async fn install_byond_version(
     check_byond_version(app, version).await
 }
 
-/// Internal function for connecting with explicit auth params.
-pub async fn connect(app: AppHandle, req: ConnectionRequest) -> CommandResult<ConnectionResult> {
-    let source_str = req.source.as_deref().unwrap_or("unknown");
+fn connection_in_progress() -> ConnectionResult {
+    ConnectionResult {
+        success: false,
+        message: "Connection already in progress".to_string(),
+        auth_error: None,
+    }
+}
 
-    if CONNECTING.swap(true, Ordering::SeqCst) {
+/// Start a new connection attempt, or `None` if one is already in progress.
+fn begin_attempt(
+    app: &AppHandle,
+    server_name: &str,
+    source: Option<&str>,
+) -> CommandResult<Option<ConnectionAttempt>> {
+    let manager = app
+        .try_state::<Arc<ConnectionManager>>()
+        .ok_or_else(|| CommandError::Internal("connection manager not available".into()))?;
+
+    let attempt = manager.begin(app, server_name);
+    if attempt.is_none() {
         tracing::warn!(
             "[connect_to_server] BLOCKED duplicate connection attempt, source={} server={}",
-            source_str,
-            req.server_name
+            source.unwrap_or("unknown"),
+            server_name
         );
-        return Ok(ConnectionResult {
-            success: false,
-            message: "Connection already in progress".to_string(),
-            auth_error: None,
-        });
     }
+    Ok(attempt)
+}
+
+/// Internal function for connecting with explicit auth params.
+pub async fn connect(app: AppHandle, req: ConnectionRequest) -> CommandResult<ConnectionResult> {
+    let Some(attempt) = begin_attempt(&app, &req.server_name, req.source.as_deref())? else {
+        return Ok(connection_in_progress());
+    };
 
     tracing::info!(
         "[connect_to_server] source={} server={} version={}",
-        source_str,
+        req.source.as_deref().unwrap_or("unknown"),
         req.server_name,
         req.version
     );
 
-    let result = connect_impl(app, req).await;
-
-    CONNECTING.store(false, Ordering::SeqCst);
+    let result = connect_impl(app, &attempt, req).await;
+    record_outcome(&attempt, &result);
     result
 }
 
+/// Mark the attempt as failed if the connection didn't go through.
+fn record_outcome(attempt: &ConnectionAttempt, result: &CommandResult<ConnectionResult>) {
+    match result {
+        Ok(r) if !r.success => attempt.fail(&r.message),
+        Err(e) if !attempt.is_cancelled() => attempt.fail(&e.to_string()),
+        _ => {}
+    }
+}
+
 #[allow(clippy::unused_async)] // Uses await when steam feature is enabled
-async fn maybe_exchange_hub_ticket(
+pub async fn maybe_exchange_hub_ticket(
+    app: &AppHandle,
     method: AccessMethod,
     server_id: &str,
 ) -> Result<AccessMethod, ConnectionResult> {
@@ -786,8 +895,16 @@ async fn maybe_exchange_hub_ticket(
         return Ok(method);
     };
 
-    let hwid = crate::control_server::generate_hwid();
-    match crate::auth::hub_client::HubClient::join(token, server_id, hwid.as_deref()).await {
+    let hwid = crate::hwid::hwid_for_server(app, server_id);
+    let legacy_hwid = crate::hwid::legacy_hwid(app);
+    match crate::auth::hub_client::HubClient::join(
+        token,
+        server_id,
+        hwid.as_deref(),
+        legacy_hwid.as_deref(),
+    )
+    .await
+    {
         Ok(ticket) => Ok(AccessMethod::HubTicket(ticket)),
         Err(e) => Err(ConnectionResult {
             success: false,
@@ -917,33 +1034,45 @@ async fn get_auth_for_connection(
     }
 }
 
-#[tauri::command]
-#[specta::specta]
-pub async fn connect_to_server(
-    app: AppHandle,
-    server_name: String,
-    source: Option<String>,
-) -> CommandResult<ConnectionResult> {
-    let source_str = source.as_deref().unwrap_or("unknown");
-
-    let server_state = app
-        .try_state::<Arc<ServerState>>()
-        .ok_or_else(|| CommandError::Internal("server state not available".into()))?;
-    let servers = server_state.get_servers().await;
-    let server = servers
-        .iter()
-        .find(|s| s.name == server_name)
-        .ok_or_else(|| CommandError::NotFound(format!("server '{server_name}'")))?
-        .clone();
-
-    let version = select_byond_version(server.engine.as_ref(), &app)?;
-
+/// Host and port to connect to for a listed server. A relay the player
+/// picked for this server comes first. Otherwise, with the relay selector
+/// enabled the host is the selected relay, or the next best one if it is
+/// unreachable.
+pub async fn server_host_port(app: &AppHandle, server: &Server) -> CommandResult<(String, String)> {
     let config = crate::config::get_config();
 
     // Parse host and port from server URL (format: byond://host:port)
     let address = server.url.strip_prefix("byond://").unwrap_or(&server.url);
 
-    let (host, port) = if config.features.relay_selector {
+    let preferred_relay = crate::settings::load_settings(app)
+        .ok()
+        .and_then(|settings| settings.server_relays.get(&server.name).cloned());
+    let preferred_host = match (preferred_relay, app.try_state::<Arc<RelayState>>()) {
+        (Some(relay_id), Some(relay_state)) => {
+            let host = relay_state.usable_host(&relay_id).await;
+            if host.is_none() {
+                tracing::warn!(
+                    "Relay {} preferred for {} is unavailable, using the default route",
+                    relay_id,
+                    server.name
+                );
+            }
+            host
+        }
+        _ => None,
+    };
+    if let Some(host) = preferred_host {
+        let port = address.rsplit(':').next().unwrap_or_default().to_string();
+        if port.is_empty() || port == address {
+            return Err(CommandError::InvalidInput(format!(
+                "Invalid server URL format: {}",
+                server.url
+            )));
+        }
+        return Ok((host, port));
+    }
+
+    if config.features.relay_selector {
         // CM mode: use relay for host, extract port from server URL
         let port = address
             .split(':')
@@ -957,11 +1086,11 @@ pub async fn connect_to_server(
             .try_state::<Arc<RelayState>>()
             .ok_or_else(|| CommandError::Internal("relay state not available".into()))?;
         let host = relay_state
-            .get_selected_host()
+            .connect_host()
             .await
             .ok_or_else(|| CommandError::NotFound("no relay selected".into()))?;
 
-        (host, port)
+        Ok((host, port))
     } else {
         // SS13 mode: use host:port directly from server URL
         let parts: Vec<&str> = address.split(':').collect();
@@ -972,10 +1101,54 @@ pub async fn connect_to_server(
             )));
         }
         #[allow(clippy::indexing_slicing)] // length checked above
-        (parts[0].to_string(), parts[1].to_string())
+        Ok((parts[0].to_string(), parts[1].to_string()))
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn connect_to_server(
+    app: AppHandle,
+    server_name: String,
+    source: Option<String>,
+) -> CommandResult<ConnectionResult> {
+    let Some(attempt) = begin_attempt(&app, &server_name, source.as_deref())? else {
+        return Ok(connection_in_progress());
     };
 
-    let auth = match get_auth_for_connection(&app, &server.auth_methods).await {
+    let result = connect_to_server_impl(app, &attempt, server_name, source).await;
+    record_outcome(&attempt, &result);
+    result
+}
+
+async fn connect_to_server_impl(
+    app: AppHandle,
+    attempt: &ConnectionAttempt,
+    server_name: String,
+    source: Option<String>,
+) -> CommandResult<ConnectionResult> {
+    let source_str = source.as_deref().unwrap_or("unknown");
+
+    let server_state = app
+        .try_state::<Arc<ServerState>>()
+        .ok_or_else(|| CommandError::Internal("server state not available".into()))?;
+    let servers = server_state.get_servers().await;
+    let server = servers
+        .iter()
+        .find(|s| s.name == server_name)
+        .ok_or_else(|| CommandError::NotFound(format!("server '{server_name}'")))?
+        .clone();
+
+    let version = select_byond_version(server.engine.as_ref(), &app)?;
+
+    let (host, port) = server_host_port(&app, &server).await?;
+
+    attempt.set_stage(ConnectionStage::Authenticating);
+
+    let auth = match attempt
+        .run(get_auth_for_connection(&app, &server.auth_methods))
+        .await?
+    {
         Ok(auth) => auth,
         Err(auth_error) => {
             return Ok(ConnectionResult {
@@ -987,7 +1160,10 @@ pub async fn connect_to_server(
     };
 
     let server_id_ref = server.id.as_deref().unwrap_or("");
-    let access_method = match maybe_exchange_hub_ticket(auth, server_id_ref).await {
+    let access_method = match attempt
+        .run(maybe_exchange_hub_ticket(&app, auth, server_id_ref))
+        .await?
+    {
         Ok(method) => method,
         Err(result) => return Ok(result),
     };
@@ -1002,8 +1178,9 @@ pub async fn connect_to_server(
         host
     );
 
-    connect(
+    connect_impl(
         app,
+        attempt,
         ConnectionRequest {
             version,
             host,
@@ -1313,6 +1490,21 @@ pub async fn connect_to_address(
     app: AppHandle,
     address: String,
     source: Option<String>,
+) -> CommandResult<ConnectionResult> {
+    let Some(attempt) = begin_attempt(&app, &address, source.as_deref())? else {
+        return Ok(connection_in_progress());
+    };
+
+    let result = connect_to_address_impl(app, &attempt, address, source).await;
+    record_outcome(&attempt, &result);
+    result
+}
+
+async fn connect_to_address_impl(
+    app: AppHandle,
+    attempt: &ConnectionAttempt,
+    address: String,
+    source: Option<String>,
 ) -> CommandResult<ConnectionResult> {
     let source_str = source.as_deref().unwrap_or("unknown");
 
@@ -1357,7 +1549,11 @@ pub async fn connect_to_address(
 
     let (access_method, server_id) = if let Some(server_id) = server_id {
         let all_methods = vec!["hub".to_string(), "byond".to_string()];
-        let auth = match get_auth_for_connection(&app, &all_methods).await {
+        attempt.set_stage(ConnectionStage::Authenticating);
+        let auth = match attempt
+            .run(get_auth_for_connection(&app, &all_methods))
+            .await?
+        {
             Ok(auth) => auth,
             Err(auth_error) => {
                 return Ok(ConnectionResult {
@@ -1367,7 +1563,10 @@ pub async fn connect_to_address(
                 });
             }
         };
-        let method = match maybe_exchange_hub_ticket(auth, &server_id).await {
+        let method = match attempt
+            .run(maybe_exchange_hub_ticket(&app, auth, &server_id))
+            .await?
+        {
             Ok(method) => method,
             Err(result) => return Ok(result),
         };
@@ -1386,8 +1585,9 @@ pub async fn connect_to_address(
         version
     );
 
-    connect(
+    connect_impl(
         app,
+        attempt,
         ConnectionRequest {
             version,
             host: hostname.to_string(),
@@ -1403,7 +1603,11 @@ pub async fn connect_to_address(
     .await
 }
 
-async fn connect_impl(app: AppHandle, req: ConnectionRequest) -> CommandResult<ConnectionResult> {
+async fn connect_impl(
+    app: AppHandle,
+    attempt: &ConnectionAttempt,
+    req: ConnectionRequest,
+) -> CommandResult<ConnectionResult> {
     let ConnectionRequest {
         version,
         host,
@@ -1416,7 +1620,25 @@ async fn connect_impl(app: AppHandle, req: ConnectionRequest) -> CommandResult<C
         players,
     } = req;
 
-    let version_info = install_byond_version(app.clone(), version.clone()).await?;
+    let version = apply_version_override(&app, &server_name, version).await;
+
+    attempt.set_stage(ConnectionStage::Installing);
+    let was_installed = dreamseeker_installed(&app, &version);
+    let version_info = match attempt
+        .run(install_byond_version(app.clone(), version.clone()))
+        .await
+    {
+        Ok(result) => result?,
+        Err(e) => {
+            // Don't leave a half-extracted version behind
+            if !was_installed {
+                if let Ok(dir) = get_byond_version_dir(&app, &version) {
+                    let _ = std::fs::remove_dir_all(dir);
+                }
+            }
+            return Err(e);
+        }
+    };
 
     if !version_info.installed {
         let msg = format!("Failed to install BYOND version {version}");
@@ -1447,23 +1669,30 @@ async fn connect_impl(app: AppHandle, req: ConnectionRequest) -> CommandResult<C
 
         let config = crate::config::get_config();
 
-        if let Some(control_server) = app.try_state::<ControlServer>() {
-            control_server.reset_connected_flag();
-        }
-
         let control_port = app.try_state::<ControlServer>().map(|s| s.port.to_string());
-        let launcher_key = app.try_state::<ControlServer>().map(|s| s.rotate_key());
-        let websocket_port = app
-            .try_state::<ControlServer>()
-            .map(|s| s.ws_port.to_string());
+        let launcher_key = app.try_state::<ControlServer>().map(|s| s.issue_key());
+        attempt.set_launcher_key(launcher_key.clone());
+        let launcher_secret = launcher_key.as_deref().and_then(|key| {
+            app.try_state::<ControlServer>()
+                .and_then(|s| s.secret_for(key))
+        });
+        // The WebSocket shares the control server's port
+        let websocket_port = control_port.clone();
 
         let webview2_data_dir = get_byond_base_dir(&app)?.join("webview2_data");
 
         let is_byond_auth = access_method.is_byond();
         let pager_running = check_byond_pager_running();
 
+        if is_byond_auth {
+            attempt.set_stage(ConnectionStage::Authenticating);
+        }
+
         let mut session_check = if is_byond_auth {
-            check_byond_web_session(app.clone()).await.ok()
+            attempt
+                .run(check_byond_web_session(app.clone()))
+                .await?
+                .ok()
         } else {
             None
         };
@@ -1475,7 +1704,7 @@ async fn connect_impl(app: AppHandle, req: ConnectionRequest) -> CommandResult<C
             }
             _ if !pager_running && is_byond_auth => {
                 tracing::info!("Not logged in to BYOND and pager not running, opening login flow");
-                let login_result = start_byond_login(app.clone()).await;
+                let login_result = attempt.run(start_byond_login(app.clone())).await?;
                 if login_result.is_err() {
                     return Err(CommandError::Cancelled {
                         operation: "byond_login".into(),
@@ -1492,6 +1721,8 @@ async fn connect_impl(app: AppHandle, req: ConnectionRequest) -> CommandResult<C
             }
         };
 
+        attempt.set_stage(ConnectionStage::Launching);
+
         if source.as_deref() != Some("control_server_restart") {
             app.emit("game-connecting", &server_name).ok();
         }
@@ -1517,6 +1748,9 @@ async fn connect_impl(app: AppHandle, req: ConnectionRequest) -> CommandResult<C
             if let Some(lk) = &launcher_key {
                 query_params.push(format!("launcher_key={}", lk));
             }
+            if let Some(secret) = &launcher_secret {
+                query_params.push(format!("launcher_secret={secret}"));
+            }
             if let Some(wp) = &websocket_port {
                 query_params.push(format!("websocket_port={}", wp));
             }
@@ -1554,17 +1788,42 @@ async fn connect_impl(app: AppHandle, req: ConnectionRequest) -> CommandResult<C
                         "WEBVIEW2_USER_DATA_FOLDER",
                         webview2_data_dir.to_str().unwrap(),
                     )],
+                    wine::launch_profile_for(&app, &server_name).as_ref(),
                 )
                 .map_err(|e| CommandError::Io(format!("Failed to launch BYOND via Wine: {e}")))?
             };
 
             existing_pids.insert(pager_child.id());
 
-            let dreamseeker_pid = wait_for_new_dreamseeker(existing_pids, 30).await;
+            let wait = attempt
+                .run(async {
+                    let pid = wait_for_new_dreamseeker(existing_pids, 30).await;
+                    if pid.is_some() {
+                        tracing::info!(
+                            "Waiting 5s for dreamseeker to authenticate before killing pager"
+                        );
+                        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                    }
+                    pid
+                })
+                .await;
+
+            let dreamseeker_pid = match wait {
+                Ok(pid) => pid,
+                Err(e) => {
+                    tracing::info!("Connection cancelled while launching, cleaning up");
+                    let _ = pager_child.kill();
+                    if let Some(key) = &launcher_key {
+                        kill_dreamseeker_by_key(key);
+                        if let Some(control_server) = app.try_state::<ControlServer>() {
+                            control_server.revoke_key(key);
+                        }
+                    }
+                    return Err(e);
+                }
+            };
 
             if dreamseeker_pid.is_some() {
-                tracing::info!("Waiting 5s for dreamseeker to authenticate before killing pager");
-                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
                 tracing::info!("Killing byond.exe pager");
                 let _ = pager_child.kill();
             }
@@ -1581,12 +1840,20 @@ async fn connect_impl(app: AppHandle, req: ConnectionRequest) -> CommandResult<C
                     launcher_key: launcher_key.clone(),
                 });
 
+                // Fall back to looking the client up by its key if the process diff missed it
+                let dreamseeker_pid = dreamseeker_pid.or_else(|| {
+                    launcher_key
+                        .as_deref()
+                        .and_then(find_dreamseeker_pid_by_key)
+                });
+
                 if let Some(pid) = dreamseeker_pid {
                     manager.start_game_session_by_pid(
                         server_name.clone(),
                         map_name.clone(),
                         players.unwrap_or(0) as u32,
                         pid,
+                        launcher_key.clone(),
                     );
                 } else {
                     tracing::warn!(
@@ -1601,6 +1868,7 @@ async fn connect_impl(app: AppHandle, req: ConnectionRequest) -> CommandResult<C
                 &access_method,
                 control_port.as_deref(),
                 launcher_key.as_deref(),
+                launcher_secret.as_deref(),
                 websocket_port.as_deref(),
             );
 
@@ -1619,6 +1887,7 @@ async fn connect_impl(app: AppHandle, req: ConnectionRequest) -> CommandResult<C
                     "WEBVIEW2_USER_DATA_FOLDER",
                     webview2_data_dir.to_str().unwrap(),
                 )],
+                wine::launch_profile_for(&app, &server_name).as_ref(),
             )
             .map_err(|e| CommandError::Io(format!("Failed to launch BYOND via Wine: {e}")))?;
 
@@ -1639,6 +1908,7 @@ async fn connect_impl(app: AppHandle, req: ConnectionRequest) -> CommandResult<C
                     map_name.clone(),
                     players.unwrap_or(0) as u32,
                     child,
+                    launcher_key.clone(),
                 );
             }
         }
@@ -1648,15 +1918,32 @@ async fn connect_impl(app: AppHandle, req: ConnectionRequest) -> CommandResult<C
                 let app_clone = app.clone();
                 let server_name_clone = server_name.clone();
                 let manager_clone = Arc::clone(&manager);
+                let key_clone = launcher_key.clone();
                 tokio::spawn(async move {
                     tokio::time::sleep(std::time::Duration::from_secs(30)).await;
-                    if manager_clone.get_game_session().is_some() {
+                    let still_running = match key_clone.as_deref() {
+                        Some(key) => manager_clone.get_session_by_key(key).is_some(),
+                        None => manager_clone.get_game_session().is_some(),
+                    };
+                    // Only needed for game builds that never reach the control server
+                    let handshake_done = key_clone.as_deref().is_some_and(|key| {
+                        app_clone
+                            .try_state::<ControlServer>()
+                            .is_some_and(|cs| cs.is_key_connected(key))
+                    });
+                    if still_running && !handshake_done {
+                        tracing::info!("No handshake within 30s, assuming the game connected");
+                        if let Some(connection) = app_clone.try_state::<Arc<ConnectionManager>>() {
+                            connection.mark_connected(&app_clone, key_clone.as_deref());
+                        }
                         app_clone.emit("game-connected", &server_name_clone).ok();
                     }
                 });
             }
         }
 
+        attempt.set_stage(ConnectionStage::WaitingForHandshake);
+
         // Record last-used timestamp
         if let Err(e) = record_version_used(&app, &version) {
             tracing::warn!("Failed to record BYOND version usage: {}", e);
@@ -1677,6 +1964,7 @@ async fn connect_impl(app: AppHandle, req: ConnectionRequest) -> CommandResult<C
     #[cfg(not(any(target_os = "windows", target_os = "linux")))]
     {
         let _ = (
+            attempt,
             dreamseeker_path,
             host,
             port,
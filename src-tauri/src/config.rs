@@ -26,6 +26,8 @@ pub struct LauncherConfig {
     pub singleplayer: SingleplayerConfig,
     pub oidc: Option<OidcConfig>,
     pub social_links: &'static [SocialLink],
+    /// Relays shipped with the launcher.
+    pub relays: &'static [RelayConfig],
 }
 
 #[derive(Debug, Clone, Serialize, specta::Type)]
@@ -42,6 +44,8 @@ pub struct LauncherFeatures {
     pub connect_logo: bool,
     pub favorites: bool,
     pub direct_connect: bool,
+    /// Players may turn off the hardware ID sent to servers.
+    pub hwid_opt_out: bool,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, specta::Type)]
@@ -58,6 +62,13 @@ pub struct SingleplayerConfig {
     pub dmb_name: Option<&'static str>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, specta::Type)]
+pub struct RelayConfig {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub host: &'static str,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, specta::Type)]
 pub struct OidcConfig {
     pub client_id: &'static str,
@@ -75,6 +86,8 @@ pub struct LauncherUrls {
     pub byond_hash_api: Option<&'static str>,
     pub register_url: Option<&'static str>,
     pub help_url: &'static str,
+    /// JSON list of extra relays, fetched at startup and on every relay check.
+    pub relay_manifest: Option<&'static str>,
 }
 
 #[derive(Debug, Clone, Serialize, specta::Type)]
@@ -108,6 +121,7 @@ pub fn get_config() -> LauncherConfig {
             connect_logo: false,
             favorites: false,
             direct_connect: false,
+            hwid_opt_out: false,
         },
         urls: LauncherUrls {
             server_api: "https://db.cm-ss13.com/api/Round",
@@ -117,6 +131,7 @@ pub fn get_config() -> LauncherConfig {
             byond_hash_api: Some("https://db.cm-ss13.com/api/ByondHash"),
             register_url: None,
             help_url: "https://github.com/cmss13-devs/cm-launcher/issues",
+            relay_manifest: None,
         },
         strings: LauncherStrings {
             auth_provider_name: "CM-SS13",
@@ -156,6 +171,53 @@ pub fn get_config() -> LauncherConfig {
                 icon: "wiki",
             },
         ],
+        relays: &[
+            RelayConfig {
+                id: "direct",
+                name: "Direct",
+                host: "direct.cm-ss13.com",
+            },
+            RelayConfig {
+                id: "nyc",
+                name: "NYC",
+                host: "nyc.cm-ss13.com",
+            },
+            RelayConfig {
+                id: "uk",
+                name: "UK",
+                host: "uk.cm-ss13.com",
+            },
+            RelayConfig {
+                id: "eu-e",
+                name: "EU East",
+                host: "eu-e.cm-ss13.com",
+            },
+            RelayConfig {
+                id: "eu-w",
+                name: "EU West",
+                host: "eu-w.cm-ss13.com",
+            },
+            RelayConfig {
+                id: "aus",
+                name: "Australia",
+                host: "aus.cm-ss13.com",
+            },
+            RelayConfig {
+                id: "us-e",
+                name: "US East",
+                host: "us-e.cm-ss13.com",
+            },
+            RelayConfig {
+                id: "us-w",
+                name: "US West",
+                host: "us-w.cm-ss13.com",
+            },
+            RelayConfig {
+                id: "asia-se",
+                name: "SE Asia",
+                host: "asia-se.cm-ss13.com",
+            },
+        ],
     }
 }
 
@@ -183,6 +245,7 @@ pub fn get_config() -> LauncherConfig {
             connect_logo: true,
             favorites: true,
             direct_connect: true,
+            hwid_opt_out: false,
         },
         urls: LauncherUrls {
             server_api: "https://api.zewaka.webcam/api/servers",
@@ -192,6 +255,7 @@ pub fn get_config() -> LauncherConfig {
             byond_hash_api: None,
             register_url: Some("https://ss13.cm-ss13.com/register"),
             help_url: "https://github.com/hry-gh/ss13-launcher/issues",
+            relay_manifest: None,
         },
         strings: LauncherStrings {
             auth_provider_name: "SS13",
@@ -205,6 +269,7 @@ pub fn get_config() -> LauncherConfig {
         },
         oidc: None,
         social_links: &[],
+        relays: &[],
     }
 }
 
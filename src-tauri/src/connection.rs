@@ -0,0 +1,366 @@
+//! Connection attempt state machine.
+//!
+//! Every launch goes through [`ConnectionManager::begin`], which hands out a
+//! [`ConnectionAttempt`]. The attempt reports each step as a `connection-state`
+//! event and can be cancelled from the frontend with `cancel_connection`, in
+//! which case the in-flight step is dropped and the caller unwinds whatever it
+//! had already done.
+//!
+//! Only one attempt can be resolving, installing, authenticating or launching
+//! at a time, since new clients are picked up by diffing the process list.
+//! Once an attempt is waiting for its handshake another one may start, so
+//! several game clients can run side by side.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::watch;
+
+use crate::error::{CommandError, CommandResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionStage {
+    Idle,
+    Resolving,
+    Installing,
+    Authenticating,
+    Launching,
+    WaitingForHandshake,
+    Connected,
+    Failed,
+    Cancelled,
+}
+
+impl ConnectionStage {
+    /// Stages in which the launcher is still doing work for the attempt.
+    /// Only one attempt may be in one of these at a time.
+    fn is_active(self) -> bool {
+        matches!(
+            self,
+            Self::Resolving | Self::Installing | Self::Authenticating | Self::Launching
+        )
+    }
+
+    /// Stages that `cancel_connection` can still act on.
+    fn is_cancellable(self) -> bool {
+        self.is_active() || self == Self::WaitingForHandshake
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct ConnectionStatus {
+    pub attempt_id: u32,
+    pub stage: ConnectionStage,
+    pub server_name: Option<String>,
+    pub message: Option<String>,
+}
+
+impl ConnectionStatus {
+    fn idle() -> Self {
+        Self {
+            attempt_id: 0,
+            stage: ConnectionStage::Idle,
+            server_name: None,
+            message: None,
+        }
+    }
+}
+
+struct Inner {
+    status: ConnectionStatus,
+    cancel_tx: Option<watch::Sender<bool>>,
+    /// Launcher key given to the client launched by this attempt.
+    launcher_key: Option<String>,
+}
+
+/// Tracks the current connection attempt and its stage.
+pub struct ConnectionManager {
+    inner: Mutex<Inner>,
+    next_id: AtomicU32,
+}
+
+impl ConnectionManager {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                status: ConnectionStatus::idle(),
+                cancel_tx: None,
+                launcher_key: None,
+            }),
+            next_id: AtomicU32::new(1),
+        }
+    }
+
+    pub fn status(&self) -> ConnectionStatus {
+        self.inner.lock().status.clone()
+    }
+
+    /// Start a new attempt, or return `None` if one is already in progress.
+    pub fn begin(
+        self: &Arc<Self>,
+        app: &AppHandle,
+        server_name: &str,
+    ) -> Option<ConnectionAttempt> {
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+
+        let status = {
+            let mut inner = self.inner.lock();
+            if inner.status.stage.is_active() {
+                return None;
+            }
+            inner.status = ConnectionStatus {
+                attempt_id: id,
+                stage: ConnectionStage::Resolving,
+                server_name: Some(server_name.to_string()),
+                message: None,
+            };
+            inner.cancel_tx = Some(cancel_tx);
+            inner.launcher_key = None;
+            inner.status.clone()
+        };
+        emit_status(app, &status);
+
+        Some(ConnectionAttempt {
+            id,
+            app: app.clone(),
+            manager: Arc::clone(self),
+            cancel_rx,
+        })
+    }
+
+    /// Cancel the current attempt. Returns the stage it was cancelled in and
+    /// the launcher key of its client, or `None` if there was nothing to cancel.
+    pub fn cancel(&self, app: &AppHandle) -> Option<(ConnectionStage, Option<String>)> {
+        let (previous, launcher_key, status) = {
+            let mut inner = self.inner.lock();
+            let previous = inner.status.stage;
+            if !previous.is_cancellable() {
+                return None;
+            }
+            if let Some(tx) = inner.cancel_tx.take() {
+                tx.send(true).ok();
+            }
+            inner.status.stage = ConnectionStage::Cancelled;
+            inner.status.message = None;
+            (previous, inner.launcher_key.clone(), inner.status.clone())
+        };
+        tracing::info!(
+            "Cancelled connection attempt {} during {:?}",
+            status.attempt_id,
+            previous
+        );
+        emit_status(app, &status);
+        Some((previous, launcher_key))
+    }
+
+    /// The client holding `launcher_key` reached the control server.
+    pub fn mark_connected(&self, app: &AppHandle, launcher_key: Option<&str>) {
+        self.transition_from(
+            app,
+            launcher_key,
+            &[ConnectionStage::WaitingForHandshake],
+            ConnectionStage::Connected,
+            None,
+        );
+    }
+
+    /// The client holding `launcher_key` went away.
+    pub fn mark_game_closed(&self, app: &AppHandle, launcher_key: Option<&str>) {
+        self.transition_from(
+            app,
+            launcher_key,
+            &[ConnectionStage::WaitingForHandshake],
+            ConnectionStage::Failed,
+            Some("Game closed before connecting".to_string()),
+        );
+        self.transition_from(
+            app,
+            launcher_key,
+            &[ConnectionStage::Connected],
+            ConnectionStage::Idle,
+            None,
+        );
+    }
+
+    /// The client holding `launcher_key` reported that it reached the wrong
+    /// server or that its login was rejected.
+    pub fn mark_handshake_failed(&self, app: &AppHandle, launcher_key: &str, message: String) {
+        self.transition_from(
+            app,
+            Some(launcher_key),
+            &[
+                ConnectionStage::WaitingForHandshake,
+                ConnectionStage::Connected,
+            ],
+            ConnectionStage::Failed,
+            Some(message),
+        );
+    }
+
+    /// Only applies to the current attempt's own client; other sessions
+    /// coming and going don't affect it.
+    fn transition_from(
+        &self,
+        app: &AppHandle,
+        launcher_key: Option<&str>,
+        from: &[ConnectionStage],
+        to: ConnectionStage,
+        message: Option<String>,
+    ) {
+        let status = {
+            let mut inner = self.inner.lock();
+            if !from.contains(&inner.status.stage) || inner.launcher_key.as_deref() != launcher_key
+            {
+                return;
+            }
+            inner.status.stage = to;
+            inner.status.message = message;
+            inner.status.clone()
+        };
+        emit_status(app, &status);
+    }
+
+    fn update(&self, app: &AppHandle, id: u32, stage: ConnectionStage, message: Option<String>) {
+        let status = {
+            let mut inner = self.inner.lock();
+            // Stale attempts (already cancelled or superseded) must not clobber the status
+            if inner.status.attempt_id != id || inner.status.stage == ConnectionStage::Cancelled {
+                return;
+            }
+            inner.status.stage = stage;
+            inner.status.message = message;
+            if !stage.is_cancellable() {
+                inner.cancel_tx = None;
+            }
+            inner.status.clone()
+        };
+        emit_status(app, &status);
+    }
+}
+
+impl Default for ConnectionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn emit_status(app: &AppHandle, status: &ConnectionStatus) {
+    tracing::debug!(
+        "Connection attempt {} -> {:?}",
+        status.attempt_id,
+        status.stage
+    );
+    app.emit("connection-state", status).ok();
+}
+
+/// Handle to an in-flight connection attempt.
+///
+/// Dropping an attempt that is still in an active stage marks it as failed, so
+/// early returns never leave the manager stuck.
+pub struct ConnectionAttempt {
+    id: u32,
+    app: AppHandle,
+    manager: Arc<ConnectionManager>,
+    cancel_rx: watch::Receiver<bool>,
+}
+
+impl ConnectionAttempt {
+    pub fn set_stage(&self, stage: ConnectionStage) {
+        self.manager.update(&self.app, self.id, stage, None);
+    }
+
+    pub fn set_launcher_key(&self, launcher_key: Option<String>) {
+        let mut inner = self.manager.inner.lock();
+        if inner.status.attempt_id == self.id {
+            inner.launcher_key = launcher_key;
+        }
+    }
+
+    pub fn fail(&self, message: &str) {
+        self.manager.update(
+            &self.app,
+            self.id,
+            ConnectionStage::Failed,
+            Some(message.to_string()),
+        );
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancel_rx.borrow()
+    }
+
+    /// Run one step of the attempt, dropping it if the attempt is cancelled.
+    pub async fn run<F: Future>(&self, fut: F) -> CommandResult<F::Output> {
+        if self.is_cancelled() {
+            return Err(cancelled());
+        }
+
+        let mut cancel_rx = self.cancel_rx.clone();
+        let wait_cancelled = async move {
+            if cancel_rx.wait_for(|c| *c).await.is_err() {
+                // Sender dropped without cancelling: the attempt finished normally
+                std::future::pending::<()>().await;
+            }
+        };
+
+        tokio::select! {
+            out = fut => Ok(out),
+            () = wait_cancelled => Err(cancelled()),
+        }
+    }
+}
+
+impl Drop for ConnectionAttempt {
+    fn drop(&mut self) {
+        let still_active = {
+            let inner = self.manager.inner.lock();
+            inner.status.attempt_id == self.id && inner.status.stage.is_active()
+        };
+        if still_active {
+            self.fail("Connection aborted");
+        }
+    }
+}
+
+fn cancelled() -> CommandError {
+    CommandError::Cancelled {
+        operation: "connection".into(),
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_connection_state(manager: tauri::State<'_, Arc<ConnectionManager>>) -> ConnectionStatus {
+    manager.status()
+}
+
+/// Cancel the current connection attempt. If the game has already been
+/// launched but hasn't reached the control server yet, it is killed. Other
+/// running clients are left alone.
+#[tauri::command]
+#[specta::specta]
+pub fn cancel_connection(
+    app: AppHandle,
+    manager: tauri::State<'_, Arc<ConnectionManager>>,
+) -> bool {
+    let Some((previous, launcher_key)) = manager.cancel(&app) else {
+        return false;
+    };
+
+    if previous == ConnectionStage::WaitingForHandshake {
+        if let (Some(presence), Some(key)) = (
+            app.try_state::<Arc<crate::presence::PresenceManager>>(),
+            launcher_key.as_deref(),
+        ) {
+            presence.kill_session_by_key(key);
+        }
+    }
+
+    true
+}
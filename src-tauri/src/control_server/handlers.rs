@@ -0,0 +1,527 @@
+//! Version 1 control-server endpoints.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager};
+
+use super::routes::{
+    ApiError, ApiResult, CapabilitiesResponse, Endpoint, RequestContext, RouteTable,
+};
+use super::security::{RateLimit, Signing};
+use super::{refresh_auth_token, ControlServer};
+use crate::connection::ConnectionManager;
+use crate::error::CommandError;
+use crate::hop::HopStatus;
+use crate::presence::PresenceManager;
+
+/// Register the v1 endpoints.
+pub fn register_v1(routes: &mut RouteTable) {
+    routes.register(1, Capabilities);
+    routes.register(1, Hello);
+    routes.register(1, Restart);
+    routes.register(1, GetUrl);
+    routes.register(1, Status);
+    routes.register(1, AuthTicket);
+    routes.register(1, Hop);
+}
+
+/// For endpoints that take no parameters.
+#[derive(Debug, Deserialize)]
+pub struct NoParams {}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HandshakeResult {
+    Confirmed,
+    ServerMismatch { expected: String, actual: String },
+    AuthFailed { message: String },
+}
+
+/// Payload of the `game-handshake` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct GameHandshake {
+    pub server_name: String,
+    pub ckey: Option<String>,
+    pub round_id: Option<String>,
+    pub server_id: Option<String>,
+    pub result: HandshakeResult,
+}
+
+/// Mark the client holding `launcher_key` as connected.
+pub(super) fn confirm_connected(
+    launcher_key: &str,
+    app_handle: &tauri::AppHandle,
+    presence_manager: &Arc<PresenceManager>,
+) {
+    if let Some(connection) = app_handle.try_state::<Arc<ConnectionManager>>() {
+        connection.mark_connected(app_handle, Some(launcher_key));
+    }
+    let server_name = presence_manager
+        .get_connection_params(launcher_key)
+        .map(|p| p.server_name);
+    if let Some(server_name) = server_name {
+        app_handle.emit("game-connected", &server_name).ok();
+    }
+}
+
+/// What this launcher supports, so games can feature-detect before using an
+/// endpoint. Unsigned, since a game asks before it knows whether to sign.
+pub struct Capabilities;
+
+#[async_trait]
+impl Endpoint for Capabilities {
+    type Request = NoParams;
+    type Response = CapabilitiesResponse;
+    const PATH: &'static str = "capabilities";
+    const SIGNING: Signing = Signing::None;
+
+    async fn handle(
+        &self,
+        ctx: &RequestContext,
+        _request: NoParams,
+    ) -> ApiResult<CapabilitiesResponse> {
+        Ok(ctx.routes.capabilities())
+    }
+}
+
+/// Handshake sent by the game once it has joined a server:
+/// `/hello?ckey=..&round_id=..&server_id=..`, or with `status=auth_failed`
+/// and an `error` if the server rejected the login.
+pub struct Hello;
+
+#[derive(Debug, Deserialize)]
+pub struct HelloRequest {
+    pub ckey: Option<String>,
+    pub round_id: Option<String>,
+    pub server_id: Option<String>,
+    pub status: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HelloResponse {
+    /// `ok`, `mismatch` or `auth_failed`.
+    pub status: &'static str,
+}
+
+#[async_trait]
+impl Endpoint for Hello {
+    type Request = HelloRequest;
+    type Response = HelloResponse;
+    const PATH: &'static str = "hello";
+    const SIGNING: Signing = Signing::Legacy;
+
+    async fn handle(
+        &self,
+        ctx: &RequestContext,
+        request: HelloRequest,
+    ) -> ApiResult<HelloResponse> {
+        let Some(params) = ctx
+            .presence_manager
+            .get_connection_params(&ctx.launcher_key)
+        else {
+            return Err(ApiError::bad_request("No connection for this launcher key"));
+        };
+
+        let result = if request.status.as_deref() == Some("auth_failed") {
+            HandshakeResult::AuthFailed {
+                message: request
+                    .error
+                    .unwrap_or_else(|| "The server rejected the login".to_string()),
+            }
+        } else {
+            match (params.server_id.as_deref(), request.server_id.as_deref()) {
+                (Some(expected), Some(actual)) if expected != actual => {
+                    HandshakeResult::ServerMismatch {
+                        expected: expected.to_string(),
+                        actual: actual.to_string(),
+                    }
+                }
+                _ => HandshakeResult::Confirmed,
+            }
+        };
+
+        let handshake = GameHandshake {
+            server_name: params.server_name,
+            ckey: request.ckey,
+            round_id: request.round_id,
+            server_id: request.server_id,
+            result,
+        };
+        tracing::info!(
+            "Game handshake for {}: ckey={:?} round={:?} result={:?}",
+            handshake.server_name,
+            handshake.ckey,
+            handshake.round_id,
+            handshake.result
+        );
+
+        ctx.presence_manager.set_session_handshake(
+            &ctx.launcher_key,
+            handshake.ckey.clone(),
+            handshake.round_id.clone(),
+        );
+        ctx.app_handle.emit("game-handshake", &handshake).ok();
+
+        let status = match &handshake.result {
+            HandshakeResult::Confirmed => {
+                if ctx.first_request {
+                    confirm_connected(&ctx.launcher_key, &ctx.app_handle, &ctx.presence_manager);
+                }
+                "ok"
+            }
+            HandshakeResult::ServerMismatch { expected, actual } => {
+                tracing::warn!(
+                    "Game reached server {} but {} was expected",
+                    actual,
+                    expected
+                );
+                if let Some(connection) = ctx.app_handle.try_state::<Arc<ConnectionManager>>() {
+                    connection.mark_handshake_failed(
+                        &ctx.app_handle,
+                        &ctx.launcher_key,
+                        format!("Connected to the wrong server ({actual})"),
+                    );
+                }
+                "mismatch"
+            }
+            HandshakeResult::AuthFailed { message } => {
+                tracing::warn!("Game reported an authentication failure: {}", message);
+                if let Some(connection) = ctx.app_handle.try_state::<Arc<ConnectionManager>>() {
+                    connection.mark_handshake_failed(
+                        &ctx.app_handle,
+                        &ctx.launcher_key,
+                        message.clone(),
+                    );
+                }
+                "auth_failed"
+            }
+        };
+
+        Ok(HelloResponse { status })
+    }
+}
+
+/// Relaunch the game on the server it is connected to.
+pub struct Restart;
+
+#[derive(Debug, Deserialize)]
+pub struct RestartRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestartResponse {
+    pub status: &'static str,
+}
+
+#[async_trait]
+impl Endpoint for Restart {
+    type Request = RestartRequest;
+    type Response = RestartResponse;
+    const PATH: &'static str = "restart";
+    const RATE_LIMIT: RateLimit = RateLimit {
+        burst: 2,
+        per_minute: 4,
+    };
+    const AUDITED: bool = true;
+
+    async fn handle(
+        &self,
+        ctx: &RequestContext,
+        request: RestartRequest,
+    ) -> ApiResult<RestartResponse> {
+        tracing::info!("Restart command received with reason: {:?}", request.reason);
+
+        let Some(params) = ctx
+            .presence_manager
+            .get_connection_params(&ctx.launcher_key)
+        else {
+            return Err(ApiError::bad_request("No previous connection to restart"));
+        };
+
+        if ctx.presence_manager.kill_session_by_key(&ctx.launcher_key) {
+            tracing::info!("Killed existing game process");
+        }
+
+        ctx.app_handle
+            .emit(
+                "game-restarting",
+                serde_json::json!({
+                    "server_name": params.server_name,
+                    "reason": request.reason,
+                }),
+            )
+            .ok();
+
+        let app_handle = ctx.app_handle.clone();
+        let server_name = params.server_name.clone();
+
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+
+            let fresh_params = match refresh_auth_token(&app_handle, params).await {
+                Ok(params) => params,
+                Err(e) => {
+                    tracing::error!("Failed to refresh auth token: {}", e);
+                    return;
+                }
+            };
+            let host = crate::relays::fallback_host(&app_handle, fresh_params.host).await;
+
+            let result = crate::byond::connect(
+                app_handle,
+                crate::byond::ConnectionRequest {
+                    version: fresh_params.version,
+                    host,
+                    port: fresh_params.port,
+                    access_method: fresh_params.access_method,
+                    server_name: fresh_params.server_name,
+                    map_name: fresh_params.map_name,
+                    source: Some("control_server_restart".to_string()),
+                    server_id: fresh_params.server_id,
+                    players: None,
+                },
+            )
+            .await;
+
+            match result {
+                Ok(_) => tracing::info!("Successfully restarted connection to {}", server_name),
+                Err(e) => tracing::error!("Failed to restart connection: {}", e),
+            }
+        });
+
+        Ok(RestartResponse {
+            status: "restarting",
+        })
+    }
+}
+
+/// A fresh connect URL for the current server, with a new launcher key.
+pub struct GetUrl;
+
+#[derive(Debug, Serialize)]
+pub struct GetUrlResponse {
+    pub url: String,
+}
+
+#[async_trait]
+impl Endpoint for GetUrl {
+    type Request = NoParams;
+    type Response = GetUrlResponse;
+    const PATH: &'static str = "get-url";
+    const RATE_LIMIT: RateLimit = RateLimit {
+        burst: 3,
+        per_minute: 6,
+    };
+    const AUDITED: bool = true;
+
+    async fn handle(&self, ctx: &RequestContext, _request: NoParams) -> ApiResult<GetUrlResponse> {
+        tracing::info!("Get URL request received");
+
+        let Some(params) = ctx
+            .presence_manager
+            .get_connection_params(&ctx.launcher_key)
+        else {
+            return Err(ApiError::bad_request("No previous connection available"));
+        };
+
+        let app_handle = &ctx.app_handle;
+        let fresh_params = refresh_auth_token(app_handle, params)
+            .await
+            .map_err(ApiError::internal)?;
+
+        let control_server = app_handle.try_state::<ControlServer>();
+        let control_port = control_server.as_ref().map(|s| s.port.to_string());
+        let new_key = control_server
+            .as_ref()
+            .map(|s| s.reissue_key(&ctx.launcher_key));
+        if let Some(new_key) = &new_key {
+            ctx.presence_manager
+                .rekey_session(&ctx.launcher_key, new_key);
+        }
+        let launcher_secret = new_key
+            .as_deref()
+            .and_then(|key| control_server.as_ref().and_then(|s| s.secret_for(key)));
+
+        // The WebSocket shares the control server's port
+        let url = crate::byond::build_connect_url(
+            &fresh_params.host,
+            &fresh_params.port,
+            &fresh_params.access_method,
+            control_port.as_deref(),
+            new_key.as_deref(),
+            launcher_secret.as_deref(),
+            control_port.as_deref(),
+        );
+
+        Ok(GetUrlResponse { url })
+    }
+}
+
+/// Whether the game's session is still tracked by the launcher.
+pub struct Status;
+
+#[derive(Debug, Serialize)]
+pub struct StatusResponse {
+    pub running: bool,
+    pub server_name: Option<String>,
+    pub hwid: Option<String>,
+    /// The version 1 hardware ID, while servers move their bans over.
+    pub legacy_hwid: Option<String>,
+}
+
+#[async_trait]
+impl Endpoint for Status {
+    type Request = NoParams;
+    type Response = StatusResponse;
+    const PATH: &'static str = "status";
+    const SIGNING: Signing = Signing::Legacy;
+
+    async fn handle(&self, ctx: &RequestContext, _request: NoParams) -> ApiResult<StatusResponse> {
+        let session = ctx.presence_manager.get_session_by_key(&ctx.launcher_key);
+        let hwid = ctx
+            .presence_manager
+            .get_connection_params(&ctx.launcher_key)
+            .and_then(|params| {
+                crate::hwid::hwid_for_server(&ctx.app_handle, crate::hwid::server_identity(&params))
+            });
+        let legacy_hwid = hwid
+            .is_some()
+            .then(|| crate::hwid::legacy_hwid(&ctx.app_handle))
+            .flatten();
+
+        Ok(StatusResponse {
+            running: session.is_some(),
+            server_name: session.map(|s| s.server_name),
+            hwid,
+            legacy_hwid,
+        })
+    }
+}
+
+/// A hub join ticket for the current server.
+pub struct AuthTicket;
+
+#[derive(Debug, Serialize)]
+pub struct AuthTicketResponse {
+    pub auth_ticket: String,
+}
+
+#[async_trait]
+impl Endpoint for AuthTicket {
+    type Request = NoParams;
+    type Response = AuthTicketResponse;
+    const PATH: &'static str = "auth-ticket";
+    const RATE_LIMIT: RateLimit = RateLimit {
+        burst: 3,
+        per_minute: 6,
+    };
+    const AUDITED: bool = true;
+
+    async fn handle(
+        &self,
+        ctx: &RequestContext,
+        _request: NoParams,
+    ) -> ApiResult<AuthTicketResponse> {
+        tracing::info!("Auth ticket request received");
+
+        let Some(params) = ctx
+            .presence_manager
+            .get_connection_params(&ctx.launcher_key)
+        else {
+            return Err(ApiError::bad_request("No previous connection available"));
+        };
+
+        let result: Result<String, String> = async {
+            let session_token = match crate::auth::TokenStorage::get_tokens() {
+                Ok(Some(tokens)) if !crate::auth::TokenStorage::is_expired() => tokens.access_token,
+                Ok(_) => return Err("Hub authentication expired or not available".to_string()),
+                Err(e) => return Err(format!("Failed to read auth tokens: {e}")),
+            };
+
+            let server_id = params.server_id.as_deref().ok_or("Server has no hub ID")?;
+
+            let hwid = crate::hwid::hwid_for_server(&ctx.app_handle, server_id);
+            let legacy_hwid = crate::hwid::legacy_hwid(&ctx.app_handle);
+
+            crate::auth::hub_client::HubClient::join(
+                &session_token,
+                server_id,
+                hwid.as_deref(),
+                legacy_hwid.as_deref(),
+            )
+            .await
+            .map_err(|e| format!("Failed to get auth ticket: {e}"))
+        }
+        .await;
+
+        match result {
+            Ok(auth_ticket) => Ok(AuthTicketResponse { auth_ticket }),
+            Err(e) => {
+                tracing::error!("Auth ticket request failed: {e}");
+                Err(ApiError::internal(e))
+            }
+        }
+    }
+}
+
+/// Move the player to another server, given by hub server id or address.
+pub struct Hop;
+
+#[derive(Debug, Deserialize)]
+pub struct HopRequest {
+    pub server_id: Option<String>,
+    pub address: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HopResponse {
+    pub status: HopStatus,
+    pub server_name: String,
+}
+
+#[async_trait]
+impl Endpoint for Hop {
+    type Request = HopRequest;
+    type Response = HopResponse;
+    const PATH: &'static str = "hop";
+    const RATE_LIMIT: RateLimit = RateLimit {
+        burst: 2,
+        per_minute: 4,
+    };
+    const AUDITED: bool = true;
+
+    async fn handle(&self, ctx: &RequestContext, request: HopRequest) -> ApiResult<HopResponse> {
+        let Some(params) = ctx
+            .presence_manager
+            .get_connection_params(&ctx.launcher_key)
+        else {
+            return Err(ApiError::bad_request("No previous connection available"));
+        };
+
+        let (status, destination) = crate::hop::request_hop(
+            &ctx.app_handle,
+            &ctx.launcher_key,
+            params,
+            request.server_id,
+            request.address,
+            request.reason,
+        )
+        .await
+        .map_err(|e| match e {
+            CommandError::NotFound(_) | CommandError::InvalidInput(_) => {
+                ApiError::bad_request(e.to_string())
+            }
+            _ => ApiError::internal(e.to_string()),
+        })?;
+
+        Ok(HopResponse {
+            status,
+            server_name: destination.server_name,
+        })
+    }
+}
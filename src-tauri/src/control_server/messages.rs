@@ -0,0 +1,84 @@
+//! JSON messages on the WebSocket channel between the launcher and the game.
+//!
+//! Every message is an object `{"type": "<snake_case name>", "data": {...}}`;
+//! `data` is left out for messages without a payload.
+//!
+//! The game must authenticate first by sending
+//! `{"type": "auth", "data": {"launcher_key": "...", "timestamp": "...",
+//! "nonce": "...", "signature": "..."}}` with the key it was launched with,
+//! signed like an HTTP request (see `security`) with method `WS` and target
+//! `auth`. The launcher answers with `connected` and then starts forwarding
+//! events, or with `error` and closes the socket.
+//!
+//! There is no friend activity message: the launcher has no way to see what
+//! friends are playing, as Steam doesn't expose other users' rich presence
+//! through the API we use.
+
+use serde::{Deserialize, Serialize};
+
+use crate::presence::GameStatus;
+
+/// Messages the game sends to the launcher.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum GameMessage {
+    Auth {
+        launcher_key: String,
+        timestamp: Option<String>,
+        nonce: Option<String>,
+        signature: Option<String>,
+    },
+    /// Changes to the game's state, e.g.
+    /// `{"type": "status", "data": {"in_lobby": false, "character": "John Doe"}}`.
+    /// Fields left out are unchanged.
+    Status(GameStatus),
+    /// The player is quitting. Sent just before the client closes, so the
+    /// launcher doesn't mistake the exit for a crash and reconnect.
+    Quit,
+    Ping,
+}
+
+/// A listed server, as sent in `servers_updated`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerSummary {
+    pub id: Option<String>,
+    pub name: String,
+    pub status: String,
+    pub players: i32,
+    pub map_name: Option<String>,
+}
+
+/// Messages the launcher sends to the game.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum LauncherMessage {
+    /// The game authenticated successfully.
+    Connected {
+        message: String,
+    },
+    Pong,
+    /// The user's launcher login was refreshed. Games holding an auth ticket
+    /// may want to fetch a new one from `/v1/auth-ticket`.
+    AuthRefreshed,
+    /// The user logged out or their login expired.
+    AuthExpired,
+    ServersUpdated {
+        servers: Vec<ServerSummary>,
+    },
+    /// The last message from the game was rejected.
+    Error {
+        message: String,
+    },
+}
+
+impl ServerSummary {
+    pub fn from_server(server: &crate::servers::Server) -> Self {
+        Self {
+            id: server.id.clone(),
+            name: server.name.clone(),
+            status: server.status.clone(),
+            players: server.players,
+            map_name: server.data.as_ref().map(|d| d.map_name.clone()),
+        }
+    }
+}
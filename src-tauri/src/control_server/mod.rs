@@ -0,0 +1,821 @@
+#![allow(clippy::unwrap_used)] // Mutex::lock().unwrap() is idiomatic - panic on poison
+
+use futures_util::{SinkExt, StreamExt};
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{self, HeaderMap, HeaderValue};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::upgrade::Upgraded;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use rand::Rng;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tauri::Emitter;
+#[cfg(feature = "steam")]
+use tauri::Manager;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use url::Url;
+
+use crate::presence::{ConnectionParams, PresenceManager};
+
+mod handlers;
+mod messages;
+mod routes;
+mod security;
+
+use handlers::Hello;
+use messages::GameMessage;
+pub use messages::{LauncherMessage, ServerSummary};
+use routes::{Endpoint, RequestContext, RouteTable};
+use security::ClientState;
+
+type Body = Full<Bytes>;
+
+/// Whether a page at `origin` may read control server responses. Only pages
+/// served from this machine qualify, which is where BYOND serves the game's
+/// own browser content from; websites open in the player's browser don't.
+fn is_allowed_origin(origin: &str) -> bool {
+    let Ok(url) = Url::parse(origin) else {
+        return false;
+    };
+    url.scheme() == "http"
+        && match url.host() {
+            Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+            Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+            Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+            None => false,
+        }
+}
+
+/// Add CORS headers for `origin` if it is allowed, echoing it back rather
+/// than allowing everyone.
+fn add_cors_headers(headers: &mut HeaderMap, origin: Option<&HeaderValue>) {
+    headers.insert(header::VARY, HeaderValue::from_static("Origin"));
+    let Some(origin) = origin.filter(|o| o.to_str().is_ok_and(is_allowed_origin)) else {
+        return;
+    };
+    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+    headers.insert(
+        header::ACCESS_CONTROL_ALLOW_METHODS,
+        HeaderValue::from_static("GET, OPTIONS"),
+    );
+    headers.insert(
+        header::ACCESS_CONTROL_ALLOW_HEADERS,
+        HeaderValue::from_static(
+            "Content-Type, Launcher-Key, Launcher-Timestamp, Launcher-Nonce, Launcher-Signature",
+        ),
+    );
+}
+
+fn json_response(status: u16, body: serde_json::Value) -> Response<Body> {
+    let mut response = Response::new(Body::from(body.to_string()));
+    *response.status_mut() =
+        StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+fn preflight_response() -> Response<Body> {
+    let mut response = Response::new(Body::default());
+    *response.status_mut() = StatusCode::NO_CONTENT;
+    response
+}
+
+fn is_websocket_upgrade(request: &Request<Incoming>) -> bool {
+    request
+        .headers()
+        .get(header::UPGRADE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("websocket"))
+}
+
+pub struct ControlServer {
+    /// Port for both HTTP requests and the WebSocket.
+    pub port: u16,
+
+    /// Launcher keys of running game clients, one per session.
+    /// Requests must include one of these in the `Launcher-Key` header.
+    launcher_keys: Arc<std::sync::Mutex<HashMap<String, ClientState>>>,
+
+    /// Messages for authenticated WebSocket clients
+    event_tx: broadcast::Sender<OutgoingMessage>,
+
+    /// Set to true to stop the server.
+    shutdown_tx: watch::Sender<bool>,
+    /// The accept loop, which finishes once every connection has closed.
+    task: std::sync::Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+}
+
+/// A serialized [`LauncherMessage`], for one client or all of them.
+#[derive(Debug, Clone)]
+struct OutgoingMessage {
+    launcher_key: Option<String>,
+    text: String,
+}
+
+/// How long a WebSocket client has to send its `auth` message.
+const WS_AUTH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// How long shutdown waits for in-flight requests and open sockets.
+const SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// What a connection needs from the launcher.
+#[derive(Clone)]
+struct ServerContext {
+    app_handle: tauri::AppHandle,
+    presence_manager: Arc<PresenceManager>,
+    launcher_keys: Arc<std::sync::Mutex<HashMap<String, ClientState>>>,
+    routes: Arc<RouteTable>,
+    event_tx: broadcast::Sender<OutgoingMessage>,
+    shutdown_rx: watch::Receiver<bool>,
+    port: u16,
+    /// Held by every open connection, so shutdown can wait for all of them
+    /// to be dropped.
+    _drain: mpsc::Sender<()>,
+}
+
+fn generate_launcher_key() -> String {
+    use rand::distributions::Alphanumeric;
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
+impl ControlServer {
+    pub fn start(
+        app_handle: tauri::AppHandle,
+        presence_manager: Arc<PresenceManager>,
+    ) -> crate::error::CommandResult<Self> {
+        use crate::error::CommandError;
+
+        tracing::info!("Starting control server on 127.0.0.1:0");
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| {
+                listener.set_nonblocking(true)?;
+                Ok(listener)
+            })
+            .map_err(|e| {
+                tracing::error!("Failed to start control server: {} ({:?})", e, e.kind());
+                tracing::error!(
+                    "This may be caused by: firewall blocking the connection, \
+                    antivirus software, or network configuration issues. \
+                    On Windows, check Windows Firewall settings and any third-party security software."
+                );
+                CommandError::Io(format!(
+                    "Failed to start control server: {e}. \
+                    Please check your firewall and antivirus settings."
+                ))
+            })?;
+
+        let addr = listener.local_addr().map_err(|e| {
+            tracing::error!("Failed to get control server address after binding: {}", e);
+            CommandError::Internal("control server address unavailable after bind".into())
+        })?;
+
+        let port = addr.port();
+        tracing::info!(
+            "Control server started successfully on {}:{} (HTTP and WebSocket)",
+            addr.ip(),
+            port
+        );
+
+        let (event_tx, _) = broadcast::channel::<OutgoingMessage>(32);
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let (drain_tx, drain_rx) = mpsc::channel::<()>(1);
+
+        let launcher_keys = Arc::new(std::sync::Mutex::new(HashMap::new()));
+
+        let mut routes = RouteTable::new();
+        handlers::register_v1(&mut routes);
+
+        let context = ServerContext {
+            app_handle,
+            presence_manager,
+            launcher_keys: Arc::clone(&launcher_keys),
+            routes: Arc::new(routes),
+            event_tx: event_tx.clone(),
+            shutdown_rx,
+            port,
+            _drain: drain_tx,
+        };
+
+        let task = tauri::async_runtime::spawn(Self::run_server(listener, context, drain_rx));
+
+        Ok(Self {
+            port,
+            launcher_keys,
+            event_tx,
+            shutdown_tx,
+            task: std::sync::Mutex::new(Some(task)),
+        })
+    }
+
+    /// Stop accepting connections, close the open ones and wait a little
+    /// for in-flight requests to finish.
+    pub fn shutdown(&self) {
+        self.shutdown_tx.send_replace(true);
+        let task = self.task.lock().unwrap().take();
+        if let Some(task) = task {
+            let finished =
+                tauri::async_runtime::block_on(tokio::time::timeout(SHUTDOWN_TIMEOUT, task));
+            if finished.is_err() {
+                tracing::warn!(
+                    "Control server connections still open after {:?}, exiting anyway",
+                    SHUTDOWN_TIMEOUT
+                );
+            }
+        }
+    }
+
+    /// Generate a launcher key for a new game client.
+    /// Returns the new key to be passed to the game.
+    pub fn issue_key(&self) -> String {
+        let new_key = generate_launcher_key();
+        self.launcher_keys
+            .lock()
+            .unwrap()
+            .insert(new_key.clone(), ClientState::new());
+        new_key
+    }
+
+    /// Secret the client holding `key` signs its requests with.
+    pub fn secret_for(&self, key: &str) -> Option<String> {
+        self.launcher_keys
+            .lock()
+            .unwrap()
+            .get(key)
+            .map(|client| client.secret().to_string())
+    }
+
+    /// Replace a session's launcher key and secret with new ones, keeping
+    /// its connected state. Used when the game asks for a fresh connect URL.
+    pub fn reissue_key(&self, old_key: &str) -> String {
+        let new_key = generate_launcher_key();
+        let mut keys = self.launcher_keys.lock().unwrap();
+        let mut client = ClientState::new();
+        client.connected = keys.remove(old_key).is_some_and(|old| old.connected);
+        keys.insert(new_key.clone(), client);
+        new_key
+    }
+
+    /// Whether the client holding `key` has reached the control server.
+    pub fn is_key_connected(&self, key: &str) -> bool {
+        self.launcher_keys
+            .lock()
+            .unwrap()
+            .get(key)
+            .is_some_and(|client| client.connected)
+    }
+
+    /// Stop accepting a launcher key once its game client has exited.
+    pub fn revoke_key(&self, key: &str) {
+        self.launcher_keys.lock().unwrap().remove(key);
+    }
+
+    /// Send a message to every authenticated WebSocket client.
+    pub fn broadcast(&self, message: &LauncherMessage) {
+        self.send(None, message);
+    }
+
+    /// Send a message to the WebSocket client holding `launcher_key`.
+    #[allow(dead_code)]
+    pub fn send_to(&self, launcher_key: &str, message: &LauncherMessage) {
+        self.send(Some(launcher_key.to_string()), message);
+    }
+
+    fn send(&self, launcher_key: Option<String>, message: &LauncherMessage) {
+        if self.event_tx.receiver_count() == 0 {
+            return;
+        }
+        let text = match serde_json::to_string(message) {
+            Ok(text) => text,
+            Err(e) => {
+                tracing::warn!("Failed to serialize WebSocket message: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = self.event_tx.send(OutgoingMessage { launcher_key, text }) {
+            tracing::warn!("Failed to broadcast event: {}", e);
+        }
+    }
+
+    async fn run_server(
+        listener: std::net::TcpListener,
+        context: ServerContext,
+        mut drain_rx: mpsc::Receiver<()>,
+    ) {
+        let listener = match TcpListener::from_std(listener) {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!("Failed to start control server listener: {}", e);
+                return;
+            }
+        };
+
+        let mut shutdown_rx = context.shutdown_rx.clone();
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, peer)) => {
+                        tokio::spawn(Self::serve_connection(stream, peer, context.clone()));
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to accept control server connection: {}", e);
+                    }
+                },
+                _ = shutdown_rx.changed() => break,
+            }
+        }
+
+        tracing::info!("Control server shutting down");
+        drop(listener);
+        drop(context);
+        // Returns once every connection has dropped its context
+        drain_rx.recv().await;
+        tracing::info!("Control server stopped");
+    }
+
+    async fn serve_connection(stream: TcpStream, peer: SocketAddr, context: ServerContext) {
+        let mut shutdown_rx = context.shutdown_rx.clone();
+        let service_context = context.clone();
+        let service = service_fn(move |request| {
+            let context = service_context.clone();
+            async move {
+                let origin = request.headers().get(header::ORIGIN).cloned();
+                let mut response = Self::handle_request(request, peer, context).await;
+                add_cors_headers(response.headers_mut(), origin.as_ref());
+                Ok::<_, Infallible>(response)
+            }
+        });
+
+        let connection = http1::Builder::new()
+            .serve_connection(TokioIo::new(stream), service)
+            .with_upgrades();
+        tokio::pin!(connection);
+
+        tokio::select! {
+            result = connection.as_mut() => {
+                if let Err(e) = result {
+                    tracing::debug!("Control server connection error: {}", e);
+                }
+                return;
+            }
+            _ = shutdown_rx.changed() => {}
+        }
+
+        // Let an in-flight request finish, then close
+        connection.as_mut().graceful_shutdown();
+        if let Err(e) = connection.await {
+            tracing::debug!("Control server connection error during shutdown: {}", e);
+        }
+    }
+
+    /// Accept a WebSocket upgrade and hand the socket to
+    /// [`Self::handle_websocket_connection`] once hyper releases it.
+    fn upgrade_websocket(
+        mut request: Request<Incoming>,
+        peer: SocketAddr,
+        context: ServerContext,
+    ) -> Response<Body> {
+        let Some(accept_key) = request
+            .headers()
+            .get(header::SEC_WEBSOCKET_KEY)
+            .map(|key| derive_accept_key(key.as_bytes()))
+            .and_then(|accept| HeaderValue::from_str(&accept).ok())
+        else {
+            return json_response(
+                400,
+                serde_json::json!({"error": "Invalid WebSocket request"}),
+            );
+        };
+
+        let on_upgrade = hyper::upgrade::on(&mut request);
+        tokio::spawn(async move {
+            match on_upgrade.await {
+                Ok(upgraded) => {
+                    tracing::info!("New WebSocket connection from {}", peer);
+                    let ws_stream = WebSocketStream::from_raw_socket(
+                        TokioIo::new(upgraded),
+                        Role::Server,
+                        None,
+                    )
+                    .await;
+                    Self::handle_websocket_connection(ws_stream, context).await;
+                }
+                Err(e) => tracing::error!("WebSocket handshake failed: {}", e),
+            }
+        });
+
+        let mut response = Response::new(Body::default());
+        *response.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+        let headers = response.headers_mut();
+        headers.insert(header::CONNECTION, HeaderValue::from_static("Upgrade"));
+        headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+        headers.insert(header::SEC_WEBSOCKET_ACCEPT, accept_key);
+        response
+    }
+
+    async fn handle_websocket_connection(
+        ws_stream: WebSocketStream<TokioIo<Upgraded>>,
+        context: ServerContext,
+    ) {
+        let mut event_rx = context.event_tx.subscribe();
+        let mut shutdown_rx = context.shutdown_rx.clone();
+        let (mut write, mut read) = ws_stream.split();
+
+        let first = tokio::time::timeout(WS_AUTH_TIMEOUT, read.next()).await;
+        let auth = match first {
+            Ok(Some(Ok(Message::Text(text)))) => serde_json::from_str::<GameMessage>(&text).ok(),
+            _ => None,
+        };
+        let verified = if let Some(GameMessage::Auth {
+            launcher_key,
+            timestamp,
+            nonce,
+            signature,
+        }) = auth
+        {
+            context
+                .launcher_keys
+                .lock()
+                .unwrap()
+                .get_mut(&launcher_key)
+                .is_some_and(|client| {
+                    client
+                        .verify_signature(
+                            "WS",
+                            "auth",
+                            timestamp.as_deref(),
+                            nonce.as_deref(),
+                            signature.as_deref(),
+                        )
+                        .is_ok()
+                })
+                .then_some(launcher_key)
+        } else {
+            None
+        };
+        let Some(launcher_key) = verified else {
+            tracing::warn!("WebSocket client rejected: invalid or missing auth");
+            let error = LauncherMessage::Error {
+                message: "Expected a signed auth message with a valid launcher key".to_string(),
+            };
+            if let Ok(text) = serde_json::to_string(&error) {
+                write.send(Message::Text(text)).await.ok();
+            }
+            write.send(Message::Close(None)).await.ok();
+            return;
+        };
+
+        let config = crate::config::get_config();
+        let welcome = LauncherMessage::Connected {
+            message: format!("Connected to {}", config.product_name),
+        };
+        if let Err(e) = Self::send_message(&mut write, &welcome).await {
+            tracing::error!("Failed to send welcome message: {}", e);
+            return;
+        }
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    write.send(Message::Close(None)).await.ok();
+                    break;
+                }
+                event = event_rx.recv() => {
+                    match event {
+                        Ok(msg) => {
+                            if msg.launcher_key.as_ref().is_some_and(|k| *k != launcher_key) {
+                                continue;
+                            }
+                            if let Err(e) = write.send(Message::Text(msg.text)).await {
+                                tracing::debug!("WebSocket send error (client disconnected): {}", e);
+                                break;
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            tracing::warn!("WebSocket client lagged, skipped {} messages", n);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => {
+                            break;
+                        }
+                    }
+                }
+                msg = read.next() => {
+                    match msg {
+                        Some(Ok(Message::Ping(data))) => {
+                            if let Err(e) = write.send(Message::Pong(data)).await {
+                                tracing::debug!("Failed to send pong: {}", e);
+                                break;
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) | None => {
+                            tracing::info!("WebSocket client disconnected");
+                            break;
+                        }
+                        Some(Ok(Message::Text(text))) => {
+                            let reply = Self::handle_game_message(&text, &launcher_key, &context);
+                            if let Some(reply) = reply {
+                                if let Err(e) = Self::send_message(&mut write, &reply).await {
+                                    tracing::debug!("WebSocket send error (client disconnected): {}", e);
+                                    break;
+                                }
+                            }
+                        }
+                        Some(Err(e)) => {
+                            tracing::debug!("WebSocket error: {}", e);
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    async fn send_message<S>(write: &mut S, message: &LauncherMessage) -> Result<(), String>
+    where
+        S: futures_util::Sink<Message> + Unpin,
+        S::Error: std::fmt::Display,
+    {
+        let text = serde_json::to_string(message).map_err(|e| e.to_string())?;
+        write
+            .send(Message::Text(text))
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Handle a message from an authenticated game. Returns the reply, if any.
+    fn handle_game_message(
+        text: &str,
+        launcher_key: &str,
+        context: &ServerContext,
+    ) -> Option<LauncherMessage> {
+        let message = match serde_json::from_str::<GameMessage>(text) {
+            Ok(message) => message,
+            Err(e) => {
+                tracing::debug!("Invalid WebSocket message: {}", e);
+                return Some(LauncherMessage::Error {
+                    message: format!("Invalid message: {e}"),
+                });
+            }
+        };
+
+        match message {
+            GameMessage::Auth { .. } => None,
+            GameMessage::Ping => Some(LauncherMessage::Pong),
+            GameMessage::Quit => {
+                if context.presence_manager.mark_session_quitting(launcher_key) {
+                    tracing::debug!("Game with key {} is quitting", launcher_key);
+                }
+                None
+            }
+            GameMessage::Status(status) => {
+                let session = context
+                    .presence_manager
+                    .update_session_status(launcher_key, status)?;
+                context.app_handle.emit("game-status", &session).ok();
+                None
+            }
+        }
+    }
+
+    async fn handle_request(
+        request: Request<Incoming>,
+        peer: SocketAddr,
+        context: ServerContext,
+    ) -> Response<Body> {
+        if request.method() == Method::OPTIONS {
+            return preflight_response();
+        }
+        if is_websocket_upgrade(&request) {
+            return Self::upgrade_websocket(request, peer, context);
+        }
+
+        let header = |name: &str| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned)
+        };
+        let target = request
+            .uri()
+            .path_and_query()
+            .map_or("/", |target| target.as_str());
+        let launcher_keys = &context.launcher_keys;
+        let presence_manager = &context.presence_manager;
+
+        // Validate the Launcher-Key header
+        let provided_key =
+            header("Launcher-Key").filter(|key| launcher_keys.lock().unwrap().contains_key(key));
+        let Some(key) = provided_key else {
+            tracing::warn!("Control server request rejected: invalid or missing Launcher-Key");
+            return json_response(403, serde_json::json!({"error": "Forbidden"}));
+        };
+
+        let full_url = format!("http://127.0.0.1{target}");
+        let url = match Url::parse(&full_url) {
+            Ok(url) => url,
+            Err(e) => {
+                tracing::error!("Failed to parse control server URL: {}", e);
+                return json_response(400, serde_json::json!({"error": e.to_string()}));
+            }
+        };
+
+        tracing::debug!("Control server received request: {}", url.path());
+
+        let Some((endpoint, policy)) = context.routes.policy(url.path()) else {
+            return json_response(404, serde_json::json!({"error": "Not found"}));
+        };
+
+        let port = context.port;
+        let bound_pid = launcher_keys
+            .lock()
+            .unwrap()
+            .get(&key)
+            .and_then(ClientState::bound_pid);
+        let peer_pid =
+            tokio::task::spawn_blocking(move || security::peer_pid(peer, port, bound_pid))
+                .await
+                .ok()
+                .flatten();
+        let session = presence_manager.get_session_by_key(&key);
+        let server_name = session.as_ref().map(|s| s.server_name.clone());
+
+        let checked = {
+            let mut keys = launcher_keys.lock().unwrap();
+            match keys.get_mut(&key) {
+                None => Err((403, "Forbidden".to_string())),
+                Some(client) => client
+                    .check_process(peer_pid, session.as_ref().map(|s| s.pid))
+                    .and_then(|()| {
+                        client.authorize(
+                            policy.signing,
+                            request.method().as_str(),
+                            target,
+                            header("Launcher-Timestamp").as_deref(),
+                            header("Launcher-Nonce").as_deref(),
+                            header("Launcher-Signature").as_deref(),
+                        )
+                    })
+                    .map_err(|failure| (401, failure.message().to_string()))
+                    .and_then(|()| {
+                        if client.check_rate(endpoint, policy.rate_limit) {
+                            Ok(!std::mem::replace(&mut client.connected, true))
+                        } else {
+                            Err((429, "Too many requests".to_string()))
+                        }
+                    }),
+            }
+        };
+        let first_request = match checked {
+            Ok(first_request) => first_request,
+            Err((status, message)) => {
+                tracing::warn!(
+                    "Control server request to {} rejected: {}",
+                    endpoint,
+                    message
+                );
+                if policy.audited {
+                    security::audit(
+                        endpoint,
+                        &key,
+                        server_name.as_deref(),
+                        peer_pid,
+                        &format!("denied: {message}"),
+                    );
+                }
+                return json_response(status, serde_json::json!({"error": message}));
+            }
+        };
+
+        let ctx = RequestContext {
+            app_handle: context.app_handle.clone(),
+            presence_manager: Arc::clone(presence_manager),
+            launcher_key: key.clone(),
+            first_request,
+            routes: Arc::clone(&context.routes),
+        };
+
+        // Game builds without `/hello` only prove they're connected by
+        // making some other request
+        if first_request && endpoint != Hello::PATH {
+            tracing::info!("Game connected to control server (no handshake)");
+            handlers::confirm_connected(&key, &context.app_handle, presence_manager);
+        }
+
+        let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        let result = context.routes.dispatch(url.path(), ctx, &query).await;
+        if policy.audited {
+            let outcome = match &result {
+                Some(Ok(_)) => "ok".to_string(),
+                Some(Err(e)) => format!("error: {}", e.message),
+                None => "not found".to_string(),
+            };
+            security::audit(endpoint, &key, server_name.as_deref(), peer_pid, &outcome);
+        }
+        match result {
+            Some(Ok(body)) => json_response(200, body),
+            Some(Err(e)) => json_response(e.status, serde_json::json!({"error": e.message})),
+            None => json_response(404, serde_json::json!({"error": "Not found"})),
+        }
+    }
+}
+
+#[allow(clippy::unused_async)]
+pub async fn refresh_auth_token(
+    #[allow(unused_variables)] app_handle: &tauri::AppHandle,
+    mut params: ConnectionParams,
+) -> Result<ConnectionParams, String> {
+    use crate::byond::AccessMethod;
+
+    match &params.access_method {
+        AccessMethod::Steam(_) => {
+            #[cfg(feature = "steam")]
+            {
+                tracing::info!("Refreshing Steam authentication token");
+                let steam_state = app_handle
+                    .try_state::<Arc<crate::steam::SteamState>>()
+                    .ok_or("Steam state not available")?;
+
+                let auth_result = crate::steam::authenticate_with_steam(&steam_state, false)
+                    .await
+                    .map_err(|e| e.to_string())?;
+
+                if !auth_result.success {
+                    return Err(auth_result
+                        .error
+                        .unwrap_or_else(|| "Steam authentication failed".to_string()));
+                }
+
+                if let Some(token) = auth_result.access_token {
+                    params.access_method = AccessMethod::Steam(token);
+                }
+                Ok(params)
+            }
+
+            #[cfg(not(feature = "steam"))]
+            {
+                Err("Steam feature not enabled".to_string())
+            }
+        }
+        AccessMethod::SessionToken { .. } | AccessMethod::HubTicket(_) => {
+            let config = crate::config::get_config();
+            tracing::info!(
+                "Fetching current {} access token",
+                config.strings.auth_provider_name
+            );
+            match crate::auth::TokenStorage::get_tokens().map_err(|e| e.to_string())? {
+                Some(tokens) if !crate::auth::TokenStorage::is_expired() => {
+                    params.access_method = AccessMethod::SessionToken {
+                        variant: config.variant.to_string(),
+                        token: tokens.access_token,
+                    };
+                    Ok(params)
+                }
+                _ => Err(format!(
+                    "{} authentication expired or not available",
+                    config.strings.auth_provider_name
+                )),
+            }
+        }
+        _ => Ok(params),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_allowed_origin;
+
+    #[test]
+    fn allows_loopback_origins() {
+        assert!(is_allowed_origin("http://127.0.0.1:51234"));
+        assert!(is_allowed_origin("http://localhost:8080"));
+        assert!(is_allowed_origin("http://[::1]:80"));
+    }
+
+    #[test]
+    fn refuses_other_origins() {
+        assert!(!is_allowed_origin("https://example.com"));
+        assert!(!is_allowed_origin("http://localhost.example.com"));
+        assert!(!is_allowed_origin("http://192.168.1.5:8080"));
+        assert!(!is_allowed_origin("null"));
+        assert!(!is_allowed_origin("file:///tmp/page.html"));
+    }
+}
@@ -0,0 +1,279 @@
+//! Versioned route table for the control server.
+//!
+//! Each game-to-launcher feature is an [`Endpoint`] with typed request and
+//! response structs, registered under an API version. The dispatcher only
+//! looks endpoints up by path, so new features just register themselves.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures_util::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::security::{RateLimit, Signing};
+use crate::presence::PresenceManager;
+
+/// Newest API version this launcher speaks.
+pub const CURRENT_API_VERSION: u32 = 1;
+
+/// Everything a handler gets to know about the request it is serving.
+pub struct RequestContext {
+    pub app_handle: tauri::AppHandle,
+    pub presence_manager: Arc<PresenceManager>,
+    pub launcher_key: String,
+    /// This is the first request made with `launcher_key`.
+    pub first_request: bool,
+    /// The table the request was dispatched from.
+    pub routes: Arc<RouteTable>,
+}
+
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: 400,
+            message: message.into(),
+        }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self {
+            status: 500,
+            message: message.into(),
+        }
+    }
+}
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+/// A control-server feature available to the game. Requests are handled
+/// concurrently, so handlers may await slow work such as hub calls.
+#[async_trait]
+pub trait Endpoint: Send + Sync + 'static {
+    /// Query parameters, deserialized from string values.
+    type Request: DeserializeOwned + Send;
+    type Response: Serialize + Send;
+
+    /// Path below the version prefix, e.g. `restart` for `/v1/restart`.
+    const PATH: &'static str;
+    /// Name advertised by `/capabilities`.
+    const CAPABILITY: &'static str = Self::PATH;
+    /// How requests are checked against the session secret.
+    const SIGNING: Signing = Signing::Required;
+    const RATE_LIMIT: RateLimit = DEFAULT_RATE_LIMIT;
+    /// Requests are recorded in the audit log.
+    const AUDITED: bool = false;
+
+    async fn handle(
+        &self,
+        ctx: &RequestContext,
+        request: Self::Request,
+    ) -> ApiResult<Self::Response>;
+}
+
+type Handler = Box<
+    dyn Fn(
+            Arc<RequestContext>,
+            &HashMap<String, String>,
+        ) -> BoxFuture<'static, ApiResult<serde_json::Value>>
+        + Send
+        + Sync,
+>;
+
+/// How requests to an endpoint are checked before it runs.
+#[derive(Debug, Clone, Copy)]
+pub struct RoutePolicy {
+    pub signing: Signing,
+    pub rate_limit: RateLimit,
+    pub audited: bool,
+}
+
+const DEFAULT_RATE_LIMIT: RateLimit = RateLimit {
+    burst: 10,
+    per_minute: 60,
+};
+
+struct Route {
+    path: &'static str,
+    capability: &'static str,
+    policy: RoutePolicy,
+    handler: Handler,
+}
+
+/// Response of `/capabilities`.
+#[derive(Debug, Serialize)]
+pub struct CapabilitiesResponse {
+    pub launcher_version: &'static str,
+    pub api_versions: Vec<u32>,
+    pub current_version: u32,
+    /// Capabilities per API version.
+    pub features: HashMap<u32, Vec<&'static str>>,
+    /// How signed requests are signed.
+    pub signing: &'static str,
+    /// Capabilities that check signatures, per API version. Only `hello` and
+    /// `status` take unsigned requests, and only until the client signs one.
+    pub signed_features: HashMap<u32, Vec<&'static str>>,
+}
+
+#[derive(Default)]
+pub struct RouteTable {
+    routes: HashMap<(u32, &'static str), Route>,
+}
+
+impl RouteTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `endpoint` under API `version`, replacing any endpoint
+    /// already at that path.
+    pub fn register<E: Endpoint>(&mut self, version: u32, endpoint: E) {
+        let endpoint = Arc::new(endpoint);
+        let handler: Handler = Box::new(move |ctx, query| {
+            let params = query
+                .iter()
+                .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
+                .collect();
+            let request = serde_json::from_value::<E::Request>(serde_json::Value::Object(params))
+                .map_err(|e| ApiError::bad_request(format!("Invalid request: {e}")));
+            let endpoint = Arc::clone(&endpoint);
+            Box::pin(async move {
+                let response = endpoint.handle(&ctx, request?).await?;
+                serde_json::to_value(response)
+                    .map_err(|e| ApiError::internal(format!("Failed to serialize response: {e}")))
+            })
+        });
+        self.routes.insert(
+            (version, E::PATH),
+            Route {
+                path: E::PATH,
+                capability: E::CAPABILITY,
+                policy: RoutePolicy {
+                    signing: E::SIGNING,
+                    rate_limit: E::RATE_LIMIT,
+                    audited: E::AUDITED,
+                },
+                handler,
+            },
+        );
+    }
+
+    /// Split a request path into its API version and endpoint path.
+    /// Unversioned paths are v1, which is what games predating versioning
+    /// use.
+    pub fn parse_path(path: &str) -> Option<(u32, &str)> {
+        let path = path.trim_start_matches('/');
+        if let Some(rest) = path.strip_prefix('v') {
+            if let Some((version, endpoint)) = rest.split_once('/') {
+                if let Ok(version) = version.parse() {
+                    return Some((version, endpoint));
+                }
+            }
+        }
+        (!path.is_empty()).then_some((1, path))
+    }
+
+    /// The endpoint at `path`, as `(name, policy)`.
+    pub fn policy(&self, path: &str) -> Option<(&'static str, RoutePolicy)> {
+        let (version, endpoint) = Self::parse_path(path)?;
+        self.routes
+            .get(&(version, endpoint))
+            .map(|route| (route.path, route.policy))
+    }
+
+    /// Run the endpoint at `path`. Returns `None` if there is none.
+    pub async fn dispatch(
+        &self,
+        path: &str,
+        ctx: RequestContext,
+        query: &HashMap<String, String>,
+    ) -> Option<ApiResult<serde_json::Value>> {
+        let (version, endpoint) = Self::parse_path(path)?;
+        let route = self.routes.get(&(version, endpoint))?;
+        Some((route.handler)(Arc::new(ctx), query).await)
+    }
+
+    pub fn capabilities(&self) -> CapabilitiesResponse {
+        let mut features: HashMap<u32, Vec<&'static str>> = HashMap::new();
+        let mut signed_features: HashMap<u32, Vec<&'static str>> = HashMap::new();
+        for ((version, _), route) in &self.routes {
+            features.entry(*version).or_default().push(route.capability);
+            if route.policy.signing != Signing::None {
+                signed_features
+                    .entry(*version)
+                    .or_default()
+                    .push(route.capability);
+            }
+        }
+        for names in features.values_mut().chain(signed_features.values_mut()) {
+            names.sort_unstable();
+            names.dedup();
+        }
+        let api_versions: BTreeSet<u32> = features.keys().copied().collect();
+
+        CapabilitiesResponse {
+            launcher_version: env!("CARGO_PKG_VERSION"),
+            api_versions: api_versions.into_iter().collect(),
+            current_version: CURRENT_API_VERSION,
+            features,
+            signing: "hmac-sha256",
+            signed_features,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RouteTable, Signing};
+
+    #[test]
+    fn parses_versioned_paths() {
+        assert_eq!(RouteTable::parse_path("/v1/status"), Some((1, "status")));
+        assert_eq!(
+            RouteTable::parse_path("/v2/auth-ticket"),
+            Some((2, "auth-ticket"))
+        );
+    }
+
+    #[test]
+    fn unversioned_paths_are_v1() {
+        assert_eq!(RouteTable::parse_path("/restart"), Some((1, "restart")));
+        assert_eq!(RouteTable::parse_path("get-url"), Some((1, "get-url")));
+    }
+
+    #[test]
+    fn non_numeric_version_is_a_path() {
+        assert_eq!(RouteTable::parse_path("/vx/status"), Some((1, "vx/status")));
+        assert_eq!(RouteTable::parse_path("/version"), Some((1, "version")));
+    }
+
+    #[test]
+    fn capabilities_is_a_registered_unsigned_route() {
+        let mut routes = RouteTable::new();
+        super::super::handlers::register_v1(&mut routes);
+
+        let (name, policy) = routes
+            .policy("/capabilities")
+            .unwrap_or_else(|| unreachable!());
+        assert_eq!(name, "capabilities");
+        assert_eq!(policy.signing, Signing::None);
+        assert!(routes
+            .capabilities()
+            .features
+            .get(&1)
+            .is_some_and(|names| names.contains(&"capabilities")));
+    }
+
+    #[test]
+    fn empty_path_has_no_endpoint() {
+        assert_eq!(RouteTable::parse_path("/"), None);
+        assert_eq!(RouteTable::parse_path(""), None);
+    }
+}
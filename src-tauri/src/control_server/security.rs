@@ -0,0 +1,606 @@
+//! Per-session request authentication, replay protection and rate limiting.
+//!
+//! Each game session gets a launcher key, which names the session, and a
+//! secret, which is passed to the game in its connect URL as
+//! `launcher_secret`. Requests to endpoints that need it are signed with:
+//!
+//! ```text
+//! Launcher-Timestamp: <unix seconds>
+//! Launcher-Nonce: <random string, at most 64 characters, never reused>
+//! Launcher-Signature: hex(HMAC-SHA256(secret, "<METHOD>\n<path?query>\n<timestamp>\n<nonce>"))
+//! ```
+//!
+//! where `path?query` is the request target exactly as sent.
+//!
+//! Endpoints that can act for the player (`restart`, `get-url`,
+//! `auth-ticket`, `hop`) always need a signature. Game builds from before
+//! signing only send `Launcher-Key`, so the read-only `hello` and `status`
+//! let a client that has never signed a request through on its key alone.
+//! Once a client signs one request it has opted in, and those must be signed
+//! too.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::Instant;
+
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha2::Sha256;
+
+/// How far a request's timestamp may be from our clock.
+const MAX_CLOCK_SKEW_SECS: i64 = 60;
+const MAX_NONCE_LEN: usize = 64;
+const SECRET_LEN: usize = 32;
+
+const AUDIT_LOG_FILE: &str = "control_audit.log";
+
+/// Requests allowed per client for one endpoint: up to `burst` at once,
+/// refilling at `per_minute`.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    pub burst: u32,
+    pub per_minute: u32,
+}
+
+/// How an endpoint checks request signatures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signing {
+    /// Signatures are never checked.
+    None,
+    /// Unsigned requests are allowed until the client first signs one.
+    Legacy,
+    /// Every request must be signed.
+    Required,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthFailure {
+    MissingSignature,
+    StaleTimestamp,
+    ReplayedNonce,
+    BadSignature,
+    WrongProcess,
+}
+
+impl AuthFailure {
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::MissingSignature => "Request must be signed",
+            Self::StaleTimestamp => "Request timestamp is missing or too far from the current time",
+            Self::ReplayedNonce => "Request nonce is missing or was already used",
+            Self::BadSignature => "Invalid request signature",
+            Self::WrongProcess => "Request did not come from the game client",
+        }
+    }
+}
+
+struct Bucket {
+    /// Thousandths of a request.
+    tokens: u64,
+    updated: Instant,
+}
+
+/// Everything the control server tracks about one game client.
+pub struct ClientState {
+    /// The client has made its first request.
+    pub connected: bool,
+    secret: String,
+    /// The client has signed a request, so it must sign from now on.
+    signing: bool,
+    /// Process the client's requests come from, once known.
+    bound_pid: Option<u32>,
+    /// Nonces seen within the clock skew window, with their timestamps.
+    nonces: HashMap<String, i64>,
+    buckets: HashMap<&'static str, Bucket>,
+}
+
+impl ClientState {
+    pub fn new() -> Self {
+        use rand::distributions::Alphanumeric;
+        let secret = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(SECRET_LEN)
+            .map(char::from)
+            .collect();
+        Self {
+            connected: false,
+            secret,
+            signing: false,
+            bound_pid: None,
+            nonces: HashMap::new(),
+            buckets: HashMap::new(),
+        }
+    }
+
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+
+    /// Process the client's requests come from, once known.
+    pub fn bound_pid(&self) -> Option<u32> {
+        self.bound_pid
+    }
+
+    /// Check a request to an endpoint with the given `signing` policy.
+    pub fn authorize(
+        &mut self,
+        signing: Signing,
+        method: &str,
+        target: &str,
+        timestamp: Option<&str>,
+        nonce: Option<&str>,
+        signature: Option<&str>,
+    ) -> Result<(), AuthFailure> {
+        match signing {
+            Signing::None => return Ok(()),
+            Signing::Legacy if signature.is_none() && !self.signing => return Ok(()),
+            Signing::Legacy | Signing::Required => {}
+        }
+        self.verify_signature(method, target, timestamp, nonce, signature)?;
+        self.signing = true;
+        Ok(())
+    }
+
+    /// Check a request's signature headers, remembering its nonce.
+    pub fn verify_signature(
+        &mut self,
+        method: &str,
+        target: &str,
+        timestamp: Option<&str>,
+        nonce: Option<&str>,
+        signature: Option<&str>,
+    ) -> Result<(), AuthFailure> {
+        let Some(signature) = signature else {
+            return Err(AuthFailure::MissingSignature);
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        let timestamp_secs = timestamp
+            .and_then(|t| t.parse::<i64>().ok())
+            .filter(|t| t.abs_diff(now) <= MAX_CLOCK_SKEW_SECS.unsigned_abs())
+            .ok_or(AuthFailure::StaleTimestamp)?;
+
+        let nonce = nonce
+            .filter(|n| !n.is_empty() && n.len() <= MAX_NONCE_LEN)
+            .ok_or(AuthFailure::ReplayedNonce)?;
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes())
+            .map_err(|_| AuthFailure::BadSignature)?;
+        mac.update(
+            format!(
+                "{method}\n{target}\n{}\n{nonce}",
+                timestamp.unwrap_or_default()
+            )
+            .as_bytes(),
+        );
+        let signature = hex::decode(signature).map_err(|_| AuthFailure::BadSignature)?;
+        mac.verify_slice(&signature)
+            .map_err(|_| AuthFailure::BadSignature)?;
+
+        // Old nonces can't be replayed anyway once their timestamp is stale
+        self.nonces
+            .retain(|_, seen| seen.abs_diff(now) <= MAX_CLOCK_SKEW_SECS.unsigned_abs());
+        if self
+            .nonces
+            .insert(nonce.to_string(), timestamp_secs)
+            .is_some()
+        {
+            return Err(AuthFailure::ReplayedNonce);
+        }
+
+        Ok(())
+    }
+
+    /// Take one request from `endpoint`'s budget. Returns false if it is
+    /// used up.
+    pub fn check_rate(&mut self, endpoint: &'static str, limit: RateLimit) -> bool {
+        let capacity = u64::from(limit.burst).saturating_mul(1000);
+        let now = Instant::now();
+        let bucket = self.buckets.entry(endpoint).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+
+        let elapsed_ms =
+            u64::try_from(now.duration_since(bucket.updated).as_millis()).unwrap_or(u64::MAX);
+        let refill = elapsed_ms.saturating_mul(u64::from(limit.per_minute)) / 60;
+        bucket.tokens = bucket.tokens.saturating_add(refill).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens < 1000 {
+            return false;
+        }
+        bucket.tokens = bucket.tokens.saturating_sub(1000);
+        true
+    }
+
+    /// Check that a request came from the game. The first process seen is
+    /// remembered and later requests must come from it too. If the game's own
+    /// PID is known the first process must also belong to it.
+    pub fn check_process(
+        &mut self,
+        peer_pid: Option<u32>,
+        session_pid: Option<u32>,
+    ) -> Result<(), AuthFailure> {
+        let Some(peer_pid) = peer_pid else {
+            return Ok(());
+        };
+        match self.bound_pid {
+            Some(bound) if bound == peer_pid => Ok(()),
+            Some(_) => Err(AuthFailure::WrongProcess),
+            None => {
+                if let Some(session_pid) = session_pid {
+                    if !is_in_process_tree(peer_pid, session_pid) {
+                        return Err(AuthFailure::WrongProcess);
+                    }
+                }
+                self.bound_pid = Some(peer_pid);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Whether `pid` is `root` or one of its descendants. Only the processes on
+/// the way up to `root` are read, not the whole process table.
+fn is_in_process_tree(pid: u32, root: u32) -> bool {
+    let mut system = sysinfo::System::new();
+    let root = sysinfo::Pid::from_u32(root);
+    let mut current = Some(sysinfo::Pid::from_u32(pid));
+    // Bounded in case of a cycle from PID reuse
+    for _ in 0..64 {
+        let Some(pid) = current else {
+            return false;
+        };
+        if pid == root {
+            return true;
+        }
+        system.refresh_processes_specifics(
+            sysinfo::ProcessesToUpdate::Some(&[pid]),
+            false,
+            sysinfo::ProcessRefreshKind::nothing(),
+        );
+        current = system.process(pid).and_then(sysinfo::Process::parent);
+    }
+    false
+}
+
+/// Inode of the client end of a connection to the control server on
+/// `server_port`.
+#[cfg(target_os = "linux")]
+fn socket_inode(peer: std::net::SocketAddr, server_port: u16) -> Option<String> {
+    ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .find_map(|table| {
+            table.lines().skip(1).find_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let local_port = fields.get(1)?.rsplit(':').next()?;
+                let remote_port = fields.get(2)?.rsplit(':').next()?;
+                (u16::from_str_radix(local_port, 16).ok()? == peer.port()
+                    && u16::from_str_radix(remote_port, 16).ok()? == server_port)
+                    .then(|| fields.get(9).map(|inode| (*inode).to_string()))
+                    .flatten()
+            })
+        })
+}
+
+/// Whether process `pid` has `socket` (as `socket:[inode]`) open.
+#[cfg(target_os = "linux")]
+fn owns_socket(pid: u32, socket: &str) -> bool {
+    std::fs::read_dir(format!("/proc/{pid}/fd")).is_ok_and(|fds| {
+        fds.flatten().any(|fd| {
+            std::fs::read_link(fd.path()).is_ok_and(|link| link.to_string_lossy() == socket)
+        })
+    })
+}
+
+/// PID of the local process that owns the client end of a connection to the
+/// control server on `server_port`, where the platform lets us find it.
+/// `known` is checked first, so a client's usual process is found without
+/// scanning every process.
+#[cfg(target_os = "linux")]
+pub fn peer_pid(peer: std::net::SocketAddr, server_port: u16, known: Option<u32>) -> Option<u32> {
+    let socket = format!("socket:[{}]", socket_inode(peer, server_port)?);
+    if let Some(pid) = known.filter(|pid| owns_socket(*pid, &socket)) {
+        return Some(pid);
+    }
+
+    std::fs::read_dir("/proc")
+        .ok()?
+        .flatten()
+        .find_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            owns_socket(pid, &socket).then_some(pid)
+        })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn peer_pid(
+    _peer: std::net::SocketAddr,
+    _server_port: u16,
+    _known: Option<u32>,
+) -> Option<u32> {
+    None
+}
+
+/// Record a sensitive request, such as a ticket or restart, in the audit log.
+pub fn audit(
+    endpoint: &str,
+    launcher_key: &str,
+    server_name: Option<&str>,
+    peer_pid: Option<u32>,
+    outcome: &str,
+) {
+    // Enough to tell sessions apart without writing the key itself to disk
+    let key_id: String = launcher_key.chars().take(6).collect();
+    tracing::info!(
+        "[audit] endpoint={} session={} server={:?} pid={:?} outcome={}",
+        endpoint,
+        key_id,
+        server_name,
+        peer_pid,
+        outcome
+    );
+
+    let entry = serde_json::json!({
+        "time": chrono::Utc::now().to_rfc3339(),
+        "endpoint": endpoint,
+        "session": key_id,
+        "server_name": server_name,
+        "pid": peer_pid,
+        "outcome": outcome,
+    });
+    let path = crate::logging::get_log_directory().join(AUDIT_LOG_FILE);
+    let result = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{entry}"));
+    if let Err(e) = result {
+        tracing::warn!("Failed to write control server audit log: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuthFailure, ClientState, RateLimit, Signing, MAX_CLOCK_SKEW_SECS};
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    fn sign(
+        client: &ClientState,
+        method: &str,
+        target: &str,
+        timestamp: &str,
+        nonce: &str,
+    ) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(client.secret().as_bytes())
+            .unwrap_or_else(|_| unreachable!("HMAC accepts keys of any length"));
+        mac.update(format!("{method}\n{target}\n{timestamp}\n{nonce}").as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn now() -> String {
+        chrono::Utc::now().timestamp().to_string()
+    }
+
+    #[test]
+    fn accepts_valid_signature() {
+        let mut client = ClientState::new();
+        let ts = now();
+        let sig = sign(&client, "GET", "/v1/status", &ts, "n1");
+        assert_eq!(
+            client.verify_signature("GET", "/v1/status", Some(&ts), Some("n1"), Some(&sig)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_signature_for_other_request() {
+        let mut client = ClientState::new();
+        let ts = now();
+        let sig = sign(&client, "GET", "/v1/status", &ts, "n1");
+        assert_eq!(
+            client.verify_signature("GET", "/v1/restart", Some(&ts), Some("n1"), Some(&sig)),
+            Err(AuthFailure::BadSignature)
+        );
+    }
+
+    #[test]
+    fn rejects_signature_from_other_secret() {
+        let mut client = ClientState::new();
+        let other = ClientState::new();
+        let ts = now();
+        let sig = sign(&other, "GET", "/v1/status", &ts, "n1");
+        assert_eq!(
+            client.verify_signature("GET", "/v1/status", Some(&ts), Some("n1"), Some(&sig)),
+            Err(AuthFailure::BadSignature)
+        );
+        assert_eq!(
+            client.verify_signature("GET", "/v1/status", Some(&ts), Some("n2"), Some("not hex")),
+            Err(AuthFailure::BadSignature)
+        );
+    }
+
+    #[test]
+    fn rejects_missing_signature() {
+        let mut client = ClientState::new();
+        assert_eq!(
+            client.verify_signature("GET", "/v1/status", Some(&now()), Some("n1"), None),
+            Err(AuthFailure::MissingSignature)
+        );
+    }
+
+    #[test]
+    fn rejects_skewed_timestamps() {
+        let mut client = ClientState::new();
+        let now = chrono::Utc::now().timestamp();
+        let skew = MAX_CLOCK_SKEW_SECS.saturating_add(5);
+        for ts in [now.saturating_sub(skew), now.saturating_add(skew)] {
+            let ts = ts.to_string();
+            let sig = sign(&client, "GET", "/v1/status", &ts, "n1");
+            assert_eq!(
+                client.verify_signature("GET", "/v1/status", Some(&ts), Some("n1"), Some(&sig)),
+                Err(AuthFailure::StaleTimestamp)
+            );
+        }
+        let sig = sign(&client, "GET", "/v1/status", "soon", "n1");
+        assert_eq!(
+            client.verify_signature("GET", "/v1/status", Some("soon"), Some("n1"), Some(&sig)),
+            Err(AuthFailure::StaleTimestamp)
+        );
+    }
+
+    #[test]
+    fn accepts_timestamps_within_skew() {
+        let mut client = ClientState::new();
+        let ts = chrono::Utc::now()
+            .timestamp()
+            .saturating_sub(MAX_CLOCK_SKEW_SECS.saturating_sub(5))
+            .to_string();
+        let sig = sign(&client, "GET", "/v1/status", &ts, "n1");
+        assert_eq!(
+            client.verify_signature("GET", "/v1/status", Some(&ts), Some("n1"), Some(&sig)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_replayed_nonce() {
+        let mut client = ClientState::new();
+        let ts = now();
+        let sig = sign(&client, "GET", "/v1/status", &ts, "once");
+        assert_eq!(
+            client.verify_signature("GET", "/v1/status", Some(&ts), Some("once"), Some(&sig)),
+            Ok(())
+        );
+        assert_eq!(
+            client.verify_signature("GET", "/v1/status", Some(&ts), Some("once"), Some(&sig)),
+            Err(AuthFailure::ReplayedNonce)
+        );
+    }
+
+    #[test]
+    fn rejects_empty_and_oversized_nonces() {
+        let mut client = ClientState::new();
+        let ts = now();
+        let long = "n".repeat(65);
+        for nonce in [None, Some(""), Some(long.as_str())] {
+            let sig = sign(&client, "GET", "/v1/status", &ts, nonce.unwrap_or_default());
+            assert_eq!(
+                client.verify_signature("GET", "/v1/status", Some(&ts), nonce, Some(&sig)),
+                Err(AuthFailure::ReplayedNonce)
+            );
+        }
+    }
+
+    #[test]
+    fn unsigned_requests_allowed_until_client_signs() {
+        let mut client = ClientState::new();
+        assert_eq!(
+            client.authorize(Signing::Legacy, "GET", "/status", None, None, None),
+            Ok(())
+        );
+
+        let ts = now();
+        let sig = sign(&client, "GET", "/status", &ts, "n1");
+        assert_eq!(
+            client.authorize(
+                Signing::Legacy,
+                "GET",
+                "/status",
+                Some(&ts),
+                Some("n1"),
+                Some(&sig)
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            client.authorize(Signing::Legacy, "GET", "/status", None, None, None),
+            Err(AuthFailure::MissingSignature)
+        );
+    }
+
+    #[test]
+    fn required_endpoints_reject_unsigned_requests() {
+        let mut client = ClientState::new();
+        assert_eq!(
+            client.authorize(Signing::Required, "GET", "/restart", None, None, None),
+            Err(AuthFailure::MissingSignature)
+        );
+
+        let ts = now();
+        let sig = sign(&client, "GET", "/restart", &ts, "n1");
+        assert_eq!(
+            client.authorize(
+                Signing::Required,
+                "GET",
+                "/restart",
+                Some(&ts),
+                Some("n1"),
+                Some(&sig)
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn bad_signature_does_not_opt_in() {
+        let mut client = ClientState::new();
+        assert_eq!(
+            client.authorize(
+                Signing::Legacy,
+                "GET",
+                "/status",
+                Some(&now()),
+                Some("n1"),
+                Some("00")
+            ),
+            Err(AuthFailure::BadSignature)
+        );
+        assert_eq!(
+            client.authorize(Signing::Legacy, "GET", "/status", None, None, None),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rate_limit_allows_burst_then_refuses() {
+        let mut client = ClientState::new();
+        let limit = RateLimit {
+            burst: 2,
+            per_minute: 0,
+        };
+        assert!(client.check_rate("restart", limit));
+        assert!(client.check_rate("restart", limit));
+        assert!(!client.check_rate("restart", limit));
+        // Each endpoint has its own budget
+        assert!(client.check_rate("status", limit));
+    }
+
+    #[test]
+    fn rate_limit_refills() {
+        let mut client = ClientState::new();
+        let limit = RateLimit {
+            burst: 1,
+            per_minute: 60_000,
+        };
+        assert!(client.check_rate("status", limit));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(client.check_rate("status", limit));
+    }
+
+    #[test]
+    fn binds_first_process() {
+        let mut client = ClientState::new();
+        assert_eq!(client.check_process(None, None), Ok(()));
+        assert_eq!(client.check_process(Some(100), None), Ok(()));
+        assert_eq!(client.bound_pid(), Some(100));
+        assert_eq!(client.check_process(Some(100), None), Ok(()));
+        assert_eq!(
+            client.check_process(Some(101), None),
+            Err(AuthFailure::WrongProcess)
+        );
+    }
+}
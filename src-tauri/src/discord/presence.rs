@@ -151,19 +151,24 @@ impl DiscordState {
                     server_name,
                     player_count,
                     map_name,
+                    details,
                 } => {
-                    let details = match map_name {
+                    let summary = details.activity().unwrap_or_else(|| match map_name {
                         Some(map) => format!("{player_count} players on {map}"),
                         None => format!("{player_count} players online"),
-                    };
+                    });
 
                     let game_name = crate::config::get_config().strings.discord_game_name;
                     #[allow(unused_mut)]
                     let mut activity = ActivityBuilder::new()
                         .state(format!("Playing on {server_name}"))
-                        .details(details)
+                        .details(summary)
                         .assets(Assets::default().large("logo", Some(game_name)));
 
+                    if let Some(started_at) = details.round_started_at {
+                        activity = activity.start_timestamp(started_at);
+                    }
+
                     // Only add join button when Steam feature is enabled (provides valid URL)
                     #[cfg(feature = "steam")]
                     {
@@ -0,0 +1,463 @@
+//! Server-initiated hops, where the game asks the launcher to move the player
+//! to another server such as a linked shard or an event server.
+//!
+//! Targets are resolved the same way as a direct connect. Listed servers and
+//! trusted addresses are joined straight away. For anything else the user is
+//! asked first, through a `game-hop-confirm` event answered by
+//! `respond_to_server_hop`. Unanswered prompts expire after
+//! [`HOP_CONFIRM_TTL`].
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::byond::{AccessMethod, ConnectionRequest, DirectConnectTrust};
+use crate::error::{CommandError, CommandResult};
+use crate::presence::{ConnectionParams, PresenceManager};
+use crate::servers::{EngineRequirements, Server, ServerState};
+use crate::settings::AppSettings;
+
+/// Where a hop is going, as shown to the user.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct HopDestination {
+    pub hop_id: String,
+    pub from_server: String,
+    pub server_name: String,
+    /// `host:port` of the target.
+    pub address: String,
+    pub server_id: Option<String>,
+    pub trust: DirectConnectTrust,
+    pub verified_domain: Option<String>,
+    /// Why the server sent the player here, if it said.
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HopStatus {
+    Hopping,
+    ConfirmationRequired,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HopFailedEvent {
+    pub server_name: String,
+    pub message: String,
+}
+
+/// How long a hop waits for the user to confirm it.
+const HOP_CONFIRM_TTL: Duration = Duration::from_mins(2);
+
+/// Hops that may wait on the user at once, across all games. Each game only
+/// gets one.
+const MAX_PENDING_HOPS: usize = 4;
+
+struct PendingHop {
+    requested_at: Instant,
+    destination: HopDestination,
+    launcher_key: String,
+    params: ConnectionParams,
+    host: String,
+    port: String,
+    map_name: Option<String>,
+    engine: Option<EngineRequirements>,
+}
+
+/// Hops waiting for the user to confirm, by hop id.
+#[derive(Default)]
+pub struct HopState {
+    pending: Mutex<HashMap<String, PendingHop>>,
+}
+
+impl HopState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue `hop` for confirmation, replacing any hop its game already has
+    /// waiting and dropping expired ones.
+    fn add_pending(&self, hop: PendingHop) -> CommandResult<()> {
+        let mut hops = self.pending.lock();
+        hops.retain(|_, pending| {
+            pending.launcher_key != hop.launcher_key
+                && pending.requested_at.elapsed() < HOP_CONFIRM_TTL
+        });
+        if hops.len() >= MAX_PENDING_HOPS {
+            return Err(CommandError::Busy {
+                operation: "hop confirmation".into(),
+            });
+        }
+        hops.insert(hop.destination.hop_id.clone(), hop);
+        Ok(())
+    }
+
+    /// Take the pending hop `hop_id`, unless it has expired.
+    fn take_pending(&self, hop_id: &str) -> CommandResult<PendingHop> {
+        self.pending
+            .lock()
+            .remove(hop_id)
+            .filter(|hop| hop.requested_at.elapsed() < HOP_CONFIRM_TTL)
+            .ok_or_else(|| CommandError::NotFound(format!("hop '{hop_id}'")))
+    }
+}
+
+fn split_address(address: &str) -> CommandResult<(String, String)> {
+    let address = address.strip_prefix("byond://").unwrap_or(address);
+    match address.split_once(':') {
+        Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => {
+            Ok((host.to_string(), port.to_string()))
+        }
+        _ => Err(CommandError::InvalidInput(format!(
+            "Invalid address format, expected host:port: {address}"
+        ))),
+    }
+}
+
+/// Same rules the direct connect dialog uses to skip its confirmation.
+fn is_trusted(
+    trust: &DirectConnectTrust,
+    address: &str,
+    settings: &AppSettings,
+    params: &ConnectionParams,
+) -> bool {
+    if matches!(
+        trust,
+        DirectConnectTrust::HubVerified | DirectConnectTrust::HubKnown
+    ) {
+        return true;
+    }
+    if settings
+        .trusted_direct_connect_addresses
+        .contains(&address.to_lowercase())
+    {
+        return true;
+    }
+    let needs_hub_auth = !matches!(params.access_method.auth_mode(), "byond" | "none");
+    !needs_hub_auth && !matches!(trust, DirectConnectTrust::Unreachable)
+}
+
+async fn pending_for_listed(
+    app: &AppHandle,
+    server: Server,
+    destination: HopDestination,
+    launcher_key: &str,
+    params: ConnectionParams,
+) -> CommandResult<PendingHop> {
+    let (host, port) = crate::byond::server_host_port(app, &server).await?;
+    Ok(PendingHop {
+        requested_at: Instant::now(),
+        destination: HopDestination {
+            server_name: server.name,
+            server_id: server.id,
+            trust: DirectConnectTrust::HubKnown,
+            ..destination
+        },
+        launcher_key: launcher_key.to_string(),
+        params,
+        host,
+        port,
+        map_name: server.data.map(|d| d.map_name),
+        engine: server.engine,
+    })
+}
+
+/// Resolve a hop requested by the game holding `launcher_key`, and either
+/// start it or ask the user to confirm it.
+pub async fn request_hop(
+    app: &AppHandle,
+    launcher_key: &str,
+    params: ConnectionParams,
+    server_id: Option<String>,
+    address: Option<String>,
+    reason: Option<String>,
+) -> CommandResult<(HopStatus, HopDestination)> {
+    let servers = match app.try_state::<Arc<ServerState>>() {
+        Some(state) => state.get_servers().await,
+        None => Vec::new(),
+    };
+
+    let destination = HopDestination {
+        hop_id: uuid::Uuid::new_v4().to_string(),
+        from_server: params.server_name.clone(),
+        server_name: String::new(),
+        address: String::new(),
+        server_id: None,
+        trust: DirectConnectTrust::Unreachable,
+        verified_domain: None,
+        reason,
+    };
+
+    let pending = match (server_id, address) {
+        (Some(server_id), _) => {
+            let server = servers
+                .into_iter()
+                .find(|s| s.id.as_deref() == Some(server_id.as_str()))
+                .ok_or_else(|| CommandError::NotFound(format!("server '{server_id}'")))?;
+            let address = server
+                .url
+                .strip_prefix("byond://")
+                .unwrap_or(&server.url)
+                .to_string();
+            let destination = HopDestination {
+                address,
+                ..destination
+            };
+            pending_for_listed(app, server, destination, launcher_key, params).await?
+        }
+        (None, Some(address)) => {
+            let (host, port) = split_address(&address)?;
+            let address = format!("{host}:{port}");
+            let listed = servers.into_iter().find(|s| {
+                s.url
+                    .strip_prefix("byond://")
+                    .unwrap_or(&s.url)
+                    .eq_ignore_ascii_case(&address)
+            });
+            let destination = HopDestination {
+                address: address.clone(),
+                ..destination
+            };
+            if let Some(server) = listed {
+                pending_for_listed(app, server, destination, launcher_key, params).await?
+            } else {
+                let info = crate::byond::resolve_direct_connect(address.clone()).await?;
+                // Only the hub can vouch for a server id; anything else is the
+                // target's own claim and must not earn it a hub ticket
+                let server_id = info.server_id.filter(|_| {
+                    matches!(
+                        info.trust,
+                        DirectConnectTrust::HubVerified | DirectConnectTrust::HubKnown
+                    )
+                });
+                PendingHop {
+                    requested_at: Instant::now(),
+                    destination: HopDestination {
+                        server_name: address,
+                        server_id,
+                        trust: info.trust,
+                        verified_domain: info.verified_domain,
+                        ..destination
+                    },
+                    launcher_key: launcher_key.to_string(),
+                    params,
+                    host,
+                    port,
+                    map_name: None,
+                    engine: None,
+                }
+            }
+        }
+        (None, None) => {
+            return Err(CommandError::InvalidInput(
+                "A hop needs a server_id or an address".into(),
+            ))
+        }
+    };
+
+    let destination = pending.destination.clone();
+    let settings = crate::settings::load_settings(app)?;
+
+    tracing::info!(
+        "Game on {} asked to hop to {} ({:?})",
+        destination.from_server,
+        destination.address,
+        destination.trust
+    );
+
+    if is_trusted(
+        &destination.trust,
+        &destination.address,
+        &settings,
+        &pending.params,
+    ) {
+        execute_hop(app, pending);
+        Ok((HopStatus::Hopping, destination))
+    } else {
+        let state = app
+            .try_state::<Arc<HopState>>()
+            .ok_or_else(|| CommandError::Internal("Hop state not available".into()))?;
+        state.add_pending(pending)?;
+        app.emit("game-hop-confirm", &destination).ok();
+        Ok((HopStatus::ConfirmationRequired, destination))
+    }
+}
+
+fn execute_hop(app: &AppHandle, hop: PendingHop) {
+    if let Some(presence) = app.try_state::<Arc<PresenceManager>>() {
+        if presence.kill_session_by_key(&hop.launcher_key) {
+            tracing::info!("Killed game process for hop");
+        }
+    }
+
+    app.emit("game-hopping", &hop.destination).ok();
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+
+        let server_name = hop.destination.server_name.clone();
+        if let Err(message) = run_hop(app.clone(), hop).await {
+            tracing::error!("Failed to hop to {}: {}", server_name, message);
+            app.emit(
+                "game-hop-failed",
+                HopFailedEvent {
+                    server_name,
+                    message,
+                },
+            )
+            .ok();
+        }
+    });
+}
+
+async fn run_hop(app: AppHandle, hop: PendingHop) -> Result<(), String> {
+    let fresh_params = crate::control_server::refresh_auth_token(&app, hop.params).await?;
+
+    // Keep the auth mode the player was already using, but never hand
+    // credentials to a server the hub doesn't know, as with direct connect
+    let access_method = match &hop.destination.server_id {
+        Some(server_id) => {
+            crate::byond::maybe_exchange_hub_ticket(&app, fresh_params.access_method, server_id)
+                .await
+                .map_err(|result| result.message)?
+        }
+        None => AccessMethod::None,
+    };
+
+    let version =
+        crate::byond::select_byond_version(hop.engine.as_ref(), &app).map_err(|e| e.to_string())?;
+
+    let result = crate::byond::connect(
+        app,
+        ConnectionRequest {
+            version,
+            host: hop.host,
+            port: hop.port,
+            access_method,
+            server_name: hop.destination.server_name,
+            map_name: hop.map_name,
+            source: Some("control_server_hop".to_string()),
+            server_id: hop.destination.server_id,
+            players: None,
+        },
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if result.success {
+        Ok(())
+    } else {
+        Err(result.message)
+    }
+}
+
+/// Answer a `game-hop-confirm` prompt. With `trust_address` the target is
+/// remembered like a trusted direct connect address.
+#[tauri::command]
+#[specta::specta]
+pub async fn respond_to_server_hop(
+    app: AppHandle,
+    state: tauri::State<'_, Arc<HopState>>,
+    hop_id: String,
+    accepted: bool,
+    trust_address: bool,
+) -> CommandResult<()> {
+    let hop = state.take_pending(&hop_id)?;
+
+    if !accepted {
+        tracing::info!("User declined hop to {}", hop.destination.address);
+        return Ok(());
+    }
+
+    if trust_address {
+        crate::settings::trust_direct_connect_address(app.clone(), hop.destination.address.clone())
+            .await?;
+    }
+
+    execute_hop(&app, hop);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(hop_id: &str, launcher_key: &str, age: Duration) -> PendingHop {
+        PendingHop {
+            requested_at: Instant::now().checked_sub(age).unwrap_or_else(Instant::now),
+            destination: HopDestination {
+                hop_id: hop_id.to_string(),
+                from_server: "Origin".to_string(),
+                server_name: "Target".to_string(),
+                address: "example.com:1400".to_string(),
+                server_id: None,
+                trust: DirectConnectTrust::Unreachable,
+                verified_domain: None,
+                reason: None,
+            },
+            launcher_key: launcher_key.to_string(),
+            params: ConnectionParams {
+                version: "516.1659".to_string(),
+                host: "origin.example.com".to_string(),
+                port: "1400".to_string(),
+                access_method: AccessMethod::None,
+                server_name: "Origin".to_string(),
+                map_name: None,
+                server_id: None,
+                launcher_key: Some(launcher_key.to_string()),
+            },
+            host: "example.com".to_string(),
+            port: "1400".to_string(),
+            map_name: None,
+            engine: None,
+        }
+    }
+
+    #[test]
+    fn a_game_only_keeps_its_latest_hop() {
+        let state = HopState::new();
+        assert!(state
+            .add_pending(pending("a", "key", Duration::ZERO))
+            .is_ok());
+        assert!(state
+            .add_pending(pending("b", "key", Duration::ZERO))
+            .is_ok());
+        assert!(state.take_pending("a").is_err());
+        assert!(state.take_pending("b").is_ok());
+    }
+
+    #[test]
+    fn expired_hops_cannot_be_confirmed() {
+        let state = HopState::new();
+        let age = HOP_CONFIRM_TTL.saturating_add(Duration::from_secs(1));
+        assert!(state.add_pending(pending("old", "key", age)).is_ok());
+        assert!(state.take_pending("old").is_err());
+    }
+
+    #[test]
+    fn pending_hops_are_capped() {
+        let state = HopState::new();
+        for i in 0..MAX_PENDING_HOPS {
+            let id = i.to_string();
+            assert!(state.add_pending(pending(&id, &id, Duration::ZERO)).is_ok());
+        }
+        assert!(state
+            .add_pending(pending("extra", "extra", Duration::ZERO))
+            .is_err());
+
+        // Expired hops don't count towards the cap
+        let state = HopState::new();
+        let age = HOP_CONFIRM_TTL.saturating_add(Duration::from_secs(1));
+        for i in 0..MAX_PENDING_HOPS {
+            let id = i.to_string();
+            assert!(state.add_pending(pending(&id, &id, age)).is_ok());
+        }
+        assert!(state
+            .add_pending(pending("fresh", "fresh", Duration::ZERO))
+            .is_ok());
+    }
+}
@@ -0,0 +1,384 @@
+//! Hardware IDs sent to game servers and the hub.
+//!
+//! Scheme version 2 works in two steps:
+//!
+//! 1. A machine key is derived once from hardware identifiers (see
+//!    [`sources`]) and cached in `hwid.json`. Only sources that read the same
+//!    on every launch are used: the OS machine id on Linux, which Wine
+//!    prefixes don't change, and the SMBIOS UUID elsewhere. Identifiers that
+//!    move around, such as the CPU brand string, are left out. If nothing can
+//!    be read a random key is used, so the ID is still stable per install.
+//! 2. Each server gets `v2.<base64(HMAC-SHA256(machine key, "<variant>\n<salt>\n<server>"))>`,
+//!    where `server` is its hub id, or its listed name if it has none (see
+//!    [`server_identity`]). Different servers can't match up their IDs. The salt starts out derived from the machine key, so
+//!    losing `hwid.json` gives the same IDs again. Where the servers let
+//!    players opt out, rotating to a random salt gives new IDs everywhere.
+//!
+//! Raw identifiers never leave the machine and aren't written to disk.
+//! Version 1 hashed the identifiers with the variant name and sent the same
+//! ID to every server.
+//!
+//! Switching to version 2 changes every player's ID, which would orphan
+//! bans recorded against version 1 IDs. For the transition the version 1 ID
+//! is sent alongside as `legacy_hwid` (see [`legacy_hwid`]), so servers can
+//! link the two before it is dropped.
+
+use std::fs;
+use std::path::PathBuf;
+
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use parking_lot::Mutex;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use crate::error::{CommandError, CommandResult};
+
+pub const HWID_SCHEME_VERSION: u32 = 2;
+
+const HWID_FILE: &str = "hwid.json";
+
+/// Held while the cache is read or written, so two first launches at once
+/// can't each create a different machine key.
+static HWID_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HwidCache {
+    scheme_version: u32,
+    /// Hex SHA-256 of the identifiers that were available.
+    machine_key: String,
+    /// Mixed into every derived ID; replaced on rotation.
+    salt: String,
+    /// RFC 3339 timestamps.
+    created_at: String,
+    rotated_at: Option<String>,
+}
+
+/// One kind of identifier that may go into the machine key.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct HwidSource {
+    pub name: String,
+    pub description: String,
+    /// Whether it could be read on this machine.
+    pub available: bool,
+}
+
+/// What the settings screen shows about hardware IDs.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct HwidInfo {
+    pub scheme_version: u32,
+    pub enabled: bool,
+    /// Whether this launcher's servers allow turning the ID off.
+    pub opt_out_allowed: bool,
+    pub sources: Vec<HwidSource>,
+    /// RFC 3339 timestamps, unset until an ID has been generated.
+    pub created_at: Option<String>,
+    pub rotated_at: Option<String>,
+}
+
+fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    rand::thread_rng().fill(&mut bytes[..]);
+    hex::encode(bytes)
+}
+
+#[cfg(target_os = "linux")]
+fn machine_id() -> Option<String> {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn machine_id() -> Option<String> {
+    sysinfo::Product::uuid().filter(|id| !id.is_empty())
+}
+
+fn board_serial() -> Option<String> {
+    sysinfo::Motherboard::new()
+        .and_then(|board| board.serial_number())
+        .map(|serial| serial.trim().to_string())
+        // Placeholder serials are shared by many boards
+        .filter(|serial| {
+            !serial.is_empty()
+                && !serial.eq_ignore_ascii_case("default string")
+                && !serial.eq_ignore_ascii_case("to be filled by o.e.m.")
+        })
+}
+
+/// Identifiers the machine key is derived from, with their values if they
+/// could be read.
+fn sources() -> Vec<(HwidSource, Option<String>)> {
+    let machine_id_description = if cfg!(target_os = "linux") {
+        "The operating system's machine ID (/etc/machine-id)"
+    } else {
+        "The system UUID reported by the firmware"
+    };
+    let machine_id = machine_id();
+    let board_serial = board_serial();
+
+    vec![
+        (
+            HwidSource {
+                name: "machine_id".to_string(),
+                description: machine_id_description.to_string(),
+                available: machine_id.is_some(),
+            },
+            machine_id,
+        ),
+        (
+            HwidSource {
+                name: "board_serial".to_string(),
+                description: "The motherboard serial number".to_string(),
+                available: board_serial.is_some(),
+            },
+            board_serial,
+        ),
+    ]
+}
+
+fn derive_machine_key() -> String {
+    let values: Vec<String> = sources().into_iter().filter_map(|(_, v)| v).collect();
+    if values.is_empty() {
+        tracing::warn!("No hardware identifiers available, using a random install ID");
+        return random_hex(32);
+    }
+
+    let mut hasher = Sha256::new();
+    for value in values {
+        hasher.update(value.as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Salt for a machine key that has never been rotated.
+fn default_salt(machine_key: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(b"salt\n");
+    hasher.update(machine_key.as_bytes());
+    let digest = hasher.finalize();
+    hex::encode(digest.get(..16).unwrap_or_default())
+}
+
+fn get_cache_path(app: &AppHandle) -> CommandResult<PathBuf> {
+    let app_data = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| CommandError::Io(format!("app data directory unavailable: {e}")))?;
+
+    fs::create_dir_all(&app_data)?;
+
+    Ok(app_data.join(HWID_FILE))
+}
+
+fn load_cache(app: &AppHandle) -> Option<HwidCache> {
+    let path = get_cache_path(app).ok()?;
+    let contents = fs::read_to_string(path).ok()?;
+    match serde_json::from_str::<HwidCache>(&contents) {
+        Ok(cache) if cache.scheme_version == HWID_SCHEME_VERSION => Some(cache),
+        Ok(cache) => {
+            tracing::info!(
+                "Replacing HWID cache from scheme v{} with v{}",
+                cache.scheme_version,
+                HWID_SCHEME_VERSION
+            );
+            None
+        }
+        Err(e) => {
+            tracing::warn!("Failed to parse HWID cache, regenerating: {}", e);
+            None
+        }
+    }
+}
+
+fn save_cache(app: &AppHandle, cache: &HwidCache) -> CommandResult<()> {
+    let path = get_cache_path(app)?;
+    let contents = serde_json::to_string_pretty(cache)
+        .map_err(|e| CommandError::Internal(format!("Failed to serialize HWID cache: {e}")))?;
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+fn load_or_create_cache(app: &AppHandle) -> CommandResult<HwidCache> {
+    let _guard = HWID_LOCK.lock();
+    if let Some(cache) = load_cache(app) {
+        return Ok(cache);
+    }
+
+    let machine_key = derive_machine_key();
+    let cache = HwidCache {
+        scheme_version: HWID_SCHEME_VERSION,
+        salt: default_salt(&machine_key),
+        machine_key,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        rotated_at: None,
+    };
+    save_cache(app, &cache)?;
+    tracing::info!(
+        "Generated HWID machine key (scheme v{})",
+        HWID_SCHEME_VERSION
+    );
+    Ok(cache)
+}
+
+fn opt_out_allowed() -> bool {
+    crate::config::get_config().features.hwid_opt_out
+}
+
+fn is_enabled(app: &AppHandle) -> bool {
+    !opt_out_allowed()
+        || crate::settings::load_settings(app).map_or(true, |settings| settings.hwid_enabled)
+}
+
+/// What a server's hardware ID is derived from: its hub id, or its name for
+/// servers without one. Never the address actually connected to, which
+/// changes with the relay.
+pub fn server_identity(params: &crate::presence::ConnectionParams) -> &str {
+    params.server_id.as_deref().unwrap_or(&params.server_name)
+}
+
+/// The hardware ID to send for `server`, see [`server_identity`]. `None` if
+/// the player opted out or no ID could be stored.
+pub fn hwid_for_server(app: &AppHandle, server: &str) -> Option<String> {
+    if !is_enabled(app) {
+        return None;
+    }
+
+    let cache = match load_or_create_cache(app) {
+        Ok(cache) => cache,
+        Err(e) => {
+            tracing::warn!("Failed to load HWID: {}", e);
+            return None;
+        }
+    };
+
+    let config = crate::config::get_config();
+    let mut mac = Hmac::<Sha256>::new_from_slice(cache.machine_key.as_bytes()).ok()?;
+    mac.update(format!("{}\n{}\n{}", config.variant, cache.salt, server).as_bytes());
+    let digest =
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+    Some(format!("v{HWID_SCHEME_VERSION}.{digest}"))
+}
+
+/// The version 1 ID, sent next to the version 2 one while servers move their
+/// bans over. `None` if the player opted out or no hardware could be read.
+pub fn legacy_hwid(app: &AppHandle) -> Option<String> {
+    use sysinfo::{Motherboard, Product, System};
+
+    if !is_enabled(app) {
+        return None;
+    }
+
+    let mut hasher = Sha256::new();
+    let mut has_data = false;
+
+    if let Some(uuid) = Product::uuid() {
+        hasher.update(uuid.as_bytes());
+        has_data = true;
+    }
+    if let Some(serial) = Product::serial_number() {
+        hasher.update(serial.as_bytes());
+        has_data = true;
+    }
+
+    if let Some(mb) = Motherboard::new() {
+        if let Some(serial) = mb.serial_number() {
+            hasher.update(serial.as_bytes());
+            has_data = true;
+        }
+        if let Some(name) = mb.name() {
+            hasher.update(name.as_bytes());
+        }
+        if let Some(vendor) = mb.vendor_name() {
+            hasher.update(vendor.as_bytes());
+        }
+    }
+
+    let sys = System::new();
+    if let Some(cpu) = sys.cpus().first() {
+        hasher.update(cpu.vendor_id().as_bytes());
+        hasher.update(cpu.brand().as_bytes());
+        has_data = true;
+    }
+
+    let config = crate::config::get_config();
+    hasher.update(format!("{}-hwid-v1", config.variant).as_bytes());
+
+    has_data.then(|| base64::engine::general_purpose::STANDARD.encode(hasher.finalize()))
+}
+
+fn hwid_info(app: &AppHandle) -> HwidInfo {
+    let cache = load_cache(app);
+    HwidInfo {
+        scheme_version: HWID_SCHEME_VERSION,
+        enabled: is_enabled(app),
+        opt_out_allowed: opt_out_allowed(),
+        sources: sources().into_iter().map(|(source, _)| source).collect(),
+        created_at: cache.as_ref().map(|c| c.created_at.clone()),
+        rotated_at: cache.and_then(|c| c.rotated_at),
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_hwid_info(app: AppHandle) -> CommandResult<HwidInfo> {
+    Ok(hwid_info(&app))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_hwid_enabled(app: AppHandle, enabled: bool) -> CommandResult<HwidInfo> {
+    if !enabled && !opt_out_allowed() {
+        return Err(CommandError::InvalidInput(
+            "this launcher's servers require a hardware ID".to_string(),
+        ));
+    }
+
+    let mut settings = crate::settings::load_settings(&app)?;
+    settings.hwid_enabled = enabled;
+    crate::settings::save_settings(&app, &settings)?;
+    tracing::info!(
+        "Hardware ID {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    Ok(hwid_info(&app))
+}
+
+/// Give every server a new hardware ID for this machine. Only allowed where
+/// the servers let players opt out, since a fresh ID evades bans just as well.
+#[tauri::command]
+#[specta::specta]
+pub async fn rotate_hwid(app: AppHandle) -> CommandResult<HwidInfo> {
+    if !opt_out_allowed() {
+        return Err(CommandError::InvalidInput(
+            "this launcher's servers require a stable hardware ID".to_string(),
+        ));
+    }
+
+    {
+        let _guard = HWID_LOCK.lock();
+        let now = chrono::Utc::now().to_rfc3339();
+        let cache = match load_cache(&app) {
+            Some(cache) => HwidCache {
+                salt: random_hex(16),
+                rotated_at: Some(now),
+                ..cache
+            },
+            None => HwidCache {
+                scheme_version: HWID_SCHEME_VERSION,
+                machine_key: derive_machine_key(),
+                salt: random_hex(16),
+                created_at: now,
+                rotated_at: None,
+            },
+        };
+        save_cache(&app, &cache)?;
+    }
+    tracing::info!("Rotated hardware ID");
+    Ok(hwid_info(&app))
+}
@@ -3,14 +3,20 @@ mod autoconnect;
 mod byond;
 mod byond_login;
 pub mod config;
+mod connection;
 mod control_server;
+mod diagnostics;
 mod discord;
 mod error;
+mod hop;
+mod hwid;
 #[cfg(target_os = "windows")]
 mod job_object;
 mod logging;
 mod open_url;
+mod play_history;
 mod presence;
+mod reconnect;
 mod relays;
 mod servers;
 mod settings;
@@ -42,11 +48,22 @@ use byond_login::{
     check_byond_web_session, clear_byond_session, get_byond_session_status, logout_byond_web,
     start_byond_login, ByondSessionState,
 };
-use relays::{get_relays, get_selected_relay, set_selected_relay};
+use connection::{cancel_connection, get_connection_state};
+use diagnostics::{create_diagnostic_bundle, get_recent_game_exits};
+use hop::respond_to_server_hop;
+use hwid::{get_hwid_info, rotate_hwid, set_hwid_enabled};
+use play_history::{clear_play_history, get_play_history, get_play_stats, get_recent_servers};
+use reconnect::cancel_auto_reconnect;
+use relays::{
+    add_custom_relay, get_relays, get_selected_relay, remove_custom_relay, set_relay_probe,
+    set_relay_selection, set_selected_relay,
+};
 use servers::get_servers;
 use settings::{
-    get_settings, save_filter_settings, set_age_verified, set_auth_mode, set_last_played_server,
-    set_last_view_mode, set_locale, set_rendering_pipeline, set_rich_presence, set_theme,
+    delete_launch_profile, get_settings, save_filter_settings, save_launch_profile,
+    set_age_verified, set_auth_mode, set_auto_reconnect, set_byond_version_override,
+    set_last_played_server, set_last_view_mode, set_launch_profile, set_locale,
+    set_presence_privacy, set_rendering_pipeline, set_rich_presence, set_server_relay, set_theme,
     toggle_favorite_server, toggle_server_notifications, trust_direct_connect_address,
 };
 
@@ -58,7 +75,13 @@ use singleplayer::{
 use config::get_launcher_config;
 
 #[cfg(target_os = "linux")]
-use wine::{check_wine_status, initialize_wine_prefix, reset_wine_prefix, WineStatus};
+use wine::{
+    add_custom_wine_runner, check_wine_status, clear_wine_asset_cache, create_wine_prefix,
+    delete_wine_prefix, diagnose_wine_prefix, export_wine_asset_pack, export_wine_prefix,
+    get_wine_asset_cache, import_wine_asset_pack, import_wine_prefix, initialize_wine_prefix,
+    list_wine_prefixes, list_wine_runners, prepare_wine_prefix, remove_custom_wine_runner,
+    repair_wine_prefix, reset_wine_prefix, set_wine_runner, switch_wine_prefix, WineStatus,
+};
 
 #[cfg(target_os = "linux")]
 pub use wine::get_platform;
@@ -127,6 +150,270 @@ async fn reset_wine_prefix() -> error::CommandResult<()> {
     })
 }
 
+#[cfg(not(target_os = "linux"))]
+#[derive(serde::Serialize, specta::Type)]
+#[allow(clippy::struct_excessive_bools)]
+struct WinePrefixInfo {
+    #[serde(flatten)]
+    profile: settings::WinePrefixProfile,
+    custom: bool,
+    path: String,
+    initialized: bool,
+    active: bool,
+    setting_up: bool,
+}
+
+#[cfg(not(target_os = "linux"))]
+#[derive(serde::Serialize, specta::Type)]
+struct WinePrefixSnapshot {
+    format_version: u32,
+    prefix: settings::WinePrefixProfile,
+    runner: String,
+    wine_version: Option<String>,
+    created_at: String,
+    init_version: u32,
+    completed_steps: Vec<String>,
+}
+
+#[cfg(not(target_os = "linux"))]
+#[derive(serde::Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+enum WineRunnerKind {
+    Bundled,
+    System,
+    Proton,
+    Custom,
+}
+
+#[cfg(not(target_os = "linux"))]
+#[derive(serde::Serialize, specta::Type)]
+struct WineRunner {
+    id: String,
+    name: String,
+    kind: WineRunnerKind,
+    path: String,
+    version: Option<String>,
+    meets_minimum_version: bool,
+    active: bool,
+    error: Option<String>,
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn list_wine_runners() -> error::CommandResult<Vec<WineRunner>> {
+    Ok(Vec::new())
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn set_wine_runner(_id: Option<String>) -> error::CommandResult<Vec<WineRunner>> {
+    Err(error::CommandError::UnsupportedPlatform {
+        feature: "wine".into(),
+        platform: std::env::consts::OS.into(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn add_custom_wine_runner(_path: String) -> error::CommandResult<Vec<WineRunner>> {
+    Err(error::CommandError::UnsupportedPlatform {
+        feature: "wine".into(),
+        platform: std::env::consts::OS.into(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn remove_custom_wine_runner(_path: String) -> error::CommandResult<Vec<WineRunner>> {
+    Err(error::CommandError::UnsupportedPlatform {
+        feature: "wine".into(),
+        platform: std::env::consts::OS.into(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+#[derive(serde::Serialize, serde::Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+enum PrefixCheckId {
+    Setup,
+    DxvkOverrides,
+    WebView2Runtime,
+    Wineserver,
+    StuckProcesses,
+    Vulkan,
+}
+
+#[cfg(not(target_os = "linux"))]
+#[derive(serde::Serialize, specta::Type)]
+struct PrefixCheck {
+    id: PrefixCheckId,
+    passed: bool,
+    detail: String,
+    repairable: bool,
+}
+
+#[cfg(not(target_os = "linux"))]
+#[derive(serde::Serialize, specta::Type)]
+struct PrefixDiagnosis {
+    prefix_id: String,
+    pipeline: settings::RenderingPipeline,
+    healthy: bool,
+    checks: Vec<PrefixCheck>,
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn diagnose_wine_prefix(_id: Option<String>) -> error::CommandResult<PrefixDiagnosis> {
+    Err(error::CommandError::UnsupportedPlatform {
+        feature: "wine".into(),
+        platform: std::env::consts::OS.into(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn repair_wine_prefix(
+    _id: Option<String>,
+    _check: PrefixCheckId,
+) -> error::CommandResult<PrefixDiagnosis> {
+    Err(error::CommandError::UnsupportedPlatform {
+        feature: "wine".into(),
+        platform: std::env::consts::OS.into(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+#[derive(serde::Serialize, specta::Type)]
+struct WineAssetCache {
+    path: String,
+    webview2_cached: bool,
+    winetricks_verbs: Vec<String>,
+    size_mb: u32,
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn clear_wine_asset_cache() -> error::CommandResult<WineAssetCache> {
+    Err(error::CommandError::UnsupportedPlatform {
+        feature: "wine".into(),
+        platform: std::env::consts::OS.into(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn get_wine_asset_cache() -> error::CommandResult<WineAssetCache> {
+    Err(error::CommandError::UnsupportedPlatform {
+        feature: "wine".into(),
+        platform: std::env::consts::OS.into(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn import_wine_asset_pack(_path: String) -> error::CommandResult<WineAssetCache> {
+    Err(error::CommandError::UnsupportedPlatform {
+        feature: "wine".into(),
+        platform: std::env::consts::OS.into(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn export_wine_asset_pack(_path: String) -> error::CommandResult<WineAssetCache> {
+    Err(error::CommandError::UnsupportedPlatform {
+        feature: "wine".into(),
+        platform: std::env::consts::OS.into(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn export_wine_prefix(
+    _id: Option<String>,
+    _path: String,
+) -> error::CommandResult<WinePrefixSnapshot> {
+    Err(error::CommandError::UnsupportedPlatform {
+        feature: "wine".into(),
+        platform: std::env::consts::OS.into(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn import_wine_prefix(
+    _path: String,
+    _id: Option<String>,
+    _allow_runner_mismatch: bool,
+) -> error::CommandResult<Vec<WinePrefixInfo>> {
+    Err(error::CommandError::UnsupportedPlatform {
+        feature: "wine".into(),
+        platform: std::env::consts::OS.into(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn list_wine_prefixes() -> error::CommandResult<Vec<WinePrefixInfo>> {
+    Ok(Vec::new())
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn create_wine_prefix(
+    _name: String,
+    _pipeline: settings::RenderingPipeline,
+) -> error::CommandResult<Vec<WinePrefixInfo>> {
+    Err(error::CommandError::UnsupportedPlatform {
+        feature: "wine".into(),
+        platform: std::env::consts::OS.into(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn prepare_wine_prefix(_id: String) -> error::CommandResult<Vec<WinePrefixInfo>> {
+    Err(error::CommandError::UnsupportedPlatform {
+        feature: "wine".into(),
+        platform: std::env::consts::OS.into(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn switch_wine_prefix(_id: String) -> error::CommandResult<Vec<WinePrefixInfo>> {
+    Err(error::CommandError::UnsupportedPlatform {
+        feature: "wine".into(),
+        platform: std::env::consts::OS.into(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+#[specta::specta]
+async fn delete_wine_prefix(_id: String) -> error::CommandResult<Vec<WinePrefixInfo>> {
+    Err(error::CommandError::UnsupportedPlatform {
+        feature: "wine".into(),
+        platform: std::env::consts::OS.into(),
+    })
+}
+
 #[cfg(feature = "steam")]
 use auth::hub_steam_login;
 #[cfg(feature = "steam")]
@@ -151,8 +438,20 @@ fn get_control_server_port(control_server: tauri::State<'_, control_server::Cont
 #[specta::specta]
 fn kill_game(
     presence_manager: tauri::State<'_, std::sync::Arc<presence::PresenceManager>>,
+    pid: Option<u32>,
 ) -> bool {
-    presence_manager.kill_game_process()
+    match pid {
+        Some(pid) => presence_manager.kill_session(pid),
+        None => presence_manager.kill_all_sessions(),
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_game_sessions(
+    presence_manager: tauri::State<'_, std::sync::Arc<presence::PresenceManager>>,
+) -> Vec<presence::GameSession> {
+    presence_manager.get_game_sessions()
 }
 
 #[tauri::command]
@@ -163,136 +462,226 @@ fn open_url(url: String) -> error::CommandResult<()> {
 
 #[cfg(not(feature = "steam"))]
 pub fn build_specta() -> tauri_specta::Builder<tauri::Wry> {
-    tauri_specta::Builder::<tauri::Wry>::new().commands(tauri_specta::collect_commands![
-        greet,
-        check_byond_version,
-        install_byond_version,
-        connect_to_server,
-        connect_to_url,
-        connect_to_address,
-        resolve_direct_connect,
-        is_dev_mode,
-        list_installed_byond_versions,
-        delete_byond_version,
-        is_byond_pager_running,
-        get_byond_username,
-        start_login,
-        hub_login,
-        hub_oauth_login,
-        get_hub_oauth_providers,
-        logout,
-        get_auth_state,
-        refresh_auth,
-        get_access_token,
-        get_settings,
-        set_age_verified,
-        set_auth_mode,
-        set_theme,
-        set_locale,
-        toggle_server_notifications,
-        set_rendering_pipeline,
-        set_rich_presence,
-        set_last_played_server,
-        set_last_view_mode,
-        toggle_favorite_server,
-        trust_direct_connect_address,
-        save_filter_settings,
-        get_control_server_port,
-        kill_game,
-        get_servers,
-        get_relays,
-        get_selected_relay,
-        set_selected_relay,
-        get_platform,
-        check_wine_status,
-        initialize_wine_prefix,
-        reset_wine_prefix,
-        open_url,
-        get_singleplayer_status,
-        get_latest_singleplayer_release,
-        install_singleplayer,
-        delete_singleplayer,
-        launch_singleplayer,
-        get_launcher_config,
-        start_byond_login,
-        cancel_byond_login,
-        byond_login_complete,
-        get_byond_session_status,
-        clear_byond_session,
-        logout_byond_web,
-        check_byond_web_session,
-        byond_session_check_complete,
-    ])
+    tauri_specta::Builder::<tauri::Wry>::new()
+        .commands(tauri_specta::collect_commands![
+            greet,
+            check_byond_version,
+            install_byond_version,
+            connect_to_server,
+            connect_to_url,
+            connect_to_address,
+            resolve_direct_connect,
+            is_dev_mode,
+            list_installed_byond_versions,
+            delete_byond_version,
+            is_byond_pager_running,
+            get_byond_username,
+            start_login,
+            hub_login,
+            hub_oauth_login,
+            get_hub_oauth_providers,
+            logout,
+            get_auth_state,
+            refresh_auth,
+            get_access_token,
+            get_settings,
+            set_age_verified,
+            set_auth_mode,
+            set_theme,
+            set_locale,
+            toggle_server_notifications,
+            set_rendering_pipeline,
+            set_rich_presence,
+            set_presence_privacy,
+            get_hwid_info,
+            set_hwid_enabled,
+            rotate_hwid,
+            set_last_played_server,
+            set_last_view_mode,
+            toggle_favorite_server,
+            trust_direct_connect_address,
+            save_filter_settings,
+            set_byond_version_override,
+            set_server_relay,
+            save_launch_profile,
+            delete_launch_profile,
+            set_launch_profile,
+            set_auto_reconnect,
+            get_control_server_port,
+            kill_game,
+            get_game_sessions,
+            get_recent_game_exits,
+            create_diagnostic_bundle,
+            get_play_history,
+            get_play_stats,
+            get_recent_servers,
+            clear_play_history,
+            get_connection_state,
+            cancel_connection,
+            cancel_auto_reconnect,
+            respond_to_server_hop,
+            get_servers,
+            get_relays,
+            get_selected_relay,
+            set_selected_relay,
+            set_relay_selection,
+            add_custom_relay,
+            remove_custom_relay,
+            set_relay_probe,
+            get_platform,
+            check_wine_status,
+            initialize_wine_prefix,
+            reset_wine_prefix,
+            list_wine_prefixes,
+            create_wine_prefix,
+            prepare_wine_prefix,
+            switch_wine_prefix,
+            delete_wine_prefix,
+            get_wine_asset_cache,
+            clear_wine_asset_cache,
+            import_wine_asset_pack,
+            export_wine_asset_pack,
+            diagnose_wine_prefix,
+            repair_wine_prefix,
+            list_wine_runners,
+            set_wine_runner,
+            add_custom_wine_runner,
+            remove_custom_wine_runner,
+            export_wine_prefix,
+            import_wine_prefix,
+            open_url,
+            get_singleplayer_status,
+            get_latest_singleplayer_release,
+            install_singleplayer,
+            delete_singleplayer,
+            launch_singleplayer,
+            get_launcher_config,
+            start_byond_login,
+            cancel_byond_login,
+            byond_login_complete,
+            get_byond_session_status,
+            clear_byond_session,
+            logout_byond_web,
+            check_byond_web_session,
+            byond_session_check_complete,
+        ])
+        .typ::<byond::ByondVersionOverrideWarning>()
+        .typ::<hop::HopDestination>()
 }
 
 #[cfg(feature = "steam")]
 pub fn build_specta() -> tauri_specta::Builder<tauri::Wry> {
-    tauri_specta::Builder::<tauri::Wry>::new().commands(tauri_specta::collect_commands![
-        greet,
-        check_byond_version,
-        install_byond_version,
-        connect_to_server,
-        connect_to_url,
-        connect_to_address,
-        resolve_direct_connect,
-        is_dev_mode,
-        list_installed_byond_versions,
-        delete_byond_version,
-        is_byond_pager_running,
-        get_byond_username,
-        start_login,
-        hub_login,
-        hub_oauth_login,
-        hub_steam_login,
-        get_hub_oauth_providers,
-        logout,
-        get_auth_state,
-        refresh_auth,
-        get_access_token,
-        get_settings,
-        set_age_verified,
-        set_auth_mode,
-        set_theme,
-        set_locale,
-        toggle_server_notifications,
-        set_rendering_pipeline,
-        set_rich_presence,
-        set_last_played_server,
-        set_last_view_mode,
-        toggle_favorite_server,
-        trust_direct_connect_address,
-        save_filter_settings,
-        get_control_server_port,
-        kill_game,
-        get_servers,
-        get_relays,
-        get_selected_relay,
-        set_selected_relay,
-        get_steam_user_info,
-        get_steam_auth_ticket,
-        cancel_steam_auth_ticket,
-        steam_authenticate,
-        get_steam_launch_options,
-        get_platform,
-        check_wine_status,
-        initialize_wine_prefix,
-        reset_wine_prefix,
-        open_url,
-        get_singleplayer_status,
-        get_latest_singleplayer_release,
-        install_singleplayer,
-        delete_singleplayer,
-        launch_singleplayer,
-        get_launcher_config,
-        start_byond_login,
-        cancel_byond_login,
-        byond_login_complete,
-        get_byond_session_status,
-        clear_byond_session,
-        logout_byond_web,
-        check_byond_web_session,
-        byond_session_check_complete,
-    ])
+    tauri_specta::Builder::<tauri::Wry>::new()
+        .commands(tauri_specta::collect_commands![
+            greet,
+            check_byond_version,
+            install_byond_version,
+            connect_to_server,
+            connect_to_url,
+            connect_to_address,
+            resolve_direct_connect,
+            is_dev_mode,
+            list_installed_byond_versions,
+            delete_byond_version,
+            is_byond_pager_running,
+            get_byond_username,
+            start_login,
+            hub_login,
+            hub_oauth_login,
+            hub_steam_login,
+            get_hub_oauth_providers,
+            logout,
+            get_auth_state,
+            refresh_auth,
+            get_access_token,
+            get_settings,
+            set_age_verified,
+            set_auth_mode,
+            set_theme,
+            set_locale,
+            toggle_server_notifications,
+            set_rendering_pipeline,
+            set_rich_presence,
+            set_presence_privacy,
+            get_hwid_info,
+            set_hwid_enabled,
+            rotate_hwid,
+            set_last_played_server,
+            set_last_view_mode,
+            toggle_favorite_server,
+            trust_direct_connect_address,
+            save_filter_settings,
+            set_byond_version_override,
+            set_server_relay,
+            save_launch_profile,
+            delete_launch_profile,
+            set_launch_profile,
+            set_auto_reconnect,
+            get_control_server_port,
+            kill_game,
+            get_game_sessions,
+            get_recent_game_exits,
+            create_diagnostic_bundle,
+            get_play_history,
+            get_play_stats,
+            get_recent_servers,
+            clear_play_history,
+            get_connection_state,
+            cancel_connection,
+            cancel_auto_reconnect,
+            respond_to_server_hop,
+            get_servers,
+            get_relays,
+            get_selected_relay,
+            set_selected_relay,
+            set_relay_selection,
+            add_custom_relay,
+            remove_custom_relay,
+            set_relay_probe,
+            get_steam_user_info,
+            get_steam_auth_ticket,
+            cancel_steam_auth_ticket,
+            steam_authenticate,
+            get_steam_launch_options,
+            get_platform,
+            check_wine_status,
+            initialize_wine_prefix,
+            reset_wine_prefix,
+            list_wine_prefixes,
+            create_wine_prefix,
+            prepare_wine_prefix,
+            switch_wine_prefix,
+            delete_wine_prefix,
+            get_wine_asset_cache,
+            clear_wine_asset_cache,
+            import_wine_asset_pack,
+            export_wine_asset_pack,
+            diagnose_wine_prefix,
+            repair_wine_prefix,
+            list_wine_runners,
+            set_wine_runner,
+            add_custom_wine_runner,
+            remove_custom_wine_runner,
+            export_wine_prefix,
+            import_wine_prefix,
+            open_url,
+            get_singleplayer_status,
+            get_latest_singleplayer_release,
+            install_singleplayer,
+            delete_singleplayer,
+            launch_singleplayer,
+            get_launcher_config,
+            start_byond_login,
+            cancel_byond_login,
+            byond_login_complete,
+            get_byond_session_status,
+            clear_byond_session,
+            logout_byond_web,
+            check_byond_web_session,
+            byond_session_check_complete,
+        ])
+        .typ::<byond::ByondVersionOverrideWarning>()
+        .typ::<hop::HopDestination>()
 }
 
 #[cfg(target_os = "windows")]
@@ -422,6 +811,10 @@ pub fn run() {
     let presence_manager = std::sync::Arc::new(manager);
     let server_state = std::sync::Arc::new(servers::ServerState::new());
     let relay_state = std::sync::Arc::new(relays::RelayState::new());
+    let connection_manager = std::sync::Arc::new(connection::ConnectionManager::new());
+    let reconnect_state = std::sync::Arc::new(reconnect::ReconnectState::new());
+    let diagnostics_state = std::sync::Arc::new(diagnostics::DiagnosticsState::new());
+    let hop_state = std::sync::Arc::new(hop::HopState::new());
 
     let byond_session_state = ByondSessionState::new();
 
@@ -429,6 +822,10 @@ pub fn run() {
         .manage(std::sync::Arc::clone(&presence_manager))
         .manage(std::sync::Arc::clone(&server_state))
         .manage(std::sync::Arc::clone(&relay_state))
+        .manage(connection_manager)
+        .manage(reconnect_state)
+        .manage(diagnostics_state)
+        .manage(hop_state)
         .manage(byond_session_state);
 
     #[allow(clippy::expect_used)] // Main entry point - no recovery possible
@@ -440,6 +837,7 @@ pub fn run() {
                 if !settings.rich_presence_enabled {
                     presence_manager.set_enabled(false);
                 }
+                presence_manager.set_privacy(settings.presence_privacy);
             }
 
             presence::start_presence_background_task(
@@ -482,20 +880,32 @@ pub fn run() {
             let relay_state =
                 std::sync::Arc::clone(app.state::<std::sync::Arc<relays::RelayState>>().inner());
 
-            let relay_state_init = relay_state;
+            let relay_state_init = std::sync::Arc::clone(&relay_state);
             let handle_for_relay_init = handle.clone();
             tauri::async_runtime::spawn(async move {
                 relays::init_relays(&relay_state_init, &handle_for_relay_init).await;
             });
 
+            let handle_for_relay_task = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                relays::relay_refresh_background_task(handle_for_relay_task, relay_state).await;
+            });
+
             byond::cleanup_old_versions(&handle);
 
             autoconnect::check_and_start_autoconnect(handle.clone());
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                if let Some(server) = app.try_state::<control_server::ControlServer>() {
+                    server.shutdown();
+                }
+            }
+        });
 }
 
 #[cfg(test)]
@@ -43,7 +43,7 @@ pub fn init_logging() -> WorkerGuard {
     guard
 }
 
-fn get_log_directory() -> std::path::PathBuf {
+pub fn get_log_directory() -> std::path::PathBuf {
     let config = crate::config::get_config();
     let log_dir = dirs::data_local_dir()
         .unwrap_or_else(|| std::path::PathBuf::from("."))
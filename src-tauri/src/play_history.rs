@@ -0,0 +1,269 @@
+//! Local record of played game sessions.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::{CommandError, CommandResult};
+use crate::presence::EndedSession;
+
+const HISTORY_FILE: &str = "play_history.json";
+/// Where an unreadable history file is kept.
+const HISTORY_BACKUP_FILE: &str = "play_history.json.bak";
+
+/// Oldest sessions are dropped past this many.
+const MAX_RECORDS: usize = 5000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct PlayRecord {
+    pub server_name: String,
+    pub map_name: Option<String>,
+    /// RFC 3339 timestamps.
+    pub started_at: String,
+    pub ended_at: String,
+    pub duration_secs: u32,
+    pub byond_version: Option<String>,
+    pub auth_mode: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PlayHistory {
+    sessions: Vec<PlayRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct PlaytimeTotal {
+    /// Server name, map name, or ISO week (`2025-W07`).
+    pub key: String,
+    pub total_secs: u32,
+    pub session_count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct PlayStats {
+    pub total_secs: u32,
+    pub session_count: u32,
+    /// Most played first.
+    pub per_server: Vec<PlaytimeTotal>,
+    /// Oldest week first.
+    pub per_week: Vec<PlaytimeTotal>,
+    /// Most played first.
+    pub per_map: Vec<PlaytimeTotal>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct RecentServer {
+    pub server_name: String,
+    pub last_played: String,
+    pub session_count: u32,
+}
+
+fn get_history_path(app: &AppHandle) -> CommandResult<PathBuf> {
+    let app_data = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| CommandError::Io(format!("app data directory unavailable: {e}")))?;
+
+    fs::create_dir_all(&app_data)?;
+
+    Ok(app_data.join(HISTORY_FILE))
+}
+
+/// Read the history. A file that can't be read or parsed is moved aside to
+/// [`HISTORY_BACKUP_FILE`] so the next save can't overwrite it, and the error
+/// is returned.
+fn load_history(app: &AppHandle) -> CommandResult<PlayHistory> {
+    let path = get_history_path(app)?;
+    if !path.exists() {
+        return Ok(PlayHistory::default());
+    }
+    let error = match fs::read_to_string(&path) {
+        Ok(contents) => match serde_json::from_str(&contents) {
+            Ok(history) => return Ok(history),
+            Err(e) => format!("Failed to parse play history: {e}"),
+        },
+        Err(e) => format!("Failed to read play history: {e}"),
+    };
+
+    let backup = path.with_file_name(HISTORY_BACKUP_FILE);
+    match fs::rename(&path, &backup) {
+        Ok(()) => {
+            tracing::warn!("{}, moved it to {:?}", error, backup);
+            Err(CommandError::Io(format!(
+                "{error}. It was moved to {}",
+                backup.display()
+            )))
+        }
+        Err(e) => {
+            tracing::warn!("{}, and failed to move it aside: {}", error, e);
+            Err(CommandError::Io(error))
+        }
+    }
+}
+
+fn save_history(app: &AppHandle, history: &PlayHistory) -> CommandResult<()> {
+    let path = get_history_path(app)?;
+    let contents = serde_json::to_string_pretty(history)
+        .map_err(|e| CommandError::Internal(format!("Failed to serialize play history: {e}")))?;
+    fs::write(&path, contents)?;
+    Ok(())
+}
+
+/// Add a finished session to the history.
+pub fn record_session(app: &AppHandle, ended: &EndedSession) -> CommandResult<()> {
+    let ended_at = Utc::now();
+    let started_at = chrono::Duration::from_std(ended.duration)
+        .ok()
+        .and_then(|d| ended_at.checked_sub_signed(d))
+        .unwrap_or(ended_at);
+
+    let record = PlayRecord {
+        server_name: ended.session.server_name.clone(),
+        map_name: ended.session.map_name.clone(),
+        started_at: started_at.to_rfc3339(),
+        ended_at: ended_at.to_rfc3339(),
+        duration_secs: u32::try_from(ended.duration.as_secs()).unwrap_or(u32::MAX),
+        byond_version: ended.params.as_ref().map(|p| p.version.clone()),
+        auth_mode: ended
+            .params
+            .as_ref()
+            .map(|p| p.access_method.auth_mode().to_string()),
+    };
+
+    let mut history = match load_history(app) {
+        Ok(history) => history,
+        // The unreadable file was moved aside, so starting over loses nothing
+        Err(e) if !get_history_path(app)?.exists() => {
+            tracing::warn!("Starting a new play history: {}", e);
+            PlayHistory::default()
+        }
+        Err(e) => return Err(e),
+    };
+    history.sessions.push(record);
+    if history.sessions.len() > MAX_RECORDS {
+        let excess = history.sessions.len().saturating_sub(MAX_RECORDS);
+        history.sessions.drain(..excess);
+    }
+    save_history(app, &history)
+}
+
+/// ISO week key like `2025-W07` for an RFC 3339 timestamp.
+fn week_key(timestamp: &str) -> Option<String> {
+    let week = DateTime::parse_from_rfc3339(timestamp).ok()?.iso_week();
+    Some(format!("{}-W{:02}", week.year(), week.week()))
+}
+
+fn add_to(totals: &mut HashMap<String, (u32, u32)>, key: String, secs: u32) {
+    let entry = totals.entry(key).or_default();
+    entry.0 = entry.0.saturating_add(secs);
+    entry.1 = entry.1.saturating_add(1);
+}
+
+fn into_totals(totals: HashMap<String, (u32, u32)>) -> Vec<PlaytimeTotal> {
+    totals
+        .into_iter()
+        .map(|(key, (total_secs, session_count))| PlaytimeTotal {
+            key,
+            total_secs,
+            session_count,
+        })
+        .collect()
+}
+
+fn compute_stats(sessions: &[PlayRecord]) -> PlayStats {
+    let mut per_server = HashMap::new();
+    let mut per_week = HashMap::new();
+    let mut per_map = HashMap::new();
+    let mut total_secs: u32 = 0;
+
+    for record in sessions {
+        total_secs = total_secs.saturating_add(record.duration_secs);
+        add_to(
+            &mut per_server,
+            record.server_name.clone(),
+            record.duration_secs,
+        );
+        if let Some(week) = week_key(&record.started_at) {
+            add_to(&mut per_week, week, record.duration_secs);
+        }
+        if let Some(map) = &record.map_name {
+            add_to(&mut per_map, map.clone(), record.duration_secs);
+        }
+    }
+
+    let mut per_server = into_totals(per_server);
+    per_server.sort_by_key(|t| std::cmp::Reverse(t.total_secs));
+    let mut per_week = into_totals(per_week);
+    per_week.sort_by(|a, b| a.key.cmp(&b.key));
+    let mut per_map = into_totals(per_map);
+    per_map.sort_by_key(|t| std::cmp::Reverse(t.total_secs));
+
+    PlayStats {
+        total_secs,
+        session_count: u32::try_from(sessions.len()).unwrap_or(u32::MAX),
+        per_server,
+        per_week,
+        per_map,
+    }
+}
+
+/// Played sessions, newest first.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_play_history(
+    app: AppHandle,
+    limit: Option<u32>,
+) -> CommandResult<Vec<PlayRecord>> {
+    let history = load_history(&app)?;
+    let limit = limit.map_or(usize::MAX, |l| l as usize);
+    Ok(history.sessions.into_iter().rev().take(limit).collect())
+}
+
+/// Playtime totals per server, per week and per map.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_play_stats(app: AppHandle) -> CommandResult<PlayStats> {
+    let history = load_history(&app)?;
+    Ok(compute_stats(&history.sessions))
+}
+
+/// Servers played on, most recent first.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_recent_servers(
+    app: AppHandle,
+    limit: Option<u32>,
+) -> CommandResult<Vec<RecentServer>> {
+    let history = load_history(&app)?;
+
+    let mut servers: Vec<RecentServer> = Vec::new();
+    for record in history.sessions.iter().rev() {
+        if let Some(server) = servers
+            .iter_mut()
+            .find(|s| s.server_name == record.server_name)
+        {
+            server.session_count = server.session_count.saturating_add(1);
+        } else {
+            servers.push(RecentServer {
+                server_name: record.server_name.clone(),
+                last_played: record.ended_at.clone(),
+                session_count: 1,
+            });
+        }
+    }
+
+    if let Some(limit) = limit {
+        servers.truncate(limit as usize);
+    }
+    Ok(servers)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn clear_play_history(app: AppHandle) -> CommandResult<()> {
+    save_history(&app, &PlayHistory::default())
+}
@@ -1,23 +1,45 @@
 //! Manages multiple presence providers and game session state
 #![allow(clippy::unwrap_used)] // Mutex::lock().unwrap() is idiomatic - panic on poison
 
+use std::collections::HashMap;
 use std::process::Child;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use tauri::Manager;
 
-use super::traits::{ConnectionParams, GameSession, PresenceProvider, PresenceState};
+use super::traits::{
+    ConnectionParams, EndedSession, GameDetails, GameSession, GameStatus, PresenceProvider,
+    PresenceState, SessionEndReason,
+};
+use crate::connection::ConnectionManager;
+use crate::control_server::ControlServer;
+use crate::diagnostics::DiagnosticsState;
 use crate::servers::ServerState;
+use crate::settings::PresencePrivacy;
+
+/// A running game client and how we're tracking it.
+struct TrackedSession {
+    session: GameSession,
+    /// Set when we spawned the process ourselves; otherwise tracked by PID.
+    process: Option<Child>,
+    started_at: Instant,
+    /// Set when the game sends `quit` over the control server.
+    quit_signalled: bool,
+}
 
 /// Manages game session state and multiple presence providers
 pub struct PresenceManager {
     providers: Vec<Box<dyn PresenceProvider>>,
-    game_session: Arc<Mutex<Option<GameSession>>>,
-    game_process: Arc<Mutex<Option<Child>>>,
-    game_process_pid: Arc<Mutex<Option<u32>>>,
+    /// Running game clients, keyed by PID.
+    sessions: Arc<Mutex<HashMap<u32, TrackedSession>>>,
+    /// Sessions that were killed or exited since the last poll.
+    ended_sessions: Arc<Mutex<Vec<EndedSession>>>,
     last_connection_params: Arc<Mutex<Option<ConnectionParams>>>,
+    /// Connection params for each session, keyed by launcher key.
+    session_params: Arc<Mutex<HashMap<String, ConnectionParams>>>,
     enabled: Arc<Mutex<bool>>,
+    privacy: Arc<Mutex<PresencePrivacy>>,
     presence_dirty: Arc<Mutex<bool>>,
 }
 
@@ -25,11 +47,12 @@ impl PresenceManager {
     pub fn new() -> Self {
         Self {
             providers: Vec::new(),
-            game_session: Arc::new(Mutex::new(None)),
-            game_process: Arc::new(Mutex::new(None)),
-            game_process_pid: Arc::new(Mutex::new(None)),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            ended_sessions: Arc::new(Mutex::new(Vec::new())),
             last_connection_params: Arc::new(Mutex::new(None)),
+            session_params: Arc::new(Mutex::new(HashMap::new())),
             enabled: Arc::new(Mutex::new(true)),
+            privacy: Arc::new(Mutex::new(PresencePrivacy::default())),
             presence_dirty: Arc::new(Mutex::new(false)),
         }
     }
@@ -43,6 +66,15 @@ impl PresenceManager {
         }
     }
 
+    pub fn set_privacy(&self, privacy: PresencePrivacy) {
+        *self.privacy.lock().unwrap() = privacy;
+        *self.presence_dirty.lock().unwrap() = true;
+    }
+
+    pub fn privacy(&self) -> PresencePrivacy {
+        *self.privacy.lock().unwrap()
+    }
+
     pub fn take_dirty(&self) -> bool {
         let mut dirty = self.presence_dirty.lock().unwrap();
         let was_dirty = *dirty;
@@ -68,25 +100,23 @@ impl PresenceManager {
         map_name: Option<String>,
         player_count: u32,
         process: Child,
+        launcher_key: Option<String>,
     ) {
-        tracing::info!("Starting game session on {}", server_name);
-        {
-            let mut session = self.game_session.lock().unwrap();
-            *session = Some(GameSession {
-                server_name: server_name.clone(),
-                map_name: map_name.clone(),
-            });
-        }
-        {
-            let mut proc = self.game_process.lock().unwrap();
-            *proc = Some(process);
-        }
-
-        self.update_all_presence(&PresenceState::Playing {
-            server_name,
+        let pid = process.id();
+        tracing::info!("Starting game session on {} (PID {})", server_name, pid);
+        self.insert_session(
+            GameSession {
+                pid,
+                server_name,
+                map_name,
+                launcher_key,
+                ckey: None,
+                round_id: None,
+                status: None,
+            },
+            Some(process),
             player_count,
-            map_name,
-        });
+        );
     }
 
     #[cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(dead_code))]
@@ -96,108 +126,260 @@ impl PresenceManager {
         map_name: Option<String>,
         player_count: u32,
         pid: u32,
+        launcher_key: Option<String>,
     ) {
         tracing::info!(
             "Starting game session on {} (tracking PID {})",
             server_name,
             pid
         );
+        self.insert_session(
+            GameSession {
+                pid,
+                server_name,
+                map_name,
+                launcher_key,
+                ckey: None,
+                round_id: None,
+                status: None,
+            },
+            None,
+            player_count,
+        );
+    }
+
+    fn insert_session(&self, session: GameSession, process: Option<Child>, player_count: u32) {
+        let state = PresenceState::Playing {
+            server_name: session.server_name.clone(),
+            player_count,
+            map_name: session.map_name.clone(),
+            details: GameDetails::default(),
+        };
         {
-            let mut session = self.game_session.lock().unwrap();
-            *session = Some(GameSession {
-                server_name: server_name.clone(),
-                map_name: map_name.clone(),
-            });
-        }
-        {
-            let mut pid_guard = self.game_process_pid.lock().unwrap();
-            *pid_guard = Some(pid);
+            let mut sessions = self.sessions.lock().unwrap();
+            if let Some(previous) = sessions.insert(
+                session.pid,
+                TrackedSession {
+                    session,
+                    process,
+                    started_at: Instant::now(),
+                    quit_signalled: false,
+                },
+            ) {
+                tracing::warn!(
+                    "PID {} was already tracked for {}, replacing",
+                    previous.session.pid,
+                    previous.session.server_name
+                );
+            }
         }
 
-        self.update_all_presence(&PresenceState::Playing {
-            server_name,
-            player_count,
-            map_name,
-        });
+        self.update_all_presence(&state);
     }
 
-    pub fn check_game_running(&self) -> bool {
-        // First check if we're tracking a Child process
-        let mut proc_guard = self.game_process.lock().unwrap();
+    /// Check every tracked session and drop the ones whose process is gone.
+    /// Returns all sessions that ended since the last call, including killed ones.
+    ///
+    /// The sessions lock is only held to reap owned children and to apply the
+    /// result; the process scan for untracked PIDs runs without it.
+    pub fn poll_sessions(&self) -> Vec<EndedSession> {
+        let mut ended = std::mem::take(&mut *self.ended_sessions.lock().unwrap());
 
-        if let Some(ref mut child) = *proc_guard {
-            match child.try_wait() {
-                Ok(Some(_status)) => {
-                    drop(proc_guard);
-                    self.clear_game_session();
-                    return false;
-                }
-                Ok(None) => return true,
-                Err(_) => {
-                    drop(proc_guard);
-                    self.clear_game_session();
-                    return false;
+        let mut dead = Vec::new();
+        let mut untracked = Vec::new();
+        {
+            let mut sessions = self.sessions.lock().unwrap();
+            if sessions.is_empty() {
+                return ended;
+            }
+
+            for (pid, tracked) in sessions.iter_mut() {
+                let Some(child) = tracked.process.as_mut() else {
+                    untracked.push((*pid, tracked.session.launcher_key.clone()));
+                    continue;
+                };
+                match child.try_wait() {
+                    Ok(None) => {}
+                    Ok(Some(status)) if status.success() => {
+                        dead.push((*pid, SessionEndReason::Exited, status.code()));
+                    }
+                    Ok(Some(status)) => {
+                        dead.push((*pid, SessionEndReason::Crashed, status.code()));
+                    }
+                    Err(_) => dead.push((*pid, SessionEndReason::Lost, None)),
                 }
             }
         }
-        drop(proc_guard);
-
-        // Check if we're tracking by PID instead
-        let pid_guard = self.game_process_pid.lock().unwrap();
-        if let Some(pid) = *pid_guard {
-            drop(pid_guard);
-            if Self::is_process_running(pid) {
-                return true;
+
+        let mut moved = Vec::new();
+        if !untracked.is_empty() {
+            let system = sysinfo::System::new_all();
+            for (pid, launcher_key) in untracked {
+                if system.process(sysinfo::Pid::from_u32(pid)).is_some() {
+                    continue;
+                }
+
+                // The client may have relaunched itself under a new PID; find it by key
+                #[cfg(any(target_os = "windows", target_os = "linux"))]
+                if let Some(new_pid) = launcher_key
+                    .as_deref()
+                    .and_then(crate::byond::find_dreamseeker_pid_by_key)
+                {
+                    moved.push((pid, new_pid));
+                    continue;
+                }
+                #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+                let _ = launcher_key;
+
+                dead.push((pid, SessionEndReason::Lost, None));
             }
-        } else {
-            drop(pid_guard);
         }
 
-        #[cfg(any(target_os = "windows", target_os = "linux"))]
-        {
-            let params = self.last_connection_params.lock().unwrap();
-            if let Some(ref key) = params.as_ref().and_then(|p| p.launcher_key.as_ref()) {
-                if crate::byond::find_dreamseeker_pid_by_key(key).is_some() {
-                    return true;
-                }
+        // Sessions may have been added or removed while the lock was released;
+        // anything no longer tracked under the same PID is left alone.
+        let mut sessions = self.sessions.lock().unwrap();
+        for (old_pid, new_pid) in moved {
+            if let Some(mut tracked) = sessions.remove(&old_pid) {
+                tracing::debug!("Game session PID changed {} -> {}", old_pid, new_pid);
+                tracked.session.pid = new_pid;
+                sessions.insert(new_pid, tracked);
             }
         }
 
-        if self.game_session.lock().unwrap().is_some() {
-            self.clear_game_session();
+        let finished: Vec<_> = dead
+            .into_iter()
+            .filter_map(|(pid, reason, code)| {
+                sessions.remove(&pid).map(|tracked| (tracked, reason, code))
+            })
+            .collect();
+        drop(sessions);
+
+        for (tracked, reason, exit_code) in finished {
+            tracing::info!(
+                "Game session on {} (PID {}) ended: {:?} (exit code {:?})",
+                tracked.session.server_name,
+                tracked.session.pid,
+                reason,
+                exit_code
+            );
+            ended.push(self.end_session(tracked, reason, exit_code));
         }
-        false
+
+        ended
     }
 
-    /// Check if a process with the given PID is still running
-    fn is_process_running(pid: u32) -> bool {
-        use sysinfo::{Pid, System};
-        let s = System::new_all();
-        s.process(Pid::from_u32(pid)).is_some()
+    fn end_session(
+        &self,
+        tracked: TrackedSession,
+        reason: SessionEndReason,
+        exit_code: Option<i32>,
+    ) -> EndedSession {
+        let params = self.take_session_params(&tracked.session);
+        EndedSession {
+            session: tracked.session,
+            reason,
+            exit_code,
+            duration: tracked.started_at.elapsed(),
+            params,
+            quit_signalled: tracked.quit_signalled,
+        }
     }
 
+    /// The most recently started session, used for presence.
     pub fn get_game_session(&self) -> Option<GameSession> {
-        self.game_session.lock().unwrap().clone()
+        self.sessions
+            .lock()
+            .unwrap()
+            .values()
+            .max_by_key(|t| t.started_at)
+            .map(|t| t.session.clone())
     }
 
-    pub fn clear_game_session(&self) {
-        {
-            let mut session = self.game_session.lock().unwrap();
-            *session = None;
+    pub fn get_game_sessions(&self) -> Vec<GameSession> {
+        let sessions = self.sessions.lock().unwrap();
+        let mut list: Vec<&TrackedSession> = sessions.values().collect();
+        list.sort_by_key(|t| t.started_at);
+        list.into_iter().map(|t| t.session.clone()).collect()
+    }
+
+    /// Update the map a session is on, as reported by the server list.
+    pub fn set_session_map(&self, pid: u32, map_name: Option<String>) {
+        if let Some(tracked) = self.sessions.lock().unwrap().get_mut(&pid) {
+            tracked.session.map_name = map_name;
         }
-        {
-            let mut proc = self.game_process.lock().unwrap();
-            *proc = None;
+    }
+
+    /// Store what the game told us in its handshake.
+    pub fn set_session_handshake(
+        &self,
+        launcher_key: &str,
+        ckey: Option<String>,
+        round_id: Option<String>,
+    ) {
+        for tracked in self.sessions.lock().unwrap().values_mut() {
+            if tracked.session.launcher_key.as_deref() == Some(launcher_key) {
+                tracked.session.ckey.clone_from(&ckey);
+                tracked.session.round_id.clone_from(&round_id);
+            }
         }
-        {
-            let mut pid = self.game_process_pid.lock().unwrap();
-            *pid = None;
+    }
+
+    /// Merge a status update from the game into its session. Returns the
+    /// updated session.
+    pub fn update_session_status(
+        &self,
+        launcher_key: &str,
+        update: GameStatus,
+    ) -> Option<GameSession> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let tracked = sessions
+            .values_mut()
+            .find(|t| t.session.launcher_key.as_deref() == Some(launcher_key))?;
+        let session = &mut tracked.session;
+        if update.ckey.is_some() {
+            session.ckey.clone_from(&update.ckey);
+        }
+        if update.round_id.is_some() {
+            session.round_id.clone_from(&update.round_id);
         }
-        self.update_all_presence(&PresenceState::InLauncher);
+        session
+            .status
+            .get_or_insert_with(GameStatus::default)
+            .merge(update);
+        Some(session.clone())
+    }
+
+    /// Note that the game is about to quit, so its process going away isn't
+    /// treated as a crash. Returns whether a session had this key.
+    pub fn mark_session_quitting(&self, launcher_key: &str) -> bool {
+        let mut sessions = self.sessions.lock().unwrap();
+        let Some(tracked) = sessions
+            .values_mut()
+            .find(|t| t.session.launcher_key.as_deref() == Some(launcher_key))
+        else {
+            return false;
+        };
+        tracked.quit_signalled = true;
+        true
+    }
+
+    pub fn get_session_by_key(&self, launcher_key: &str) -> Option<GameSession> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .values()
+            .find(|t| t.session.launcher_key.as_deref() == Some(launcher_key))
+            .map(|t| t.session.clone())
     }
 
     #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
     pub fn set_last_connection_params(&self, params: ConnectionParams) {
+        if let Some(key) = &params.launcher_key {
+            self.session_params
+                .lock()
+                .unwrap()
+                .insert(key.clone(), params.clone());
+        }
         let mut connection_params = self.last_connection_params.lock().unwrap();
         *connection_params = Some(params);
     }
@@ -206,39 +388,146 @@ impl PresenceManager {
         self.last_connection_params.lock().unwrap().clone()
     }
 
-    pub fn kill_game_process(&self) -> bool {
-        let mut proc_guard = self.game_process.lock().unwrap();
+    /// Connection params for the session that was given `launcher_key`.
+    pub fn get_connection_params(&self, launcher_key: &str) -> Option<ConnectionParams> {
+        self.session_params
+            .lock()
+            .unwrap()
+            .get(launcher_key)
+            .cloned()
+    }
+
+    /// Move a session over to a freshly issued launcher key.
+    pub fn rekey_session(&self, old_key: &str, new_key: &str) {
+        for tracked in self.sessions.lock().unwrap().values_mut() {
+            if tracked.session.launcher_key.as_deref() == Some(old_key) {
+                tracked.session.launcher_key = Some(new_key.to_string());
+            }
+        }
+
+        let mut params = self.session_params.lock().unwrap();
+        if let Some(mut p) = params.remove(old_key) {
+            p.launcher_key = Some(new_key.to_string());
+            params.insert(new_key.to_string(), p);
+        }
+        drop(params);
+
+        let mut last = self.last_connection_params.lock().unwrap();
+        if let Some(p) = last.as_mut() {
+            if p.launcher_key.as_deref() == Some(old_key) {
+                p.launcher_key = Some(new_key.to_string());
+            }
+        }
+    }
+
+    /// Remove and return the connection params for a session. Sessions
+    /// without a launcher key fall back to the last connection, if it was to
+    /// the same server.
+    fn take_session_params(&self, session: &GameSession) -> Option<ConnectionParams> {
+        if let Some(key) = &session.launcher_key {
+            return self.session_params.lock().unwrap().remove(key);
+        }
+        self.get_last_connection_params()
+            .filter(|p| p.launcher_key.is_none() && p.server_name == session.server_name)
+    }
+
+    /// Kill the session with the given PID.
+    pub fn kill_session(&self, pid: u32) -> bool {
+        let tracked = self.sessions.lock().unwrap().remove(&pid);
+        if let Some(tracked) = tracked {
+            self.kill_tracked(tracked)
+        } else {
+            tracing::debug!("No game session with PID {}", pid);
+            false
+        }
+    }
+
+    /// Kill the session that was given `launcher_key`.
+    pub fn kill_session_by_key(&self, launcher_key: &str) -> bool {
+        let tracked = {
+            let mut sessions = self.sessions.lock().unwrap();
+            let pid = sessions
+                .values()
+                .find(|t| t.session.launcher_key.as_deref() == Some(launcher_key))
+                .map(|t| t.session.pid);
+            pid.and_then(|pid| sessions.remove(&pid))
+        };
+
+        if let Some(tracked) = tracked {
+            return self.kill_tracked(tracked);
+        }
+
+        // Launched but not tracked yet (e.g. still authenticating)
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        if crate::byond::kill_dreamseeker_by_key(launcher_key) {
+            return true;
+        }
+
+        tracing::debug!("No game process to kill");
+        false
+    }
+
+    /// Kill every running session.
+    pub fn kill_all_sessions(&self) -> bool {
+        let tracked: Vec<TrackedSession> = self
+            .sessions
+            .lock()
+            .unwrap()
+            .drain()
+            .map(|(_, t)| t)
+            .collect();
+
+        if tracked.is_empty() {
+            tracing::debug!("No game process to kill");
+            return false;
+        }
+
+        let mut killed = false;
+        for t in tracked {
+            killed |= self.kill_tracked(t);
+        }
+        killed
+    }
+
+    fn kill_tracked(&self, mut tracked: TrackedSession) -> bool {
+        let pid = tracked.session.pid;
+        let mut killed = false;
 
-        if let Some(ref mut child) = *proc_guard {
+        if let Some(child) = tracked.process.as_mut() {
             match child.kill() {
                 Ok(()) => {
-                    tracing::info!("Game process killed successfully via Child handle");
+                    tracing::info!("Game process {} killed successfully via Child handle", pid);
                     let _ = child.wait();
-                    drop(proc_guard);
-                    self.clear_game_session();
-                    return true;
+                    killed = true;
                 }
                 Err(e) => {
                     tracing::warn!("Failed to kill game process via Child handle: {}", e);
                 }
             }
         }
-        drop(proc_guard);
 
         #[cfg(any(target_os = "windows", target_os = "linux"))]
-        {
-            let params = self.last_connection_params.lock().unwrap();
-            if let Some(ref key) = params.as_ref().and_then(|p| p.launcher_key.as_ref()) {
-                if crate::byond::kill_dreamseeker_by_key(key) {
-                    drop(params);
-                    self.clear_game_session();
-                    return true;
-                }
+        if !killed {
+            if let Some(key) = tracked.session.launcher_key.as_deref() {
+                killed = crate::byond::kill_dreamseeker_by_key(key);
             }
         }
 
-        tracing::debug!("No game process to kill");
-        false
+        if !killed {
+            let s = sysinfo::System::new_all();
+            if let Some(process) = s.process(sysinfo::Pid::from_u32(pid)) {
+                tracing::info!("Killing game process {}", pid);
+                killed = process.kill();
+            }
+        }
+
+        if !killed {
+            tracing::warn!("Failed to kill game process {}", pid);
+        }
+
+        let ended = self.end_session(tracked, SessionEndReason::Killed, None);
+        self.ended_sessions.lock().unwrap().push(ended);
+        killed
     }
 
     pub fn update_all_presence(&self, state: &PresenceState) {
@@ -275,63 +564,97 @@ pub fn start_presence_background_task(
     tauri::async_runtime::spawn(async move {
         let poll_interval = Duration::from_millis(100);
         let mut was_game_running = false;
+        let mut last_server_name: Option<String> = None;
         let mut last_player_count: Option<i32> = None;
         let mut last_map_name: Option<String> = None;
+        let mut last_details = GameDetails::default();
 
         loop {
             if let Some(ref callback) = poll_callback {
                 callback();
             }
 
-            let game_running = presence_manager.check_game_running();
+            let ended = presence_manager.poll_sessions();
+            for ended_session in ended {
+                let session = &ended_session.session;
+                if let Some(connection) = app_handle.try_state::<Arc<ConnectionManager>>() {
+                    connection.mark_game_closed(&app_handle, session.launcher_key.as_deref());
+                }
+                if let (Some(control_server), Some(key)) = (
+                    app_handle.try_state::<ControlServer>(),
+                    session.launcher_key.as_deref(),
+                ) {
+                    control_server.revoke_key(key);
+                }
+                app_handle.emit("game-closed", session).ok();
+                if let Some(diagnostics) = app_handle.try_state::<Arc<DiagnosticsState>>() {
+                    let record = diagnostics.record_exit(&ended_session);
+                    if record.is_failure() {
+                        app_handle.emit("game-crashed", &record).ok();
+                    }
+                }
+                if let Err(e) = crate::play_history::record_session(&app_handle, &ended_session) {
+                    tracing::warn!("Failed to record play history: {}", e);
+                }
+                crate::reconnect::handle_session_ended(&app_handle, ended_session);
+            }
+
             let force_update = presence_manager.take_dirty();
 
-            if game_running {
+            if let Some(session) = presence_manager.get_game_session() {
                 was_game_running = true;
 
-                if let Some(session) = presence_manager.get_game_session() {
-                    let (player_count, map_name) = if let Some(server_state) =
-                        app_handle.try_state::<Arc<ServerState>>()
-                    {
-                        let servers = server_state.get_servers().await;
-                        if let Some(server) = servers.iter().find(|s| s.name == session.server_name)
-                        {
-                            let player_count = server.data.as_ref().map(|d| d.players);
-                            let map_name = server
-                                .data
-                                .as_ref()
-                                .map(|d| d.map_name.clone())
-                                .or_else(|| session.map_name.clone());
-                            (player_count, map_name)
-                        } else {
-                            (None, session.map_name.clone())
-                        }
+                let (player_count, map_name) = if let Some(server_state) =
+                    app_handle.try_state::<Arc<ServerState>>()
+                {
+                    let servers = server_state.get_servers().await;
+                    if let Some(server) = servers.iter().find(|s| s.name == session.server_name) {
+                        let player_count = server.data.as_ref().map(|d| d.players);
+                        let map_name = server
+                            .data
+                            .as_ref()
+                            .map(|d| d.map_name.clone())
+                            .or_else(|| session.map_name.clone());
+                        (player_count, map_name)
                     } else {
                         (None, session.map_name.clone())
-                    };
-
-                    if force_update
-                        || player_count != last_player_count
-                        || map_name != last_map_name
-                    {
-                        last_player_count = player_count;
-                        last_map_name.clone_from(&map_name);
-
-                        presence_manager.update_all_presence(&PresenceState::Playing {
-                            server_name: session.server_name.clone(),
-                            #[allow(clippy::cast_sign_loss)] // Player count is non-negative
-                            player_count: player_count.unwrap_or(0) as u32,
-                            map_name,
-                        });
                     }
+                } else {
+                    (None, session.map_name.clone())
+                };
+
+                let details = session
+                    .status
+                    .as_ref()
+                    .map(|status| GameDetails::from_status(status, presence_manager.privacy()))
+                    .unwrap_or_default();
+
+                if force_update
+                    || last_server_name.as_deref() != Some(session.server_name.as_str())
+                    || player_count != last_player_count
+                    || map_name != last_map_name
+                    || details != last_details
+                {
+                    last_server_name = Some(session.server_name.clone());
+                    last_player_count = player_count;
+                    last_map_name.clone_from(&map_name);
+                    last_details.clone_from(&details);
+                    presence_manager.set_session_map(session.pid, map_name.clone());
+
+                    presence_manager.update_all_presence(&PresenceState::Playing {
+                        server_name: session.server_name.clone(),
+                        #[allow(clippy::cast_sign_loss)] // Player count is non-negative
+                        player_count: player_count.unwrap_or(0) as u32,
+                        map_name,
+                        details,
+                    });
                 }
-            } else if was_game_running {
+            } else if was_game_running || force_update {
                 was_game_running = false;
+                last_server_name = None;
                 last_player_count = None;
                 last_map_name = None;
-                presence_manager.update_all_presence(&PresenceState::InLauncher);
-                app_handle.emit("game-closed", ()).ok();
-            } else if force_update {
+                last_details = GameDetails::default();
                 presence_manager.update_all_presence(&PresenceState::InLauncher);
             }
 
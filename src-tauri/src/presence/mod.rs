@@ -3,4 +3,7 @@ mod traits;
 
 pub use manager::{start_presence_background_task, PresenceManager};
 #[allow(unused_imports)]
-pub use traits::{ConnectionParams, GameSession, PresenceProvider, PresenceState};
+pub use traits::{
+    ConnectionParams, EndedSession, GameDetails, GameSession, GameStatus, PresenceProvider,
+    PresenceState, SessionEndReason,
+};
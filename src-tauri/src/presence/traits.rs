@@ -1,7 +1,191 @@
-#[derive(Debug, Clone)]
+use crate::settings::PresencePrivacy;
+
+#[derive(Debug, Clone, serde::Serialize, specta::Type)]
 pub struct GameSession {
+    /// PID of the tracked game process; sessions are keyed by this.
+    pub pid: u32,
     pub server_name: String,
     pub map_name: Option<String>,
+    /// Launcher key handed to this client, used to route control server requests.
+    #[serde(skip)]
+    pub launcher_key: Option<String>,
+    /// Reported by the game in its handshake.
+    pub ckey: Option<String>,
+    pub round_id: Option<String>,
+    /// Latest status pushed by the game over the WebSocket channel.
+    pub status: Option<GameStatus>,
+}
+
+/// What the game reports about itself. Every field is optional so the game
+/// can send only what changed since its last update.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, specta::Type)]
+pub struct GameStatus {
+    pub ckey: Option<String>,
+    pub round_id: Option<String>,
+    /// Server-defined round state, e.g. `pregame`, `playing`, `finished`.
+    pub round_state: Option<String>,
+    pub in_lobby: Option<bool>,
+    /// Name of the character being played.
+    pub character: Option<String>,
+    /// Seconds since the round started.
+    pub round_time: Option<u32>,
+    pub role: Option<String>,
+    pub faction: Option<String>,
+    pub observer: Option<bool>,
+    /// Unix time the round started at, worked out from `round_time`.
+    #[serde(skip_deserializing)]
+    #[specta(type = Option<f64>)]
+    pub round_started_at: Option<i64>,
+}
+
+impl GameStatus {
+    /// Apply the fields set in `update`.
+    pub fn merge(&mut self, update: GameStatus) {
+        let GameStatus {
+            ckey,
+            round_id,
+            round_state,
+            in_lobby,
+            character,
+            round_time,
+            role,
+            faction,
+            observer,
+            round_started_at: _,
+        } = update;
+        if ckey.is_some() {
+            self.ckey = ckey;
+        }
+        if round_id.is_some() {
+            self.round_id = round_id;
+        }
+        if round_state.is_some() {
+            self.round_state = round_state;
+        }
+        if in_lobby.is_some() {
+            self.in_lobby = in_lobby;
+        }
+        if character.is_some() {
+            self.character = character;
+        }
+        if role.is_some() {
+            self.role = role;
+        }
+        if faction.is_some() {
+            self.faction = faction;
+        }
+        if observer.is_some() {
+            self.observer = observer;
+        }
+        if let Some(round_time) = round_time {
+            self.round_time = Some(round_time);
+            let started_at = chrono::Utc::now()
+                .timestamp()
+                .saturating_sub(i64::from(round_time));
+            // Reports arrive a little late; don't move the start time (and
+            // so update presence) over a second or two of drift
+            if self
+                .round_started_at
+                .is_none_or(|current| current.abs_diff(started_at) > ROUND_START_DRIFT_SECS)
+            {
+                self.round_started_at = Some(started_at);
+            }
+        }
+    }
+}
+
+const ROUND_START_DRIFT_SECS: u64 = 5;
+
+/// What the player is doing, as shown by presence providers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GameDetails {
+    pub in_lobby: Option<bool>,
+    /// Unix time the round started at.
+    pub round_started_at: Option<i64>,
+    pub role: Option<String>,
+    pub faction: Option<String>,
+    pub observer: Option<bool>,
+}
+
+impl GameDetails {
+    /// The parts of `status` the user has chosen to share.
+    pub fn from_status(status: &GameStatus, privacy: PresencePrivacy) -> Self {
+        let in_lobby = status.in_lobby.filter(|_| privacy.show_lobby_status);
+        Self {
+            in_lobby,
+            round_started_at: status
+                .round_started_at
+                .filter(|_| privacy.show_round_time && in_lobby != Some(true)),
+            role: status.role.clone().filter(|_| privacy.show_role),
+            faction: status.faction.clone().filter(|_| privacy.show_faction),
+            observer: status.observer.filter(|_| privacy.show_observer),
+        }
+    }
+
+    /// One line describing what the player is doing, e.g. "Playing as Medic".
+    pub fn activity(&self) -> Option<String> {
+        if self.in_lobby == Some(true) {
+            return Some("In the lobby".to_string());
+        }
+        if self.observer == Some(true) {
+            return Some("Observing".to_string());
+        }
+        match (&self.role, &self.faction) {
+            (Some(role), Some(faction)) => Some(format!("Playing as {role} ({faction})")),
+            (Some(role), None) => Some(format!("Playing as {role}")),
+            (None, Some(faction)) => Some(format!("Playing for {faction}")),
+            (None, None) if self.in_lobby == Some(false) => Some("In a round".to_string()),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Why a game session stopped being tracked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionEndReason {
+    /// Killed by the launcher (`kill_game`, a cancelled connection, a restart).
+    Killed,
+    /// Exited with a success code.
+    Exited,
+    /// Exited with a non-zero code.
+    Crashed,
+    /// The process went away and its exit code couldn't be read, which is
+    /// the case for clients we track by PID rather than by `Child` handle.
+    Lost,
+}
+
+impl SessionEndReason {
+    /// Whether the exit code alone says the game went away without the
+    /// player quitting or the launcher closing it. See
+    /// [`EndedSession::is_unexpected`] for sessions that ended as `Lost`.
+    pub fn is_unexpected(self) -> bool {
+        self == Self::Crashed
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EndedSession {
+    pub session: GameSession,
+    pub reason: SessionEndReason,
+    pub exit_code: Option<i32>,
+    pub duration: std::time::Duration,
+    /// How the session was connected, for relaunching it.
+    pub params: Option<ConnectionParams>,
+    /// The game said it was quitting before its process went away.
+    pub quit_signalled: bool,
+}
+
+impl EndedSession {
+    /// Whether the game went away without the player quitting or the
+    /// launcher closing it. A client tracked by PID can only end as `Lost`,
+    /// which counts as unexpected unless the game sent `quit` first.
+    pub fn is_unexpected(&self) -> bool {
+        match self.reason {
+            SessionEndReason::Lost => !self.quit_signalled,
+            reason => reason.is_unexpected(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -25,6 +209,7 @@ pub enum PresenceState {
         server_name: String,
         player_count: u32,
         map_name: Option<String>,
+        details: GameDetails,
     },
     #[allow(dead_code)]
     Disconnected,
@@ -0,0 +1,290 @@
+//! Opt-in automatic reconnect when a game client exits unexpectedly.
+//!
+//! When a session crashes or its process disappears, it is relaunched with the
+//! same connection params, waiting longer before each try. A relaunched client
+//! that fails again before it has run for [`STABLE_SESSION`] counts against
+//! the same budget, so a client stuck in a crash loop stops being relaunched
+//! once `auto_reconnect_max_attempts` is used up. Players quitting and the
+//! launcher killing the game never trigger a reconnect.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::byond::ConnectionRequest;
+use crate::presence::{ConnectionParams, EndedSession};
+
+const BASE_DELAY: Duration = Duration::from_secs(2);
+const MAX_DELAY: Duration = Duration::from_mins(1);
+
+/// A session that ran at least this long resets the attempt counter.
+const STABLE_SESSION: Duration = Duration::from_mins(5);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoReconnectEvent {
+    pub server_name: String,
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub delay_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoReconnectStoppedEvent {
+    pub server_name: String,
+    pub reason: String,
+}
+
+#[derive(Default)]
+struct ReconnectTracker {
+    /// Relaunches since the server last had a stable session.
+    attempts: u32,
+    /// A reconnect loop is currently running for this server.
+    pending: bool,
+}
+
+/// Per-server reconnect bookkeeping.
+#[derive(Default)]
+pub struct ReconnectState {
+    trackers: Mutex<HashMap<String, ReconnectTracker>>,
+}
+
+impl ReconnectState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn reset(&self, server_name: &str) {
+        self.trackers.lock().remove(server_name);
+    }
+
+    fn attempts(&self, server_name: &str) -> u32 {
+        self.trackers
+            .lock()
+            .get(server_name)
+            .map_or(0, |t| t.attempts)
+    }
+
+    /// Mark a reconnect loop as running. Returns false if one already is.
+    fn claim(&self, server_name: &str) -> bool {
+        let mut trackers = self.trackers.lock();
+        let tracker = trackers.entry(server_name.to_string()).or_default();
+        !std::mem::replace(&mut tracker.pending, true)
+    }
+
+    fn next_attempt(&self, server_name: &str) -> u32 {
+        let mut trackers = self.trackers.lock();
+        let tracker = trackers.entry(server_name.to_string()).or_default();
+        tracker.attempts = tracker.attempts.saturating_add(1);
+        tracker.attempts
+    }
+
+    fn is_pending(&self, server_name: &str) -> bool {
+        self.trackers
+            .lock()
+            .get(server_name)
+            .is_some_and(|t| t.pending)
+    }
+
+    fn finish(&self, server_name: &str) {
+        if let Some(tracker) = self.trackers.lock().get_mut(server_name) {
+            tracker.pending = false;
+        }
+    }
+
+    /// Stop all pending reconnects. Returns the servers that were pending.
+    fn cancel_all(&self) -> Vec<String> {
+        let mut trackers = self.trackers.lock();
+        trackers
+            .iter_mut()
+            .filter(|(_, t)| t.pending)
+            .map(|(name, t)| {
+                t.pending = false;
+                name.clone()
+            })
+            .collect()
+    }
+}
+
+fn backoff(attempt: u32) -> Duration {
+    BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_DELAY)
+}
+
+/// Called by the presence task for every session that ends. Starts a
+/// reconnect loop if the session ended unexpectedly and auto-reconnect is
+/// enabled.
+pub fn handle_session_ended(app: &AppHandle, ended: EndedSession) {
+    let Some(state) = app.try_state::<Arc<ReconnectState>>() else {
+        return;
+    };
+    let state = Arc::clone(&state);
+    let server_name = ended.session.server_name.clone();
+
+    if ended.duration >= STABLE_SESSION {
+        state.reset(&server_name);
+    }
+
+    if !ended.is_unexpected() {
+        return;
+    }
+
+    let settings = match crate::settings::load_settings(app) {
+        Ok(settings) => settings,
+        Err(e) => {
+            tracing::warn!("Failed to load settings for auto-reconnect: {}", e);
+            return;
+        }
+    };
+    if !settings.auto_reconnect {
+        return;
+    }
+
+    let Some(params) = ended.params else {
+        tracing::debug!(
+            "No connection params for ended session on {}, not reconnecting",
+            server_name
+        );
+        return;
+    };
+
+    let max_attempts = settings.auto_reconnect_max_attempts;
+    if state.attempts(&server_name) >= max_attempts {
+        tracing::warn!(
+            "{} crashed {} times in a row, not reconnecting",
+            server_name,
+            max_attempts
+        );
+        emit_stopped(
+            app,
+            &server_name,
+            format!("Crash loop detected: the game crashed {max_attempts} times in a row"),
+        );
+        state.reset(&server_name);
+        return;
+    }
+
+    if !state.claim(&server_name) {
+        return;
+    }
+
+    tracing::info!(
+        "Game session on {} ended unexpectedly ({:?}, exit code {:?}), reconnecting",
+        server_name,
+        ended.reason,
+        ended.exit_code
+    );
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        reconnect_loop(app, state, params, max_attempts).await;
+    });
+}
+
+async fn reconnect_loop(
+    app: AppHandle,
+    state: Arc<ReconnectState>,
+    params: ConnectionParams,
+    max_attempts: u32,
+) {
+    let server_name = params.server_name.clone();
+
+    loop {
+        let attempt = state.next_attempt(&server_name);
+        if attempt > max_attempts {
+            state.finish(&server_name);
+            emit_stopped(
+                &app,
+                &server_name,
+                format!("Failed to reconnect after {max_attempts} attempts"),
+            );
+            return;
+        }
+
+        let delay = backoff(attempt);
+        app.emit(
+            "auto-reconnect",
+            AutoReconnectEvent {
+                server_name: server_name.clone(),
+                attempt,
+                max_attempts,
+                delay_secs: delay.as_secs(),
+            },
+        )
+        .ok();
+        tokio::time::sleep(delay).await;
+
+        if !state.is_pending(&server_name) {
+            tracing::info!("Auto-reconnect to {} cancelled", server_name);
+            return;
+        }
+
+        let fresh_params =
+            match crate::control_server::refresh_auth_token(&app, params.clone()).await {
+                Ok(params) => params,
+                Err(e) => {
+                    tracing::error!("Failed to refresh auth token for reconnect: {}", e);
+                    state.finish(&server_name);
+                    emit_stopped(&app, &server_name, e);
+                    return;
+                }
+            };
+
+        let host = crate::relays::fallback_host(&app, fresh_params.host).await;
+        let result = crate::byond::connect(
+            app.clone(),
+            ConnectionRequest {
+                version: fresh_params.version,
+                host,
+                port: fresh_params.port,
+                access_method: fresh_params.access_method,
+                server_name: fresh_params.server_name,
+                map_name: fresh_params.map_name,
+                source: Some("auto_reconnect".to_string()),
+                server_id: fresh_params.server_id,
+                players: None,
+            },
+        )
+        .await;
+
+        match result {
+            Ok(result) if result.success => {
+                tracing::info!("Reconnected to {} (attempt {})", server_name, attempt);
+                state.finish(&server_name);
+                return;
+            }
+            Ok(result) => {
+                tracing::warn!("Reconnect attempt {} failed: {}", attempt, result.message);
+            }
+            Err(e) => {
+                tracing::warn!("Reconnect attempt {} failed: {}", attempt, e);
+            }
+        }
+    }
+}
+
+fn emit_stopped(app: &AppHandle, server_name: &str, reason: String) {
+    app.emit(
+        "auto-reconnect-stopped",
+        AutoReconnectStoppedEvent {
+            server_name: server_name.to_string(),
+            reason,
+        },
+    )
+    .ok();
+}
+
+/// Stop any pending automatic reconnects.
+#[tauri::command]
+#[specta::specta]
+pub fn cancel_auto_reconnect(app: AppHandle, state: tauri::State<'_, Arc<ReconnectState>>) -> bool {
+    let cancelled = state.cancel_all();
+    for server_name in &cancelled {
+        emit_stopped(&app, server_name, "Cancelled".to_string());
+    }
+    !cancelled.is_empty()
+}
@@ -1,28 +1,138 @@
-use crate::error::CommandResult;
+use crate::error::{CommandError, CommandResult};
+use crate::presence::PresenceManager;
+use crate::servers::ServerState;
+use crate::settings::{load_settings, save_settings, AppSettings, RelayProbe, RelaySelection};
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::RwLock;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
 const PING_PORT: u16 = 4000;
 const PING_COUNT: u32 = 10;
 const PING_TIMEOUT: Duration = Duration::from_secs(5);
+/// Connects and topics sent per relay by the game port probe.
+const GAME_PROBE_COUNT: u32 = 3;
+/// How often relays are pinged again after startup.
+const RELAY_REFRESH_INTERVAL_SECS: u64 = 300;
+/// Checks kept per relay for its health history.
+const HEALTH_HISTORY_LEN: usize = 20;
+const MANIFEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where a relay came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum RelaySource {
+    #[default]
+    Builtin,
+    Manifest,
+    Custom,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct Relay {
     pub id: String,
     pub name: String,
     pub host: String,
+    #[serde(default)]
+    pub source: RelaySource,
+}
+
+/// Availability and latency over a relay's recent checks.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, specta::Type)]
+pub struct RelayHealth {
+    pub checks: u32,
+    /// Share of checks that reached the relay, from 0 to 1.
+    pub availability: f32,
+    /// Percentiles of the per-check medians, in ms.
+    pub p50: Option<u32>,
+    pub p95: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct RelayWithPing {
     #[serde(flatten)]
     pub relay: Relay,
+    /// Median round trip of the last check in ms, `None` if unreachable.
+    /// For the game port probe this is the connect time.
     pub ping: Option<u32>,
+    /// 95th percentile round trip of the last check in ms.
+    pub p95: Option<u32>,
+    /// Median TCP connect time to the game port in ms, from the game port
+    /// probe only.
+    pub connect: Option<u32>,
+    /// Median topic round trip through the relay to the game server in ms,
+    /// from the game port probe only.
+    pub topic: Option<u32>,
     pub checking: bool,
+    pub health: RelayHealth,
+    /// Median of each recent check, oldest first; `None` for failed checks.
+    #[serde(skip)]
+    history: VecDeque<Option<u32>>,
+}
+
+impl RelayWithPing {
+    fn new(relay: Relay) -> Self {
+        Self {
+            relay,
+            ping: None,
+            p95: None,
+            connect: None,
+            topic: None,
+            checking: true,
+            health: RelayHealth::default(),
+            history: VecDeque::with_capacity(HEALTH_HISTORY_LEN),
+        }
+    }
+
+    fn record_check(&mut self, median: Option<u32>) {
+        if self.history.len() >= HEALTH_HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(median);
+
+        let mut latencies: Vec<u32> = self.history.iter().flatten().copied().collect();
+        latencies.sort_unstable();
+        let checks = u32::try_from(self.history.len()).unwrap_or(u32::MAX);
+        let successes = u32::try_from(latencies.len()).unwrap_or(u32::MAX);
+        #[allow(clippy::cast_precision_loss)] // At most HEALTH_HISTORY_LEN
+        let availability = successes as f32 / checks.max(1) as f32;
+        self.health = RelayHealth {
+            checks,
+            availability,
+            p50: percentile(&latencies, 50),
+            p95: percentile(&latencies, 95),
+        };
+    }
+}
+
+/// Round trips measured by one check, in ms. `median` and `p95` are what
+/// relays are ranked by.
+#[derive(Debug, Clone, Copy)]
+struct PingResult {
+    median: u32,
+    p95: u32,
+    connect: Option<u32>,
+    topic: Option<u32>,
+}
+
+/// The relay's latency as ranked by `policy`.
+fn score(relay: &RelayWithPing, policy: RelaySelection) -> Option<u32> {
+    match policy {
+        RelaySelection::LowestP95 => relay.p95,
+        _ => relay.ping,
+    }
+}
+
+/// The reachable relay with the lowest score.
+fn best_relay(relays: &[RelayWithPing], policy: RelaySelection) -> Option<(&RelayWithPing, u32)> {
+    relays
+        .iter()
+        .filter_map(|r| score(r, policy).map(|s| (r, s)))
+        .min_by_key(|(_, s)| *s)
 }
 
 pub struct RelayState {
@@ -32,13 +142,9 @@ pub struct RelayState {
 
 impl RelayState {
     pub fn new() -> Self {
-        let relays = get_default_relays()
+        let relays = builtin_relays()
             .into_iter()
-            .map(|r| RelayWithPing {
-                relay: r,
-                ping: None,
-                checking: true,
-            })
+            .map(RelayWithPing::new)
             .collect();
 
         Self {
@@ -59,88 +165,207 @@ impl RelayState {
         *self.selected.write().await = id;
     }
 
-    pub async fn get_selected_host(&self) -> Option<String> {
+    /// Replace the relay list, keeping the measurements of relays that stay.
+    async fn set_relay_list(&self, list: Vec<Relay>) {
+        let mut relays = self.relays.write().await;
+        let mut old = std::mem::take(&mut *relays);
+        for relay in list {
+            if relays.iter().any(|r| r.relay.id == relay.id) {
+                continue;
+            }
+            let existing = old
+                .iter()
+                .position(|r| r.relay.id == relay.id && r.relay.host == relay.host);
+            match existing {
+                Some(index) => {
+                    let mut existing = old.swap_remove(index);
+                    existing.relay = relay;
+                    relays.push(existing);
+                }
+                None => relays.push(RelayWithPing::new(relay)),
+            }
+        }
+    }
+
+    /// Forget the health history of every relay, for when checks start
+    /// measuring something else.
+    async fn clear_history(&self) {
+        for relay in self.relays.write().await.iter_mut() {
+            relay.history.clear();
+            relay.health = RelayHealth::default();
+        }
+    }
+
+    /// Host of relay `id` if it is known and not known to be down.
+    pub async fn usable_host(&self, id: &str) -> Option<String> {
         let relays = self.relays.read().await;
-        let selected = self.selected.read().await;
         relays
             .iter()
-            .find(|r| r.relay.id == *selected)
+            .find(|r| r.relay.id == id && (r.ping.is_some() || r.checking))
             .map(|r| r.relay.host.clone())
     }
 
+    /// Host to connect through: the selected relay, or the fastest
+    /// reachable one if the selected relay stopped responding.
+    pub async fn connect_host(&self) -> Option<String> {
+        let relays = self.relays.read().await;
+        let selected = self.selected.read().await;
+        match relays.iter().find(|r| r.relay.id == *selected) {
+            Some(relay) if relay.ping.is_some() || relay.checking => Some(relay.relay.host.clone()),
+            current => {
+                let (fallback, ping) = best_relay(&relays, RelaySelection::LowestMedian)?;
+                tracing::warn!(
+                    "Relay {} is unreachable, falling back to {} ({}ms)",
+                    current.map_or("(none)", |r| r.relay.id.as_str()),
+                    fallback.relay.id,
+                    ping
+                );
+                Some(fallback.relay.host.clone())
+            }
+        }
+    }
+
+    /// `host`, or the fastest reachable relay if `host` is a relay that
+    /// stopped responding. Used when relaunching with saved connection
+    /// details.
+    pub async fn fallback_host(&self, host: String) -> String {
+        let relays = self.relays.read().await;
+        let unreachable = relays
+            .iter()
+            .any(|r| r.relay.host == host && r.ping.is_none() && !r.checking);
+        if !unreachable {
+            return host;
+        }
+        match best_relay(&relays, RelaySelection::LowestMedian) {
+            Some((fallback, _)) => {
+                tracing::warn!(
+                    "Relay {} is unreachable, falling back to {}",
+                    host,
+                    fallback.relay.host
+                );
+                fallback.relay.host.clone()
+            }
+            None => host,
+        }
+    }
+
+    /// Pick a relay according to `policy`. Returns the newly selected relay
+    /// id if the selection changed.
+    pub async fn reselect(&self, policy: RelaySelection, hysteresis_ms: u32) -> Option<String> {
+        let relays = self.relays.read().await;
+        let mut selected = self.selected.write().await;
+        let (best, best_score) = best_relay(&relays, policy)?;
+
+        let current = relays.iter().find(|r| r.relay.id == *selected);
+        let switch = match current {
+            None => true,
+            // Don't give up on a restored choice before it has been pinged
+            Some(current) if current.checking => false,
+            Some(_) if policy == RelaySelection::Manual => false,
+            Some(current) => match score(current, policy) {
+                None => true,
+                Some(current_score) if policy == RelaySelection::Sticky => {
+                    best_score.saturating_add(hysteresis_ms) < current_score
+                }
+                Some(current_score) => best_score < current_score,
+            },
+        };
+
+        if !switch || best.relay.id == *selected {
+            return None;
+        }
+        tracing::info!(
+            "Auto-selected relay: {} ({}ms, {:?})",
+            best.relay.id,
+            best_score,
+            policy
+        );
+        selected.clone_from(&best.relay.id);
+        Some(best.relay.id.clone())
+    }
+
     #[allow(dead_code)]
     pub async fn all_relays_pinged(&self) -> bool {
         let relays = self.relays.read().await;
         relays.iter().all(|r| !r.checking)
     }
 
-    async fn update_relay_ping(&self, id: &str, ping: Option<u32>) {
+    async fn update_relay_ping(&self, id: &str, result: Option<PingResult>) {
         let mut relays = self.relays.write().await;
         if let Some(relay) = relays.iter_mut().find(|r| r.relay.id == id) {
-            relay.ping = ping;
+            relay.ping = result.map(|r| r.median);
+            relay.p95 = result.map(|r| r.p95);
+            relay.connect = result.and_then(|r| r.connect);
+            relay.topic = result.and_then(|r| r.topic);
             relay.checking = false;
+            relay.record_check(relay.ping);
         }
     }
 }
 
-#[cfg(feature = "cm_ss13")]
-fn get_default_relays() -> Vec<Relay> {
-    vec![
-        Relay {
-            id: "direct".to_string(),
-            name: "Direct".to_string(),
-            host: "direct.cm-ss13.com".to_string(),
-        },
-        Relay {
-            id: "nyc".to_string(),
-            name: "NYC".to_string(),
-            host: "nyc.cm-ss13.com".to_string(),
-        },
-        Relay {
-            id: "uk".to_string(),
-            name: "UK".to_string(),
-            host: "uk.cm-ss13.com".to_string(),
-        },
-        Relay {
-            id: "eu-e".to_string(),
-            name: "EU East".to_string(),
-            host: "eu-e.cm-ss13.com".to_string(),
-        },
-        Relay {
-            id: "eu-w".to_string(),
-            name: "EU West".to_string(),
-            host: "eu-w.cm-ss13.com".to_string(),
-        },
-        Relay {
-            id: "aus".to_string(),
-            name: "Australia".to_string(),
-            host: "aus.cm-ss13.com".to_string(),
-        },
-        Relay {
-            id: "us-e".to_string(),
-            name: "US East".to_string(),
-            host: "us-e.cm-ss13.com".to_string(),
-        },
-        Relay {
-            id: "us-w".to_string(),
-            name: "US West".to_string(),
-            host: "us-w.cm-ss13.com".to_string(),
-        },
-        Relay {
-            id: "asia-se".to_string(),
-            name: "SE Asia".to_string(),
-            host: "asia-se.cm-ss13.com".to_string(),
-        },
-    ]
-}
-
-#[cfg(not(feature = "cm_ss13"))]
-fn get_default_relays() -> Vec<Relay> {
-    vec![]
+fn builtin_relays() -> Vec<Relay> {
+    crate::config::get_config()
+        .relays
+        .iter()
+        .map(|r| Relay {
+            id: r.id.to_string(),
+            name: r.name.to_string(),
+            host: r.host.to_string(),
+            source: RelaySource::Builtin,
+        })
+        .collect()
+}
+
+async fn fetch_relay_manifest(url: &str) -> Result<Vec<Relay>, reqwest::Error> {
+    let relays: Vec<Relay> = reqwest::Client::new()
+        .get(url)
+        .timeout(MANIFEST_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(relays
+        .into_iter()
+        .map(|relay| Relay {
+            source: RelaySource::Manifest,
+            ..relay
+        })
+        .collect())
+}
+
+/// Rebuild the relay list from the launcher config, the relay manifest and
+/// the player's own relays, in that order of precedence.
+async fn refresh_relay_list(state: &RelayState, handle: &AppHandle) {
+    let mut list = builtin_relays();
+
+    if let Some(url) = crate::config::get_config().urls.relay_manifest {
+        match fetch_relay_manifest(url).await {
+            Ok(relays) => list.extend(relays),
+            Err(e) => {
+                tracing::warn!("Failed to fetch relay manifest: {}", e);
+                // Keep what the last fetch returned
+                list.extend(
+                    state
+                        .get_relays()
+                        .await
+                        .into_iter()
+                        .map(|r| r.relay)
+                        .filter(|r| r.source == RelaySource::Manifest),
+                );
+            }
+        }
+    }
+
+    if let Ok(settings) = load_settings(handle) {
+        list.extend(settings.custom_relays);
+    }
+
+    state.set_relay_list(list).await;
 }
 
 #[allow(clippy::cast_possible_truncation, clippy::arithmetic_side_effects)] // ping times in ms are small
-async fn ping_relay(host: &str) -> Option<u32> {
+async fn ping_relay(host: &str) -> Option<PingResult> {
     let url = format!("wss://{host}:{PING_PORT}");
 
     let connect_result = tokio::time::timeout(PING_TIMEOUT, connect_async(&url)).await;
@@ -179,62 +404,187 @@ async fn ping_relay(host: &str) -> Option<u32> {
 
     let _ = ws_stream.close(None).await;
 
-    if ping_times.is_empty() {
-        None
-    } else {
-        let avg = ping_times.iter().sum::<u32>() / ping_times.len() as u32;
-        Some(avg)
+    ping_times.sort_unstable();
+    Some(PingResult {
+        median: percentile(&ping_times, 50)?,
+        p95: percentile(&ping_times, 95)?,
+        connect: None,
+        topic: None,
+    })
+}
+
+/// Probe `host:port` the way the game reaches it: time a TCP connect, then
+/// a `?ping` topic that the relay forwards to the game server. Relays are
+/// ranked by connect time only, so every relay is measured the same way
+/// whether or not the server answers topics; the topic round trip is just
+/// reported.
+#[allow(clippy::cast_possible_truncation)] // ping times in ms are small
+async fn probe_game_port(host: &str, port: u16) -> Option<PingResult> {
+    let addr = match tokio::net::lookup_host((host, port)).await {
+        Ok(mut addrs) => addrs.next()?,
+        Err(e) => {
+            tracing::debug!("Failed to resolve relay {}: {}", host, e);
+            return None;
+        }
+    };
+
+    let mut connect_times = Vec::with_capacity(GAME_PROBE_COUNT as usize);
+    let mut topic_times = Vec::with_capacity(GAME_PROBE_COUNT as usize);
+
+    for _ in 0..GAME_PROBE_COUNT {
+        let start = Instant::now();
+        match tokio::time::timeout(PING_TIMEOUT, tokio::net::TcpStream::connect(addr)).await {
+            Ok(Ok(_stream)) => connect_times.push(start.elapsed().as_millis() as u32),
+            Ok(Err(e)) => {
+                tracing::debug!("TCP connection error for {}:{}: {}", host, port, e);
+                break;
+            }
+            Err(_) => {
+                tracing::debug!("TCP connection timeout for {}:{}", host, port);
+                break;
+            }
+        }
+
+        let start = Instant::now();
+        let topic = tokio::task::spawn_blocking(move || http2byond::send_byond(&addr, "?ping"));
+        if let Ok(Ok(Ok(_))) = tokio::time::timeout(PING_TIMEOUT, topic).await {
+            topic_times.push(start.elapsed().as_millis() as u32);
+        }
     }
+
+    connect_times.sort_unstable();
+    topic_times.sort_unstable();
+    Some(PingResult {
+        median: percentile(&connect_times, 50)?,
+        p95: percentile(&connect_times, 95)?,
+        connect: percentile(&connect_times, 50),
+        topic: percentile(&topic_times, 50),
+    })
 }
 
-pub async fn init_relays(state: &Arc<RelayState>, handle: &AppHandle) {
+/// Name of the server the player is on, or last joined.
+fn target_server_name(handle: &AppHandle) -> Option<String> {
+    let presence = handle.try_state::<Arc<PresenceManager>>()?;
+    presence
+        .get_game_session()
+        .map(|session| session.server_name)
+        .or_else(|| {
+            presence
+                .get_last_connection_params()
+                .map(|params| params.server_name)
+        })
+}
+
+/// Game port for the game port probe, or `None` to use the WebSocket probe.
+async fn probe_port(handle: &AppHandle) -> Option<u16> {
+    let settings = load_settings(handle).unwrap_or_default();
+    if settings.relay_probe != RelayProbe::GamePort {
+        return None;
+    }
+
+    let name = settings
+        .relay_probe_server
+        .or_else(|| target_server_name(handle));
+    let servers = handle.try_state::<Arc<ServerState>>()?.get_servers().await;
+    let server = name.and_then(|name| servers.into_iter().find(|s| s.name == name));
+    let port = server
+        .as_ref()
+        .and_then(|s| s.url.rsplit(':').next())
+        .and_then(|port| port.parse().ok());
+    if port.is_none() {
+        tracing::warn!("No server to probe relays through, using the WebSocket probe");
+    }
+    port
+}
+
+async fn check_relay(host: &str, game_port: Option<u16>) -> Option<PingResult> {
+    match game_port {
+        Some(port) => probe_game_port(host, port).await,
+        None => ping_relay(host).await,
+    }
+}
+
+/// Nearest-rank percentile of sorted samples.
+fn percentile(sorted: &[u32], pct: usize) -> Option<u32> {
+    let rank = sorted.len().saturating_mul(pct).div_ceil(100);
+    sorted.get(rank.saturating_sub(1)).copied()
+}
+
+fn selection_settings(handle: &AppHandle) -> (RelaySelection, u32) {
+    let settings = load_settings(handle).unwrap_or_default();
+    (settings.relay_selection, settings.relay_hysteresis_ms)
+}
+
+/// Re-run selection and tell the frontend if it changed. Automatic picks
+/// only live in `RelayState`; settings keep the user's own choice, which is
+/// written by the UI and must not be raced from here.
+async fn reselect_and_emit(state: &RelayState, handle: &AppHandle) {
+    let (policy, hysteresis_ms) = selection_settings(handle);
+    if let Some(id) = state.reselect(policy, hysteresis_ms).await {
+        let _ = handle.emit("relay-selected", &id);
+    }
+}
+
+/// `host`, or the fastest reachable relay if `host` is a relay that stopped
+/// responding.
+pub async fn fallback_host(app: &AppHandle, host: String) -> String {
+    match app.try_state::<Arc<RelayState>>() {
+        Some(state) => state.fallback_host(host).await,
+        None => host,
+    }
+}
+
+async fn ping_one_relay(
+    state: &RelayState,
+    handle: &AppHandle,
+    id: &str,
+    host: &str,
+    game_port: Option<u16>,
+) {
+    let result = check_relay(host, game_port).await;
+    state.update_relay_ping(id, result).await;
+    if result.is_some() {
+        reselect_and_emit(state, handle).await;
+    }
+
     let relays = state.get_relays().await;
+    let _ = handle.emit("relays-updated", &relays);
+}
 
-    let state_clone = Arc::clone(state);
-    let handle_clone = handle.clone();
+/// Ping every relay at once, updating the selection as results come in.
+async fn ping_all_relays(state: &Arc<RelayState>, handle: &AppHandle) {
+    let relays = state.get_relays().await;
+    let game_port = probe_port(handle).await;
 
     let ping_futures: Vec<_> = relays
         .iter()
-        .map(|r| {
-            let id = r.relay.id.clone();
-            let host = r.relay.host.clone();
-            let state = Arc::clone(&state_clone);
-            let handle = handle_clone.clone();
-
-            async move {
-                let ping = ping_relay(&host).await;
-                state.update_relay_ping(&id, ping).await;
-
-                if let Some(ping) = ping {
-                    let current_selected = state.get_selected().await;
-                    let relays = state.get_relays().await;
-
-                    let current_ping = relays
-                        .iter()
-                        .find(|r| r.relay.id == current_selected)
-                        .and_then(|r| r.ping);
-
-                    let should_select =
-                        current_selected.is_empty() || current_ping.is_none_or(|p| ping < p);
-
-                    if should_select {
-                        state.set_selected(id.clone()).await;
-                        tracing::info!("Auto-selected relay: {} ({}ms)", id, ping);
-                        let _ = handle.emit("relay-selected", &id);
-                    }
-
-                    let _ = handle.emit("relays-updated", &relays);
-                } else {
-                    let relays = state.get_relays().await;
-                    let _ = handle.emit("relays-updated", &relays);
-                }
-
-                (id, ping)
-            }
-        })
+        .map(|r| ping_one_relay(state, handle, &r.relay.id, &r.relay.host, game_port))
         .collect();
 
     futures_util::future::join_all(ping_futures).await;
+    // A relay that was selected but is now unreachable is only replaced
+    // once every result is in
+    reselect_and_emit(state, handle).await;
+}
+
+pub async fn init_relays(state: &Arc<RelayState>, handle: &AppHandle) {
+    refresh_relay_list(state, handle).await;
+    if let Some(id) = load_settings(handle).ok().and_then(|s| s.selected_relay) {
+        if state.get_relays().await.iter().any(|r| r.relay.id == id) {
+            state.set_selected(id).await;
+        }
+    }
+
+    ping_all_relays(state, handle).await;
+}
+
+pub async fn relay_refresh_background_task(handle: AppHandle, state: Arc<RelayState>) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(RELAY_REFRESH_INTERVAL_SECS)).await;
+        tracing::debug!("Re-pinging relays");
+        refresh_relay_list(&state, &handle).await;
+        ping_all_relays(&state, &handle).await;
+    }
 }
 
 #[tauri::command]
@@ -259,6 +609,269 @@ pub async fn set_selected_relay(
     handle: AppHandle,
 ) -> CommandResult<()> {
     state.set_selected(id.clone()).await;
+
+    // Picking a relay by hand turns off automatic selection
+    let mut settings = load_settings(&handle)?;
+    settings.selected_relay = Some(id.clone());
+    settings.relay_selection = RelaySelection::Manual;
+    save_settings(&handle, &settings)?;
+
     let _ = handle.emit("relay-selected", &id);
     Ok(())
 }
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_relay_selection(
+    policy: RelaySelection,
+    hysteresis_ms: Option<u32>,
+    state: tauri::State<'_, Arc<RelayState>>,
+    handle: AppHandle,
+) -> CommandResult<AppSettings> {
+    let mut settings = load_settings(&handle)?;
+    settings.relay_selection = policy;
+    if let Some(hysteresis_ms) = hysteresis_ms {
+        settings.relay_hysteresis_ms = hysteresis_ms;
+    }
+    save_settings(&handle, &settings)?;
+
+    reselect_and_emit(&state, &handle).await;
+    load_settings(&handle)
+}
+
+/// Add a relay of the player's own and start checking it.
+#[tauri::command]
+#[specta::specta]
+pub async fn add_custom_relay(
+    name: String,
+    host: String,
+    state: tauri::State<'_, Arc<RelayState>>,
+    handle: AppHandle,
+) -> CommandResult<Vec<RelayWithPing>> {
+    let host = host.trim().to_lowercase();
+    if url::Host::parse(&host).is_err() {
+        return Err(CommandError::InvalidInput(format!(
+            "Invalid relay host: {host}"
+        )));
+    }
+    let name = match name.trim() {
+        "" => host.clone(),
+        name => name.to_string(),
+    };
+    let relay = Relay {
+        id: format!("custom-{host}"),
+        name,
+        host,
+        source: RelaySource::Custom,
+    };
+
+    let mut settings = load_settings(&handle)?;
+    settings.custom_relays.retain(|r| r.id != relay.id);
+    settings.custom_relays.push(relay.clone());
+    save_settings(&handle, &settings)?;
+
+    refresh_relay_list(&state, &handle).await;
+    let relays = state.get_relays().await;
+    let _ = handle.emit("relays-updated", &relays);
+
+    let state = Arc::clone(state.inner());
+    tauri::async_runtime::spawn(async move {
+        let game_port = probe_port(&handle).await;
+        ping_one_relay(&state, &handle, &relay.id, &relay.host, game_port).await;
+    });
+
+    Ok(relays)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_custom_relay(
+    id: String,
+    state: tauri::State<'_, Arc<RelayState>>,
+    handle: AppHandle,
+) -> CommandResult<Vec<RelayWithPing>> {
+    let mut settings = load_settings(&handle)?;
+    settings.custom_relays.retain(|r| r.id != id);
+    settings.server_relays.retain(|_, relay_id| *relay_id != id);
+    save_settings(&handle, &settings)?;
+
+    refresh_relay_list(&state, &handle).await;
+    reselect_and_emit(&state, &handle).await;
+    let relays = state.get_relays().await;
+    let _ = handle.emit("relays-updated", &relays);
+    Ok(relays)
+}
+
+/// Switch how relays are checked and check them all again.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_relay_probe(
+    probe: RelayProbe,
+    server_name: Option<String>,
+    state: tauri::State<'_, Arc<RelayState>>,
+    handle: AppHandle,
+) -> CommandResult<AppSettings> {
+    let mut settings = load_settings(&handle)?;
+    let previous = settings.relay_probe;
+    settings.relay_probe = probe;
+    settings.relay_probe_server = server_name;
+    save_settings(&handle, &settings)?;
+    tracing::info!("Relay probe set to {:?}", probe);
+    if previous != probe {
+        state.clear_history().await;
+    }
+
+    let state = Arc::clone(state.inner());
+    let ping_handle = handle.clone();
+    tauri::async_runtime::spawn(async move {
+        ping_all_relays(&state, &ping_handle).await;
+    });
+
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{percentile, Relay, RelaySource, RelayState, RelayWithPing};
+    use crate::settings::RelaySelection;
+    use tokio::sync::RwLock;
+
+    fn relay(id: &str, ping: Option<u32>, p95: Option<u32>) -> RelayWithPing {
+        let mut relay = RelayWithPing::new(Relay {
+            id: id.to_string(),
+            name: id.to_string(),
+            host: format!("{id}.example.com"),
+            source: RelaySource::Builtin,
+        });
+        relay.ping = ping;
+        relay.p95 = p95;
+        relay.checking = false;
+        relay
+    }
+
+    fn state(relays: Vec<RelayWithPing>, selected: &str) -> RelayState {
+        RelayState {
+            relays: RwLock::new(relays),
+            selected: RwLock::new(selected.to_string()),
+        }
+    }
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let samples = [10, 20, 30, 40, 50, 60, 70, 80, 90, 100];
+        assert_eq!(percentile(&samples, 50), Some(50));
+        assert_eq!(percentile(&samples, 95), Some(100));
+        assert_eq!(percentile(&samples, 100), Some(100));
+        assert_eq!(percentile(&samples, 1), Some(10));
+    }
+
+    #[test]
+    fn percentile_of_few_samples() {
+        assert_eq!(percentile(&[42], 50), Some(42));
+        assert_eq!(percentile(&[42], 95), Some(42));
+        assert_eq!(percentile(&[10, 20], 50), Some(10));
+        assert_eq!(percentile(&[], 50), None);
+    }
+
+    #[tokio::test]
+    async fn selects_when_nothing_selected() {
+        let state = state(
+            vec![
+                relay("a", Some(50), Some(60)),
+                relay("b", Some(30), Some(90)),
+            ],
+            "",
+        );
+        assert_eq!(
+            state.reselect(RelaySelection::Sticky, 20).await,
+            Some("b".to_string())
+        );
+        assert_eq!(state.get_selected().await, "b");
+    }
+
+    #[tokio::test]
+    async fn sticky_keeps_current_within_hysteresis() {
+        let state = state(
+            vec![relay("a", Some(50), None), relay("b", Some(35), None)],
+            "a",
+        );
+        assert_eq!(state.reselect(RelaySelection::Sticky, 20).await, None);
+        assert_eq!(state.get_selected().await, "a");
+    }
+
+    #[tokio::test]
+    async fn sticky_switches_past_hysteresis() {
+        let state = state(
+            vec![relay("a", Some(50), None), relay("b", Some(20), None)],
+            "a",
+        );
+        assert_eq!(
+            state.reselect(RelaySelection::Sticky, 20).await,
+            Some("b".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn lowest_p95_ranks_by_p95() {
+        let state = state(
+            vec![
+                relay("a", Some(20), Some(200)),
+                relay("b", Some(40), Some(50)),
+            ],
+            "a",
+        );
+        assert_eq!(
+            state.reselect(RelaySelection::LowestP95, 20).await,
+            Some("b".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn manual_keeps_reachable_choice() {
+        let state = state(
+            vec![relay("a", Some(200), None), relay("b", Some(10), None)],
+            "a",
+        );
+        assert_eq!(state.reselect(RelaySelection::Manual, 20).await, None);
+    }
+
+    #[tokio::test]
+    async fn leaves_unreachable_relay() {
+        let state = state(
+            vec![relay("a", None, None), relay("b", Some(80), None)],
+            "a",
+        );
+        assert_eq!(
+            state.reselect(RelaySelection::Sticky, 20).await,
+            Some("b".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn manual_falls_back_only_when_connecting() {
+        let state = state(
+            vec![relay("a", None, None), relay("b", Some(80), None)],
+            "a",
+        );
+        assert_eq!(state.reselect(RelaySelection::Manual, 20).await, None);
+        assert_eq!(
+            state.connect_host().await,
+            Some("b.example.com".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn waits_for_restored_choice_to_be_checked() {
+        let mut restored = relay("a", None, None);
+        restored.checking = true;
+        let state = state(vec![restored, relay("b", Some(10), None)], "a");
+        assert_eq!(state.reselect(RelaySelection::LowestMedian, 20).await, None);
+    }
+
+    #[tokio::test]
+    async fn nothing_reachable_keeps_selection() {
+        let state = state(vec![relay("a", None, None), relay("b", None, None)], "a");
+        assert_eq!(state.reselect(RelaySelection::LowestMedian, 20).await, None);
+        assert_eq!(state.get_selected().await, "a");
+    }
+}
@@ -1,4 +1,5 @@
 use crate::config::get_config;
+use crate::control_server::{ControlServer, LauncherMessage, ServerSummary};
 use crate::error::{CommandError, CommandResult};
 use crate::settings::load_settings;
 use serde::{Deserialize, Serialize};
@@ -453,6 +454,12 @@ pub async fn server_fetch_background_task(handle: AppHandle, state: Arc<ServerSt
             Ok(servers) => {
                 check_and_send_notifications(&handle, &state, &servers).await;
 
+                if let Some(control_server) = handle.try_state::<ControlServer>() {
+                    control_server.broadcast(&LauncherMessage::ServersUpdated {
+                        servers: servers.iter().map(ServerSummary::from_server).collect(),
+                    });
+                }
+
                 *state.servers.write().await = servers.clone();
                 let _ = handle.emit("servers-updated", ServerUpdateEvent { servers });
             }
@@ -1,6 +1,6 @@
 use crate::error::{CommandError, CommandResult};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
@@ -35,6 +35,211 @@ pub enum RenderingPipeline {
     Dxvk,
     #[serde(rename = "wined3d")]
     Wined3d,
+    /// `WineD3D` with its Vulkan renderer.
+    #[serde(rename = "wined3d_vulkan")]
+    Wined3dVulkan,
+    /// `WineD3D` on plain OpenGL without command stream threading, for
+    /// drivers the other pipelines don't work with.
+    GlFallback,
+}
+
+impl RenderingPipeline {
+    pub const ALL: [Self; 4] = [
+        Self::Dxvk,
+        Self::Wined3d,
+        Self::Wined3dVulkan,
+        Self::GlFallback,
+    ];
+
+    /// Id of the pipeline's own Wine prefix.
+    pub fn prefix_id(self) -> &'static str {
+        match self {
+            Self::Dxvk => "dxvk",
+            Self::Wined3d => "wined3d",
+            Self::Wined3dVulkan => "wined3d_vulkan",
+            Self::GlFallback => "gl_fallback",
+        }
+    }
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Dxvk => "DXVK",
+            Self::Wined3d => "WineD3D (OpenGL)",
+            Self::Wined3dVulkan => "WineD3D (Vulkan)",
+            Self::GlFallback => "OpenGL fallback",
+        }
+    }
+}
+
+/// A named Wine prefix set up for one rendering pipeline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct WinePrefixProfile {
+    pub id: String,
+    pub name: String,
+    pub pipeline: RenderingPipeline,
+}
+
+/// Extra environment, wrapper commands and CPU affinity applied when the game
+/// is launched through Wine.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct LaunchProfile {
+    pub id: String,
+    pub name: String,
+    /// Set after the launcher's own Wine environment, so these win, except
+    /// for the variables in [`RESERVED_ENV_KEYS`].
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Commands the game is started through, outermost first, each given as a
+    /// command line such as `gamemoderun` or `nice -n 5`.
+    #[serde(default)]
+    pub wrappers: Vec<String>,
+    /// CPUs the game may run on, as a list like `0-3,6`.
+    #[serde(default)]
+    pub cpu_affinity: Option<String>,
+}
+
+impl LaunchProfile {
+    /// The wrapper command lines split into program and arguments, in order.
+    pub fn wrapper_args(&self) -> Vec<String> {
+        self.wrappers
+            .iter()
+            .flat_map(|w| w.split_whitespace())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// The CPUs from `cpu_affinity`, or `None` when unset.
+    pub fn cpus(&self) -> Option<Vec<usize>> {
+        self.cpu_affinity.as_deref().and_then(parse_cpu_list)
+    }
+}
+
+/// Environment variables the launcher sets to find Wine and the prefix,
+/// which launch profiles can't override. `WINEDEBUG` is left to them for
+/// troubleshooting.
+pub const RESERVED_ENV_KEYS: &[&str] = &[
+    "WINEPREFIX",
+    "WINESERVER",
+    "WINEDLLPATH",
+    "LD_LIBRARY_PATH",
+    "PATH",
+    "BROWSER",
+    "WEBVIEW2_USER_DATA_FOLDER",
+];
+
+/// Check a launch profile before it is saved.
+fn validate_launch_profile(profile: &LaunchProfile) -> CommandResult<()> {
+    if profile.id.trim().is_empty() || profile.name.trim().is_empty() {
+        return Err(CommandError::InvalidInput(
+            "Launch profile needs an id and a name".to_string(),
+        ));
+    }
+    if let Some(key) = profile
+        .env
+        .keys()
+        .find(|k| k.is_empty() || k.contains('=') || k.contains('\0'))
+    {
+        return Err(CommandError::InvalidInput(format!(
+            "Invalid environment variable name: {key:?}"
+        )));
+    }
+    if let Some(key) = profile
+        .env
+        .keys()
+        .find(|k| RESERVED_ENV_KEYS.contains(&k.as_str()))
+    {
+        return Err(CommandError::InvalidInput(format!(
+            "{key} is set by the launcher and can't be changed"
+        )));
+    }
+    if profile.wrappers.iter().any(|w| w.trim().is_empty()) {
+        return Err(CommandError::InvalidInput(
+            "Wrapper commands cannot be empty".to_string(),
+        ));
+    }
+    if let Some(ref list) = profile.cpu_affinity {
+        if parse_cpu_list(list).is_none() {
+            return Err(CommandError::InvalidInput(format!(
+                "Invalid CPU list: {list}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Highest CPU index accepted in an affinity list.
+const MAX_CPU_INDEX: usize = 1023;
+
+/// Parse a CPU list such as `0-3,6` into sorted, deduplicated CPU indices.
+pub fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for part in list.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (start, end): (usize, usize) = if let Some((start, end)) = part.split_once('-') {
+            (start.trim().parse().ok()?, end.trim().parse().ok()?)
+        } else {
+            let cpu = part.parse().ok()?;
+            (cpu, cpu)
+        };
+        if start > end || end > MAX_CPU_INDEX {
+            return None;
+        }
+        cpus.extend(start..=end);
+    }
+    cpus.sort_unstable();
+    cpus.dedup();
+    (!cpus.is_empty()).then_some(cpus)
+}
+
+/// How the relay used for connections is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum RelaySelection {
+    /// Keep the relay the player picked.
+    Manual,
+    LowestMedian,
+    LowestP95,
+    /// Keep the current relay until another is faster by more than the
+    /// hysteresis margin.
+    #[default]
+    Sticky,
+}
+
+/// How relays are checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayProbe {
+    /// WebSocket echo on the relay's ping port.
+    #[default]
+    WebSocket,
+    /// TCP connect and topic round trip on a server's game port through the
+    /// relay, the same path the game uses.
+    GamePort,
+}
+
+/// Which details of the game's own status are shared through rich presence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct PresencePrivacy {
+    pub show_round_time: bool,
+    pub show_lobby_status: bool,
+    pub show_role: bool,
+    pub show_faction: bool,
+    pub show_observer: bool,
+}
+
+impl Default for PresencePrivacy {
+    fn default() -> Self {
+        // Role and faction can give away what a player is up to in-round, so
+        // they are opt-in
+        Self {
+            show_round_time: true,
+            show_lobby_status: true,
+            show_role: false,
+            show_faction: false,
+            show_observer: true,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
@@ -50,6 +255,19 @@ pub struct AppSettings {
     pub locale: Option<String>,
     #[serde(default)]
     pub rendering_pipeline: RenderingPipeline,
+    /// Wine prefixes created by the player, on top of one per pipeline.
+    #[serde(default)]
+    pub wine_prefixes: Vec<WinePrefixProfile>,
+    /// Id of the Wine prefix games run in. Unset uses the prefix of
+    /// `rendering_pipeline`.
+    #[serde(default)]
+    pub active_wine_prefix: Option<String>,
+    /// Id of the Wine build games run with. Unset uses the bundled Wine.
+    #[serde(default)]
+    pub wine_runner: Option<String>,
+    /// Wine build directories added by the player.
+    #[serde(default)]
+    pub custom_wine_runners: Vec<String>,
     #[serde(default)]
     pub last_played_server: Option<String>,
     #[serde(default)]
@@ -74,12 +292,88 @@ pub struct AppSettings {
     pub trusted_direct_connect_addresses: HashSet<String>,
     #[serde(default = "default_true")]
     pub rich_presence_enabled: bool,
+    #[serde(default)]
+    pub presence_privacy: PresencePrivacy,
+    /// BYOND version forced for every server, unless a per-server override is set.
+    #[serde(default)]
+    pub byond_version_override: Option<String>,
+    /// BYOND versions forced for specific servers, keyed by server name.
+    #[serde(default)]
+    pub server_byond_versions: HashMap<String, String>,
+    /// Relaunch the game automatically when it crashes.
+    #[serde(default)]
+    pub auto_reconnect: bool,
+    #[serde(default = "default_auto_reconnect_max_attempts")]
+    pub auto_reconnect_max_attempts: u32,
+    #[serde(default)]
+    pub relay_selection: RelaySelection,
+    /// How much faster another relay must be before `Sticky` switches to it.
+    #[serde(default = "default_relay_hysteresis_ms")]
+    pub relay_hysteresis_ms: u32,
+    #[serde(default)]
+    pub relay_probe: RelayProbe,
+    /// Server whose game port `GamePort` probes, by name. Unset uses the
+    /// server the player is on or last joined.
+    #[serde(default)]
+    pub relay_probe_server: Option<String>,
+    /// Relay the user last picked themselves, restored on startup.
+    #[serde(default)]
+    pub selected_relay: Option<String>,
+    /// Relays added by the player.
+    #[serde(default)]
+    pub custom_relays: Vec<crate::relays::Relay>,
+    /// Relay ids to use for specific servers, keyed by server name.
+    #[serde(default)]
+    pub server_relays: HashMap<String, String>,
+    /// Launch profiles defined by the player.
+    #[serde(default)]
+    pub launch_profiles: Vec<LaunchProfile>,
+    /// Launch profile used for every server, unless a per-server one is set.
+    #[serde(default)]
+    pub default_launch_profile: Option<String>,
+    /// Launch profile ids to use for specific servers, keyed by server name.
+    /// Singleplayer uses [`crate::singleplayer::SINGLEPLAYER_SERVER_NAME`].
+    #[serde(default)]
+    pub server_launch_profiles: HashMap<String, String>,
+    /// Send a hardware ID to servers, where the launcher lets players opt out.
+    #[serde(default = "default_true")]
+    pub hwid_enabled: bool,
 }
 
 fn default_true() -> bool {
     true
 }
 
+const MAX_AUTO_RECONNECT_ATTEMPTS: u32 = 10;
+
+fn default_auto_reconnect_max_attempts() -> u32 {
+    3
+}
+
+fn default_relay_hysteresis_ms() -> u32 {
+    20
+}
+
+impl AppSettings {
+    /// The BYOND version the user has forced for `server_name`, falling back to
+    /// the global override.
+    pub fn byond_version_override_for(&self, server_name: &str) -> Option<&str> {
+        self.server_byond_versions
+            .get(server_name)
+            .or(self.byond_version_override.as_ref())
+            .map(String::as_str)
+    }
+
+    /// The launch profile for `server_name`, falling back to the default one.
+    pub fn launch_profile_for(&self, server_name: &str) -> Option<&LaunchProfile> {
+        let id = self
+            .server_launch_profiles
+            .get(server_name)
+            .or(self.default_launch_profile.as_ref())?;
+        self.launch_profiles.iter().find(|p| &p.id == id)
+    }
+}
+
 impl Default for AppSettings {
     fn default() -> Self {
         let config = crate::config::get_config();
@@ -112,6 +406,10 @@ impl Default for AppSettings {
             age_verified: false,
             locale: None,
             rendering_pipeline: RenderingPipeline::default(),
+            wine_prefixes: Vec::new(),
+            active_wine_prefix: None,
+            wine_runner: None,
+            custom_wine_runners: Vec::new(),
             last_played_server: None,
             favorite_servers: HashSet::new(),
             filter_tags: HashSet::new(),
@@ -124,6 +422,22 @@ impl Default for AppSettings {
             search_query: None,
             trusted_direct_connect_addresses: HashSet::new(),
             rich_presence_enabled: true,
+            presence_privacy: PresencePrivacy::default(),
+            byond_version_override: None,
+            server_byond_versions: HashMap::new(),
+            auto_reconnect: false,
+            auto_reconnect_max_attempts: default_auto_reconnect_max_attempts(),
+            relay_selection: RelaySelection::default(),
+            relay_hysteresis_ms: default_relay_hysteresis_ms(),
+            relay_probe: RelayProbe::default(),
+            relay_probe_server: None,
+            selected_relay: None,
+            custom_relays: Vec::new(),
+            server_relays: HashMap::new(),
+            launch_profiles: Vec::new(),
+            default_launch_profile: None,
+            server_launch_profiles: HashMap::new(),
+            hwid_enabled: true,
         }
     }
 }
@@ -248,6 +562,8 @@ pub async fn set_rendering_pipeline(
 ) -> CommandResult<AppSettings> {
     let mut settings = load_settings(&app)?;
     settings.rendering_pipeline = pipeline;
+    // Games move to the pipeline's own prefix
+    settings.active_wine_prefix = None;
     save_settings(&app, &settings)?;
     Ok(settings)
 }
@@ -318,6 +634,165 @@ pub async fn set_rich_presence(app: AppHandle, enabled: bool) -> CommandResult<A
     Ok(settings)
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn set_presence_privacy(
+    app: AppHandle,
+    privacy: PresencePrivacy,
+) -> CommandResult<AppSettings> {
+    let mut settings = load_settings(&app)?;
+    settings.presence_privacy = privacy;
+    save_settings(&app, &settings)?;
+
+    if let Some(manager) = app.try_state::<std::sync::Arc<crate::presence::PresenceManager>>() {
+        manager.set_privacy(privacy);
+    }
+
+    Ok(settings)
+}
+
+/// Force a BYOND version for `server_name`, or globally when no server is given.
+/// Passing no version clears the override.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_byond_version_override(
+    app: AppHandle,
+    server_name: Option<String>,
+    version: Option<String>,
+) -> CommandResult<AppSettings> {
+    if let Some(ref version) = version {
+        if crate::byond::parse_byond_version(version).is_none() {
+            return Err(CommandError::InvalidInput(format!(
+                "Invalid BYOND version format: {version}"
+            )));
+        }
+    }
+
+    let mut settings = load_settings(&app)?;
+    match (server_name, version) {
+        (Some(server_name), Some(version)) => {
+            settings.server_byond_versions.insert(server_name, version);
+        }
+        (Some(server_name), None) => {
+            settings.server_byond_versions.remove(&server_name);
+        }
+        (None, version) => settings.byond_version_override = version,
+    }
+    save_settings(&app, &settings)?;
+    Ok(settings)
+}
+
+/// Use `relay_id` for `server_name` instead of the selected relay. Passing
+/// no relay clears the preference.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_server_relay(
+    app: AppHandle,
+    server_name: String,
+    relay_id: Option<String>,
+) -> CommandResult<AppSettings> {
+    let mut settings = load_settings(&app)?;
+    match relay_id {
+        Some(relay_id) => {
+            settings.server_relays.insert(server_name, relay_id);
+        }
+        None => {
+            settings.server_relays.remove(&server_name);
+        }
+    }
+    save_settings(&app, &settings)?;
+    Ok(settings)
+}
+
+/// Add a launch profile, or replace the one with the same id.
+#[tauri::command]
+#[specta::specta]
+pub async fn save_launch_profile(
+    app: AppHandle,
+    profile: LaunchProfile,
+) -> CommandResult<AppSettings> {
+    validate_launch_profile(&profile)?;
+
+    let mut settings = load_settings(&app)?;
+    match settings
+        .launch_profiles
+        .iter_mut()
+        .find(|p| p.id == profile.id)
+    {
+        Some(existing) => *existing = profile,
+        None => settings.launch_profiles.push(profile),
+    }
+    save_settings(&app, &settings)?;
+    Ok(settings)
+}
+
+/// Remove a launch profile and any server or default assignments to it.
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_launch_profile(app: AppHandle, id: String) -> CommandResult<AppSettings> {
+    let mut settings = load_settings(&app)?;
+    settings.launch_profiles.retain(|p| p.id != id);
+    settings.server_launch_profiles.retain(|_, p| *p != id);
+    if settings.default_launch_profile.as_deref() == Some(id.as_str()) {
+        settings.default_launch_profile = None;
+    }
+    save_settings(&app, &settings)?;
+    Ok(settings)
+}
+
+/// Use launch profile `profile_id` for `server_name`, or for every server
+/// when no server is given. Passing no profile clears the assignment.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_launch_profile(
+    app: AppHandle,
+    server_name: Option<String>,
+    profile_id: Option<String>,
+) -> CommandResult<AppSettings> {
+    let mut settings = load_settings(&app)?;
+    if let Some(ref id) = profile_id {
+        if !settings.launch_profiles.iter().any(|p| &p.id == id) {
+            return Err(CommandError::NotFound(format!("launch profile {id}")));
+        }
+    }
+
+    match (server_name, profile_id) {
+        (Some(server_name), Some(id)) => {
+            settings.server_launch_profiles.insert(server_name, id);
+        }
+        (Some(server_name), None) => {
+            settings.server_launch_profiles.remove(&server_name);
+        }
+        (None, id) => settings.default_launch_profile = id,
+    }
+    save_settings(&app, &settings)?;
+    Ok(settings)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_auto_reconnect(
+    app: AppHandle,
+    enabled: bool,
+    max_attempts: Option<u32>,
+) -> CommandResult<AppSettings> {
+    if let Some(max_attempts) = max_attempts {
+        if !(1..=MAX_AUTO_RECONNECT_ATTEMPTS).contains(&max_attempts) {
+            return Err(CommandError::InvalidInput(format!(
+                "Reconnect attempts must be between 1 and {MAX_AUTO_RECONNECT_ATTEMPTS}"
+            )));
+        }
+    }
+
+    let mut settings = load_settings(&app)?;
+    settings.auto_reconnect = enabled;
+    if let Some(max_attempts) = max_attempts {
+        settings.auto_reconnect_max_attempts = max_attempts;
+    }
+    save_settings(&app, &settings)?;
+    Ok(settings)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct FilterSettings {
     pub tags: Vec<String>,
@@ -346,3 +821,61 @@ pub async fn save_filter_settings(
     save_settings(&app, &settings)?;
     Ok(settings)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{parse_cpu_list, validate_launch_profile, LaunchProfile};
+
+    fn profile_with_env(key: &str) -> LaunchProfile {
+        LaunchProfile {
+            id: "perf".to_string(),
+            name: "Performance".to_string(),
+            env: HashMap::from([(key.to_string(), "1".to_string())]),
+            wrappers: Vec::new(),
+            cpu_affinity: None,
+        }
+    }
+
+    #[test]
+    fn rejects_env_the_launcher_sets() {
+        for key in ["WINEPREFIX", "WINEDLLPATH", "PATH"] {
+            assert!(
+                validate_launch_profile(&profile_with_env(key)).is_err(),
+                "{key}"
+            );
+        }
+        assert!(validate_launch_profile(&profile_with_env("DXVK_HUD")).is_ok());
+        assert!(validate_launch_profile(&profile_with_env("WINEDEBUG")).is_ok());
+    }
+
+    #[test]
+    fn parses_single_cpus_and_ranges() {
+        assert_eq!(parse_cpu_list("0-3,6"), Some(vec![0, 1, 2, 3, 6]));
+        assert_eq!(parse_cpu_list("5"), Some(vec![5]));
+        assert_eq!(parse_cpu_list(" 1 - 2 , 4 "), Some(vec![1, 2, 4]));
+    }
+
+    #[test]
+    fn sorts_and_deduplicates() {
+        assert_eq!(parse_cpu_list("4,0-2,1"), Some(vec![0, 1, 2, 4]));
+    }
+
+    #[test]
+    fn ignores_empty_entries() {
+        assert_eq!(parse_cpu_list("0,,2,"), Some(vec![0, 2]));
+    }
+
+    #[test]
+    fn rejects_invalid_lists() {
+        for list in ["", ",", "a", "3-1", "-1", "1-", "0-1024", "1024", "0x1"] {
+            assert_eq!(parse_cpu_list(list), None, "{list:?}");
+        }
+    }
+
+    #[test]
+    fn accepts_highest_cpu() {
+        assert_eq!(parse_cpu_list("1023"), Some(vec![1023]));
+    }
+}
@@ -23,6 +23,8 @@ use crate::presence::PresenceManager;
 
 const SINGLEPLAYER_DIR: &str = "singleplayer";
 const VERSION_FILE: &str = ".version";
+/// Name singleplayer sessions are reported and configured under.
+pub const SINGLEPLAYER_SERVER_NAME: &str = "Sandbox";
 
 fn get_singleplayer_config() -> CommandResult<(String, String)> {
     let config = crate::config::get_config();
@@ -438,9 +440,9 @@ pub async fn launch_singleplayer(app: AppHandle) -> CommandResult<()> {
             .env("WEBVIEW2_USER_DATA_FOLDER", &webview2_data_dir)
             .spawn()?;
 
-        app.emit("game-connected", "Sandbox").ok();
+        app.emit("game-connected", SINGLEPLAYER_SERVER_NAME).ok();
         if let Some(manager) = app.try_state::<Arc<PresenceManager>>() {
-            manager.start_game_session("Sandbox".to_string(), None, 1, child);
+            manager.start_game_session(SINGLEPLAYER_SERVER_NAME.to_string(), None, 1, child, None);
         }
     }
 
@@ -469,12 +471,13 @@ pub async fn launch_singleplayer(app: AppHandle) -> CommandResult<()> {
                 "WEBVIEW2_USER_DATA_FOLDER",
                 webview2_data_dir.to_str().unwrap(),
             )],
+            wine::launch_profile_for(&app, SINGLEPLAYER_SERVER_NAME).as_ref(),
         )
         .map_err(|e| CommandError::Io(format!("Failed to launch DreamSeeker via Wine: {e}")))?;
 
-        app.emit("game-connected", "Sandbox").ok();
+        app.emit("game-connected", SINGLEPLAYER_SERVER_NAME).ok();
         if let Some(manager) = app.try_state::<Arc<PresenceManager>>() {
-            manager.start_game_session("Sandbox".to_string(), None, 1, child);
+            manager.start_game_session(SINGLEPLAYER_SERVER_NAME.to_string(), None, 1, child, None);
         }
     }
 
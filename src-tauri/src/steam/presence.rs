@@ -1,6 +1,6 @@
 use steamworks::Client;
 
-use crate::presence::{PresenceProvider, PresenceState};
+use crate::presence::{GameDetails, PresenceProvider, PresenceState};
 
 pub struct SteamPresence {
     client: Client,
@@ -11,7 +11,13 @@ impl SteamPresence {
         Self { client }
     }
 
-    fn set_playing_status(&self, server_name: &str, player_count: u32, map_name: Option<&str>) {
+    fn set_playing_status(
+        &self,
+        server_name: &str,
+        player_count: u32,
+        map_name: Option<&str>,
+        details: &GameDetails,
+    ) {
         tracing::debug!(
             "Setting Steam presence: Playing on {} ({} players, map: {:?})",
             server_name,
@@ -20,11 +26,13 @@ impl SteamPresence {
         );
         let friends = self.client.friends();
 
-        let status = match map_name {
+        let status = details.activity().unwrap_or_else(|| match map_name {
             Some(map) => format!("{player_count} players on {map}"),
             None => format!("Playing on {server_name}"),
-        };
+        });
         friends.set_rich_presence("status", Some(&status));
+        friends.set_rich_presence("role", details.role.as_deref());
+        friends.set_rich_presence("faction", details.faction.as_deref());
 
         let encoded_server =
             url::form_urlencoded::byte_serialize(server_name.as_bytes()).collect::<String>();
@@ -32,11 +40,12 @@ impl SteamPresence {
 
         friends.set_rich_presence("players", Some(&player_count.to_string()));
         friends.set_rich_presence("name", Some(server_name));
-        if let Some(map) = map_name {
-            friends.set_rich_presence("map", Some(map));
-        }
+        friends.set_rich_presence("map", map_name);
 
-        friends.set_rich_presence("steam_display", Some("#Status_Playing_Map"));
+        friends.set_rich_presence(
+            "steam_display",
+            Some(display_token(details, map_name.is_some())),
+        );
         friends.set_rich_presence("steam_player_group", Some(server_name));
         friends.set_rich_presence("steam_player_group_size", Some(&player_count.to_string()));
     }
@@ -52,6 +61,26 @@ impl SteamPresence {
     }
 }
 
+/// The `rich-presence-localisation.vdf` token for what the player is doing.
+/// Mirrors [`GameDetails::activity`], which fills `%status%` for other
+/// providers.
+fn display_token(details: &GameDetails, has_map: bool) -> &'static str {
+    if details.in_lobby == Some(true) {
+        return "#Status_Lobby";
+    }
+    if details.observer == Some(true) {
+        return "#Status_Observing";
+    }
+    match (&details.role, &details.faction) {
+        (Some(_), Some(_)) => "#Status_Role_Faction",
+        (Some(_), None) => "#Status_Role",
+        (None, Some(_)) => "#Status_Faction",
+        (None, None) if details.in_lobby == Some(false) => "#Status_InRound",
+        (None, None) if has_map => "#Status_Playing_Map",
+        (None, None) => "#Status_Playing",
+    }
+}
+
 impl PresenceProvider for SteamPresence {
     fn name(&self) -> &'static str {
         "Steam"
@@ -64,7 +93,8 @@ impl PresenceProvider for SteamPresence {
                 server_name,
                 player_count,
                 map_name,
-            } => self.set_playing_status(server_name, *player_count, map_name.as_deref()),
+                details,
+            } => self.set_playing_status(server_name, *player_count, map_name.as_deref(), details),
             PresenceState::Disconnected => self.clear_presence(),
         }
     }
@@ -73,3 +103,32 @@ impl PresenceProvider for SteamPresence {
         self.client.friends().clear_rich_presence();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::display_token;
+    use crate::presence::GameDetails;
+
+    #[test]
+    fn picks_the_token_matching_the_details() {
+        let details = GameDetails {
+            role: Some("Medic".to_string()),
+            faction: Some("USCM".to_string()),
+            ..GameDetails::default()
+        };
+        assert_eq!(display_token(&details, true), "#Status_Role_Faction");
+
+        let details = GameDetails {
+            in_lobby: Some(true),
+            ..details
+        };
+        assert_eq!(display_token(&details, true), "#Status_Lobby");
+    }
+
+    #[test]
+    fn falls_back_to_the_server_and_map() {
+        let details = GameDetails::default();
+        assert_eq!(display_token(&details, true), "#Status_Playing_Map");
+        assert_eq!(display_token(&details, false), "#Status_Playing");
+    }
+}
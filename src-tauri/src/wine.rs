@@ -2,19 +2,29 @@
 //!
 //! This module handles:
 //! - Wine/winetricks detection and version checking
-//! - Wine prefix initialization with required dependencies
+//! - Wine prefix initialization with required dependencies, one checkpointed
+//!   step at a time so a failed setup resumes where it stopped
+//! - One prefix per rendering pipeline, plus named prefixes created by the
+//!   player, switched between without a reset
 //! - WebView2 installation within the prefix
+//! - An asset cache of installers and winetricks downloads, kept across
+//!   prefix resets and fillable from an asset pack for offline setup
+//! - Prefix health checks with a targeted repair for each
 //! - Launching executables via Wine
 //!
 //! Wine is bundled as a compressed archive (wine.tar.zst) and extracted to the
-//! app data directory on first use.
+//! app data directory on first use. System Wine, Proton builds and other Wine
+//! directories can be picked instead; each runner gets its own prefixes.
 
-use crate::settings::RenderingPipeline;
+use crate::settings::{AppSettings, LaunchProfile, RenderingPipeline, WinePrefixProfile};
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
 use std::ffi::OsStr;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Output, Stdio};
+use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager};
 
 /// Minimum required Wine version (major.minor)
@@ -26,9 +36,22 @@ const WEBVIEW2_DOWNLOAD_URL: &str = "https://go.microsoft.com/fwlink/?linkid=212
 /// Marker file to track initialization state
 const INIT_MARKER_FILE: &str = ".cm_launcher_initialized";
 
-/// Current initialization version - bump this to force re-initialization
+/// Setup steps completed in a prefix, replacing the marker file
+const PREFIX_MANIFEST_FILE: &str = ".cm_launcher_manifest.json";
+
+/// Current initialization version. Bump this and list the changed steps in
+/// `STEP_VERSIONS` to re-run only those steps on existing prefixes.
 const INIT_VERSION: u32 = 2;
 
+/// First `INIT_VERSION` with a step manifest. Prefixes with an older marker
+/// run every step again.
+const FIRST_MANIFEST_VERSION: u32 = 2;
+
+/// Steps changed since `FIRST_MANIFEST_VERSION`, by step id, with the
+/// `INIT_VERSION` that changed them. Unlisted steps date from
+/// `FIRST_MANIFEST_VERSION`.
+const STEP_VERSIONS: &[(&str, u32)] = &[];
+
 /// Resource names for bundled Wine
 const WINE_ARCHIVE_RESOURCE: &str = "wine.tar.zst";
 const WINETRICKS_RESOURCE: &str = "winetricks";
@@ -36,6 +59,90 @@ const CABEXTRACT_RESOURCE: &str = "cabextract";
 /// Directory name for extracted Wine in app data
 const WINE_EXTRACTED_DIR: &str = "wine";
 
+/// Directory in app data holding installers and winetricks downloads
+const ASSET_CACHE_DIR: &str = "wine_cache";
+/// Asset pack that may be shipped in resources, imported on first setup
+const ASSET_PACK_RESOURCE: &str = "wine-assets.tar.zst";
+/// Marker left in the asset cache once the bundled pack is imported
+const ASSET_PACK_IMPORTED_MARKER: &str = ".bundled_pack_imported";
+/// WebView2 installer within the asset cache
+const WEBVIEW2_CACHE_FILE: &str = "webview2/MicrosoftEdgeWebview2Setup.exe";
+/// Bounds on the size of a plausible `WebView2` installer, from the online
+/// bootstrapper to the standalone installer
+const WEBVIEW2_INSTALLER_SIZE: std::ops::RangeInclusive<u64> = 512 * 1024..=512 * 1024 * 1024;
+/// Winetricks download cache (`W_CACHE`) within the asset cache
+const WINETRICKS_CACHE_DIR: &str = "winetricks";
+
+/// Directory in app data holding one directory per prefix
+const PREFIXES_DIR: &str = "wine_prefixes";
+/// Directory within `PREFIXES_DIR` for the prefixes of runners other than
+/// the bundled Wine
+const RUNNER_PREFIXES_DIR: &str = "runners";
+
+/// Runner id of the bundled Wine
+const BUNDLED_RUNNER_ID: &str = "bundled";
+
+/// Directories under the home directory that Steam installs Proton builds to
+const PROTON_SEARCH_DIRS: &[&str] = &[
+    ".steam/root/compatibilitytools.d",
+    ".local/share/Steam/compatibilitytools.d",
+    ".var/app/com.valvesoftware.Steam/data/Steam/compatibilitytools.d",
+    ".steam/root/steamapps/common",
+    ".local/share/Steam/steamapps/common",
+];
+
+/// Where distributions put wineserver when it isn't on PATH
+const SYSTEM_WINESERVER_PATHS: &[&str] = &[
+    "/usr/lib/wine/wineserver",
+    "/usr/lib/wine/wineserver64",
+    "/usr/lib64/wine/wineserver",
+    "/usr/libexec/wine/wineserver",
+];
+/// The single prefix used before prefixes were per pipeline
+const LEGACY_PREFIX_DIR: &str = "wine_prefix";
+
+/// Ids of prefixes being set up, so the same one isn't set up twice at once
+static PREFIXES_IN_SETUP: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// Marks a prefix as being set up until dropped
+struct SetupGuard(String);
+
+impl SetupGuard {
+    fn acquire(id: &str) -> Option<Self> {
+        let mut in_setup = PREFIXES_IN_SETUP.lock();
+        if in_setup
+            .get_or_insert_with(HashSet::new)
+            .insert(id.to_string())
+        {
+            Some(Self(id.to_string()))
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for SetupGuard {
+    fn drop(&mut self) {
+        if let Some(in_setup) = PREFIXES_IN_SETUP.lock().as_mut() {
+            in_setup.remove(&self.0);
+        }
+    }
+}
+
+fn is_any_prefix_in_setup() -> bool {
+    PREFIXES_IN_SETUP
+        .lock()
+        .as_ref()
+        .is_some_and(|in_setup| !in_setup.is_empty())
+}
+
+fn is_prefix_in_setup(id: &str) -> bool {
+    PREFIXES_IN_SETUP
+        .lock()
+        .as_ref()
+        .is_some_and(|in_setup| in_setup.contains(id))
+}
+
 /// Winetricks verbs shared by all rendering pipelines
 const COMMON_WINETRICKS_VERBS: &[(&str, &str)] = &[
     ("vcrun2022", "Visual C++ 2022 runtime"),
@@ -56,11 +163,162 @@ fn get_winetricks_verbs(pipeline: RenderingPipeline) -> Vec<(&'static str, &'sta
     let mut verbs: Vec<(&str, &str)> = COMMON_WINETRICKS_VERBS.to_vec();
     match pipeline {
         RenderingPipeline::Dxvk => verbs.extend_from_slice(DXVK_VERBS),
-        RenderingPipeline::Wined3d => verbs.extend_from_slice(WINED3D_VERBS),
+        RenderingPipeline::Wined3d
+        | RenderingPipeline::Wined3dVulkan
+        | RenderingPipeline::GlFallback => verbs.extend_from_slice(WINED3D_VERBS),
     }
     verbs
 }
 
+/// `WineD3D` settings (name, value, type) written for a pipeline
+fn get_direct3d_registry_values(
+    pipeline: RenderingPipeline,
+) -> &'static [(&'static str, &'static str, &'static str)] {
+    match pipeline {
+        RenderingPipeline::Dxvk | RenderingPipeline::Wined3d => &[],
+        RenderingPipeline::Wined3dVulkan => &[("renderer", "vulkan", "REG_SZ")],
+        RenderingPipeline::GlFallback => {
+            &[("renderer", "gl", "REG_SZ"), ("csmt", "0", "REG_DWORD")]
+        }
+    }
+}
+
+/// One checkpointed step of prefix setup
+#[derive(Debug, Clone, Copy)]
+enum SetupStep {
+    Wineboot,
+    Winetricks(&'static str, &'static str),
+    WebView2Compat,
+    Direct3d,
+    WebView2,
+}
+
+impl SetupStep {
+    fn id(self) -> String {
+        match self {
+            Self::Wineboot => "wineboot".to_string(),
+            Self::Winetricks(verb, _) => format!("winetricks:{verb}"),
+            Self::WebView2Compat => "webview2_compat".to_string(),
+            Self::Direct3d => "direct3d".to_string(),
+            Self::WebView2 => "webview2".to_string(),
+        }
+    }
+
+    fn description(self) -> String {
+        match self {
+            Self::Wineboot => "Creating Wine prefix...".to_string(),
+            Self::Winetricks(_, description) => format!("Installing {description}..."),
+            Self::WebView2Compat => "Configuring WebView2 compatibility...".to_string(),
+            Self::Direct3d => "Configuring the rendering pipeline...".to_string(),
+            Self::WebView2 => "Installing WebView2 (this may take a while)...".to_string(),
+        }
+    }
+}
+
+/// The `INIT_VERSION` that last changed the step with the given id
+fn step_version(id: &str) -> u32 {
+    STEP_VERSIONS
+        .iter()
+        .find(|(step, _)| *step == id)
+        .map_or(FIRST_MANIFEST_VERSION, |(_, version)| *version)
+}
+
+/// Steps that set up a prefix for `pipeline`, in order
+fn get_setup_steps(pipeline: RenderingPipeline) -> Vec<SetupStep> {
+    let mut steps = vec![SetupStep::Wineboot];
+    steps.extend(
+        get_winetricks_verbs(pipeline)
+            .into_iter()
+            .map(|(verb, description)| SetupStep::Winetricks(verb, description)),
+    );
+    steps.push(SetupStep::WebView2Compat);
+    if !get_direct3d_registry_values(pipeline).is_empty() {
+        steps.push(SetupStep::Direct3d);
+    }
+    steps.push(SetupStep::WebView2);
+    steps
+}
+
+/// Setup progress of a prefix, saved after every step
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PrefixManifest {
+    /// `INIT_VERSION` the completed steps were brought up to
+    init_version: u32,
+    pipeline: Option<RenderingPipeline>,
+    completed: BTreeSet<String>,
+    /// Step the last setup attempt stopped at
+    failed_step: Option<String>,
+}
+
+impl PrefixManifest {
+    /// Forget steps changed since the prefix was set up, so that only
+    /// those run again
+    fn migrate(&mut self) {
+        if self.init_version >= INIT_VERSION {
+            return;
+        }
+
+        let init_version = self.init_version;
+        let before = self.completed.len();
+        self.completed.retain(|id| step_version(id) <= init_version);
+        if self.completed.len() != before {
+            tracing::info!(
+                "Wine prefix setup v{} -> v{}: {} steps to re-run",
+                init_version,
+                INIT_VERSION,
+                before.saturating_sub(self.completed.len())
+            );
+        }
+        self.init_version = INIT_VERSION;
+    }
+
+    fn is_complete(&self, pipeline: RenderingPipeline) -> bool {
+        self.init_version >= INIT_VERSION
+            && get_setup_steps(pipeline)
+                .into_iter()
+                .all(|step| self.completed.contains(&step.id()))
+    }
+}
+
+/// Read a prefix's manifest. A prefix set up before manifests existed is
+/// taken to have completed every step of its marker's version, unless that
+/// predates `FIRST_MANIFEST_VERSION` and so can't say which steps still hold.
+fn load_prefix_manifest(prefix: &Path, pipeline: RenderingPipeline) -> PrefixManifest {
+    if let Ok(contents) = fs::read_to_string(prefix.join(PREFIX_MANIFEST_FILE)) {
+        match serde_json::from_str(&contents) {
+            Ok(manifest) => return manifest,
+            Err(e) => tracing::warn!("Failed to parse Wine prefix manifest: {}", e),
+        }
+    }
+
+    let Some(version) = fs::read_to_string(prefix.join(INIT_MARKER_FILE))
+        .ok()
+        .and_then(|contents| contents.trim().parse::<u32>().ok())
+    else {
+        return PrefixManifest::default();
+    };
+    if version < FIRST_MANIFEST_VERSION {
+        return PrefixManifest::default();
+    }
+    PrefixManifest {
+        init_version: version,
+        pipeline: Some(pipeline),
+        completed: get_setup_steps(pipeline)
+            .into_iter()
+            .map(SetupStep::id)
+            .filter(|id| step_version(id) <= version)
+            .collect(),
+        failed_step: None,
+    }
+}
+
+fn save_prefix_manifest(prefix: &Path, manifest: &PrefixManifest) -> Result<(), WineError> {
+    let contents = serde_json::to_string_pretty(manifest)
+        .map_err(|e| WineError::Other(format!("Failed to serialize prefix manifest: {e}")))?;
+    fs::write(prefix.join(PREFIX_MANIFEST_FILE), contents)?;
+    Ok(())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct WineStatus {
     pub installed: bool,
@@ -96,6 +354,8 @@ pub enum WineSetupStage {
 
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct WineSetupProgress {
+    /// Id of the prefix being set up
+    pub prefix_id: String,
     pub stage: WineSetupStage,
     pub progress: u8,
     pub message: String,
@@ -159,6 +419,10 @@ pub struct WinePaths {
     pub winetricks: PathBuf,
     /// Path to cabextract binary (needed by winetricks)
     pub cabextract: PathBuf,
+    /// Asset cache shared by every prefix
+    pub asset_cache: PathBuf,
+    /// Extra environment the runner needs, such as Proton's library paths
+    pub runner_env: Vec<(String, String)>,
 }
 
 /// Standard system paths that should always be available.
@@ -249,6 +513,7 @@ impl WinePaths {
             vars.push(("BROWSER".to_string(), browser));
         }
 
+        vars.extend(self.runner_env.iter().cloned());
         vars
     }
 
@@ -283,6 +548,16 @@ impl WinePaths {
             Self::build_path_with_system_dirs(&extra_dirs),
         ));
 
+        // Reuse downloads across prefixes and resets
+        vars.push((
+            "W_CACHE".to_string(),
+            self.asset_cache
+                .join(WINETRICKS_CACHE_DIR)
+                .to_string_lossy()
+                .to_string(),
+        ));
+
+        vars.extend(self.runner_env.iter().cloned());
         vars
     }
 }
@@ -445,10 +720,156 @@ fn get_cabextract(app: &AppHandle) -> Option<PathBuf> {
     None
 }
 
-/// Resolve Wine paths from bundled Wine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum WineRunnerKind {
+    Bundled,
+    System,
+    Proton,
+    Custom,
+}
+
+/// A Wine build games can run with
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct WineRunner {
+    pub id: String,
+    pub name: String,
+    pub kind: WineRunnerKind,
+    /// Directory holding the Wine binaries
+    pub path: String,
+    pub version: Option<String>,
+    pub meets_minimum_version: bool,
+    pub active: bool,
+    pub error: Option<String>,
+}
+
+/// A runner found on disk, before its version is checked
+#[derive(Debug, Clone)]
+struct RunnerCandidate {
+    id: String,
+    name: String,
+    kind: WineRunnerKind,
+    bin_dir: PathBuf,
+}
+
+fn bundled_runner(app: &AppHandle) -> Option<RunnerCandidate> {
+    get_bundled_wine_dir(app).map(|wine_dir| RunnerCandidate {
+        id: BUNDLED_RUNNER_ID.to_string(),
+        name: "Bundled Wine".to_string(),
+        kind: WineRunnerKind::Bundled,
+        bin_dir: wine_dir.join("bin"),
+    })
+}
+
+/// Wine found on PATH
+fn system_runner() -> Option<RunnerCandidate> {
+    let wine = which::which("wine").ok()?;
+    Some(RunnerCandidate {
+        id: "system".to_string(),
+        name: "System Wine".to_string(),
+        kind: WineRunnerKind::System,
+        bin_dir: wine.parent()?.to_path_buf(),
+    })
+}
+
+/// Proton and GE-Proton builds installed for Steam
+fn proton_runners(app: &AppHandle) -> Vec<RunnerCandidate> {
+    let Ok(home) = app.path().home_dir() else {
+        return Vec::new();
+    };
+
+    let mut runners: Vec<RunnerCandidate> = Vec::new();
+    for search_dir in PROTON_SEARCH_DIRS {
+        let Ok(entries) = fs::read_dir(home.join(search_dir)) else {
+            continue;
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let dir = entry.path();
+            // Newer builds ship Wine in files/, older ones in dist/
+            let Some(bin_dir) = ["files", "dist"]
+                .iter()
+                .map(|sub| dir.join(sub).join("bin"))
+                .find(|bin| bin.join("wine").exists())
+            else {
+                continue;
+            };
+            let name = entry.file_name().to_string_lossy().to_string();
+            let id = format!("proton:{name}");
+            // The search dirs overlap through Steam's symlinks
+            if runners.iter().any(|runner| runner.id == id) {
+                continue;
+            }
+            runners.push(RunnerCandidate {
+                id,
+                name,
+                kind: WineRunnerKind::Proton,
+                bin_dir,
+            });
+        }
+    }
+    runners.sort_by(|a, b| a.name.cmp(&b.name));
+    runners
+}
+
+/// Directory with the Wine binaries of a custom runner: its `bin`
+/// directory, or the directory itself
+fn custom_runner_bin_dir(dir: &Path) -> Option<PathBuf> {
+    [dir.join("bin"), dir.to_path_buf()]
+        .into_iter()
+        .find(|bin| bin.join("wine").exists() || bin.join("wine64").exists())
+}
+
+fn custom_runners(settings: &AppSettings) -> Vec<RunnerCandidate> {
+    settings
+        .custom_wine_runners
+        .iter()
+        .filter_map(|dir| {
+            let dir = PathBuf::from(dir);
+            Some(RunnerCandidate {
+                id: format!("custom:{}", dir.display()),
+                name: dir.file_name()?.to_string_lossy().to_string(),
+                kind: WineRunnerKind::Custom,
+                bin_dir: custom_runner_bin_dir(&dir)?,
+            })
+        })
+        .collect()
+}
+
+/// Every runner that could be found
+fn discover_runners(app: &AppHandle, settings: &AppSettings) -> Vec<RunnerCandidate> {
+    bundled_runner(app)
+        .into_iter()
+        .chain(system_runner())
+        .chain(proton_runners(app))
+        .chain(custom_runners(settings))
+        .collect()
+}
+
+/// Id of the runner games run with
+fn active_runner_id(settings: &AppSettings) -> &str {
+    settings.wine_runner.as_deref().unwrap_or(BUNDLED_RUNNER_ID)
+}
+
+fn find_runner(app: &AppHandle, settings: &AppSettings) -> Result<RunnerCandidate, WineError> {
+    let id = active_runner_id(settings);
+    if id == BUNDLED_RUNNER_ID {
+        return bundled_runner(app).ok_or(WineError::WineNotFound);
+    }
+    discover_runners(app, settings)
+        .into_iter()
+        .find(|runner| runner.id == id)
+        .ok_or_else(|| WineError::Other(format!("Wine runner {id} was not found")))
+}
+
+/// Resolve Wine paths from the selected runner
 pub fn resolve_wine_paths(app: &AppHandle) -> Result<WinePaths, WineError> {
-    let wine_dir = get_bundled_wine_dir(app).ok_or(WineError::WineNotFound)?;
-    let bin_dir = wine_dir.join("bin");
+    let settings = crate::settings::load_settings(app).unwrap_or_default();
+    let runner = find_runner(app, &settings)?;
+    runner_paths(app, &runner)
+}
+
+fn runner_paths(app: &AppHandle, runner: &RunnerCandidate) -> Result<WinePaths, WineError> {
+    let bin_dir = &runner.bin_dir;
 
     let wine64 = if bin_dir.join("wine64").exists() {
         bin_dir.join("wine64")
@@ -460,7 +881,16 @@ pub fn resolve_wine_paths(app: &AppHandle) -> Result<WinePaths, WineError> {
     } else {
         wine64.clone()
     };
-    let wineserver = bin_dir.join("wineserver");
+    let mut wineserver = bin_dir.join("wineserver");
+    if !wineserver.exists() && runner.kind == WineRunnerKind::System {
+        wineserver = which::which("wineserver").unwrap_or_else(|_| {
+            SYSTEM_WINESERVER_PATHS
+                .iter()
+                .map(PathBuf::from)
+                .find(|path| path.exists())
+                .unwrap_or_default()
+        });
+    }
 
     if !wine.exists() || !wineserver.exists() {
         return Err(WineError::WineNotFound);
@@ -469,16 +899,71 @@ pub fn resolve_wine_paths(app: &AppHandle) -> Result<WinePaths, WineError> {
     let winetricks = get_bundled_winetricks(app).ok_or(WineError::WinetricksNotFound)?;
     let cabextract = get_cabextract(app).ok_or(WineError::CabextractNotFound)?;
 
-    tracing::info!("Using bundled Wine from: {:?}", wine_dir);
+    // Proton expects its own libraries, which Steam's runtime normally sets up
+    let mut runner_env = Vec::new();
+    if runner.kind == WineRunnerKind::Proton {
+        if let Some(files) = bin_dir.parent() {
+            let lib_dirs = [files.join("lib64"), files.join("lib")];
+            let mut library_path: Vec<String> = lib_dirs
+                .iter()
+                .map(|dir| dir.to_string_lossy().to_string())
+                .collect();
+            if let Ok(existing) = std::env::var("LD_LIBRARY_PATH") {
+                library_path.push(existing);
+            }
+            runner_env.push(("LD_LIBRARY_PATH".to_string(), library_path.join(":")));
+            runner_env.push((
+                "WINEDLLPATH".to_string(),
+                lib_dirs
+                    .iter()
+                    .map(|dir| dir.join("wine").to_string_lossy().to_string())
+                    .collect::<Vec<_>>()
+                    .join(":"),
+            ));
+        }
+    }
+
+    tracing::info!("Using {:?} Wine from: {:?}", runner.kind, bin_dir);
     Ok(WinePaths {
         wine,
         wine64,
         wineserver,
         winetricks,
         cabextract,
+        asset_cache: get_asset_cache_dir(app)?,
+        runner_env,
     })
 }
 
+/// Check a runner's version, for listing
+fn describe_runner(app: &AppHandle, runner: RunnerCandidate, active_id: &str) -> WineRunner {
+    let checked =
+        runner_paths(app, &runner).and_then(|paths| check_wine_installed_with_paths(&paths));
+    let (version, meets_minimum_version, error) = match checked {
+        Ok((version, meets_min)) => (Some(version), meets_min, None),
+        Err(e) => (None, false, Some(e.to_string())),
+    };
+    WineRunner {
+        active: runner.id == active_id,
+        path: runner.bin_dir.to_string_lossy().to_string(),
+        id: runner.id,
+        name: runner.name,
+        kind: runner.kind,
+        version,
+        meets_minimum_version,
+        error,
+    }
+}
+
+fn list_runners(app: &AppHandle) -> Vec<WineRunner> {
+    let settings = crate::settings::load_settings(app).unwrap_or_default();
+    let active_id = active_runner_id(&settings).to_string();
+    discover_runners(app, &settings)
+        .into_iter()
+        .map(|runner| describe_runner(app, runner, &active_id))
+        .collect()
+}
+
 /// Check if Wine is installed and return its version
 pub fn check_wine_installed_with_paths(paths: &WinePaths) -> Result<(String, bool), WineError> {
     let mut cmd = Command::new(&paths.wine);
@@ -508,10 +993,11 @@ pub fn check_wine_installed_with_paths(paths: &WinePaths) -> Result<(String, boo
 
 /// Parse Wine version string and check if it meets minimum requirements
 fn parse_and_check_wine_version(version_str: &str) -> bool {
+    // e.g. "wine-10.5", "wine-10.0-rc1 (Staging)" or "wine-9.0 (Proton)"
     let version_part = version_str
         .strip_prefix("wine-")
         .unwrap_or(version_str)
-        .split('-')
+        .split(['-', ' '])
         .next()
         .unwrap_or("");
 
@@ -542,33 +1028,176 @@ pub fn check_winetricks_installed_with_paths(paths: &WinePaths) -> Result<PathBu
     }
 }
 
-/// Get the Wine prefix directory for this application
-pub fn get_wine_prefix(app: &AppHandle) -> Result<PathBuf, WineError> {
-    let app_data = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| WineError::Other(format!("Failed to get app data directory: {}", e)))?;
+/// A Wine prefix as shown in the settings screen
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct WinePrefixInfo {
+    #[serde(flatten)]
+    pub profile: WinePrefixProfile,
+    /// Whether the player created it, as opposed to a pipeline's own prefix
+    pub custom: bool,
+    pub path: String,
+    pub initialized: bool,
+    pub active: bool,
+    pub setting_up: bool,
+}
+
+/// Whether `id` is one the launcher creates: a pipeline's own prefix, or
+/// `profile-` followed by a slug from `create_wine_prefix`
+fn is_valid_prefix_id(id: &str) -> bool {
+    if RenderingPipeline::ALL
+        .iter()
+        .any(|pipeline| pipeline.prefix_id() == id)
+    {
+        return true;
+    }
+    id.strip_prefix("profile-").is_some_and(|slug| {
+        !slug.is_empty()
+            && slug
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    })
+}
 
-    Ok(app_data.join("wine_prefix"))
+/// Every prefix: one per pipeline, then the player's own
+fn prefix_profiles(settings: &AppSettings) -> Vec<WinePrefixProfile> {
+    RenderingPipeline::ALL
+        .iter()
+        .map(|pipeline| WinePrefixProfile {
+            id: pipeline.prefix_id().to_string(),
+            name: pipeline.display_name().to_string(),
+            pipeline: *pipeline,
+        })
+        .chain(settings.wine_prefixes.iter().cloned())
+        .collect()
 }
 
-/// Check if the Wine prefix has been initialized
-fn check_prefix_initialized(prefix: &Path) -> bool {
-    let marker_path = prefix.join(INIT_MARKER_FILE);
-    if !marker_path.exists() {
-        return false;
+/// The prefix games run in
+fn active_profile(settings: &AppSettings) -> WinePrefixProfile {
+    let pipeline_profile = || WinePrefixProfile {
+        id: settings.rendering_pipeline.prefix_id().to_string(),
+        name: settings.rendering_pipeline.display_name().to_string(),
+        pipeline: settings.rendering_pipeline,
+    };
+    match &settings.active_wine_prefix {
+        Some(id) => prefix_profiles(settings)
+            .into_iter()
+            .find(|profile| profile.id == *id)
+            .unwrap_or_else(pipeline_profile),
+        None => pipeline_profile(),
     }
+}
 
-    if let Ok(contents) = fs::read_to_string(&marker_path) {
-        if let Ok(version) = contents.trim().parse::<u32>() {
-            return version >= INIT_VERSION;
-        }
+fn get_app_data_dir(app: &AppHandle) -> Result<PathBuf, WineError> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| WineError::Other(format!("Failed to get app data directory: {}", e)))
+}
+
+/// Get the directory of the prefix with the given id, for the selected
+/// runner. Runners upgrade prefixes to their own Wine version, so each
+/// gets separate ones.
+fn get_prefix_path(app: &AppHandle, id: &str) -> Result<PathBuf, WineError> {
+    // The path gets renamed and deleted, so it must stay inside the
+    // prefixes directory
+    let mut components = Path::new(id).components();
+    if !matches!(
+        (components.next(), components.next()),
+        (Some(std::path::Component::Normal(_)), None)
+    ) {
+        return Err(WineError::Other(format!("Invalid Wine prefix id: {id:?}")));
+    }
+
+    let prefixes = get_app_data_dir(app)?.join(PREFIXES_DIR);
+    let settings = crate::settings::load_settings(app).unwrap_or_default();
+    let runner = active_runner_id(&settings);
+    if runner == BUNDLED_RUNNER_ID {
+        return Ok(prefixes.join(id));
+    }
+
+    Ok(prefixes
+        .join(RUNNER_PREFIXES_DIR)
+        .join(runner_dir_name(runner))
+        .join(id))
+}
+
+/// Directory name for a runner's prefixes: a readable form of its id, which
+/// may be lossy, plus a hash of the full id so no two runners share one
+fn runner_dir_name(runner: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let readable: String = runner
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let digest = Sha256::digest(runner.as_bytes());
+    format!(
+        "{readable}-{}",
+        hex::encode(digest.get(..6).unwrap_or_default())
+    )
+}
+
+/// Move the single prefix from older versions to the active pipeline's
+/// prefix, so it isn't set up again
+fn migrate_legacy_prefix(app: &AppHandle, active_id: &str) -> Result<(), WineError> {
+    let app_data = get_app_data_dir(app)?;
+    let legacy = app_data.join(LEGACY_PREFIX_DIR);
+    let prefixes = app_data.join(PREFIXES_DIR);
+    if !legacy.exists() || prefixes.exists() {
+        return Ok(());
     }
 
-    false
+    fs::create_dir_all(&prefixes)?;
+    let target = prefixes.join(active_id);
+    tracing::info!("Moving Wine prefix {:?} to {:?}", legacy, target);
+    fs::rename(&legacy, &target)?;
+    Ok(())
+}
+
+/// Get the Wine prefix directory games run in
+pub fn get_wine_prefix(app: &AppHandle) -> Result<PathBuf, WineError> {
+    let settings = crate::settings::load_settings(app).unwrap_or_default();
+    let profile = active_profile(&settings);
+    migrate_legacy_prefix(app, &profile.id)?;
+    get_prefix_path(app, &profile.id)
+}
+
+fn list_prefixes(app: &AppHandle) -> Result<Vec<WinePrefixInfo>, WineError> {
+    let settings = crate::settings::load_settings(app).unwrap_or_default();
+    let active_id = active_profile(&settings).id;
+    migrate_legacy_prefix(app, &active_id)?;
+
+    prefix_profiles(&settings)
+        .into_iter()
+        .map(|profile| {
+            let path = get_prefix_path(app, &profile.id)?;
+            Ok(WinePrefixInfo {
+                custom: settings.wine_prefixes.contains(&profile),
+                path: path.to_string_lossy().to_string(),
+                initialized: check_prefix_initialized(&path, profile.pipeline)
+                    && check_webview2_installed(&path),
+                active: profile.id == active_id,
+                setting_up: is_prefix_in_setup(&profile.id),
+                profile,
+            })
+        })
+        .collect()
+}
+
+/// Check if every setup step for `pipeline` has been completed in the prefix
+fn check_prefix_initialized(prefix: &Path, pipeline: RenderingPipeline) -> bool {
+    let mut manifest = load_prefix_manifest(prefix, pipeline);
+    manifest.migrate();
+    manifest.is_complete(pipeline)
 }
 
-/// Check if WebView2 is installed in the prefix
+/// Check if `WebView2` is installed in the prefix
 fn check_webview2_installed(prefix: &Path) -> bool {
     let webview2_path = prefix
         .join("drive_c")
@@ -607,7 +1236,9 @@ pub async fn check_prefix_status(app: &AppHandle) -> WineStatus {
     status.winetricks_installed = check_winetricks_installed_with_paths(&paths).is_ok();
 
     if let Ok(prefix) = get_wine_prefix(app) {
-        status.prefix_initialized = check_prefix_initialized(&prefix);
+        let settings = crate::settings::load_settings(app).unwrap_or_default();
+        let pipeline = active_profile(&settings).pipeline;
+        status.prefix_initialized = check_prefix_initialized(&prefix, pipeline);
         status.webview2_installed = check_webview2_installed(&prefix);
     }
 
@@ -615,8 +1246,15 @@ pub async fn check_prefix_status(app: &AppHandle) -> WineStatus {
 }
 
 /// Emit a progress event
-fn emit_progress(app: &AppHandle, stage: WineSetupStage, progress: u8, message: &str) {
+fn emit_progress(
+    app: &AppHandle,
+    prefix_id: &str,
+    stage: WineSetupStage,
+    progress: u8,
+    message: &str,
+) {
     let progress_event = WineSetupProgress {
+        prefix_id: prefix_id.to_string(),
         stage,
         progress,
         message: message.to_string(),
@@ -626,7 +1264,7 @@ fn emit_progress(app: &AppHandle, stage: WineSetupStage, progress: u8, message:
         tracing::warn!("Failed to emit progress event: {}", e);
     }
 
-    tracing::info!("[{}%] {}", progress, message);
+    tracing::info!("[{}] [{}%] {}", prefix_id, progress, message);
 }
 
 /// Run a Wine command with the specified prefix
@@ -655,11 +1293,17 @@ fn run_winetricks_with_paths(
     paths: &WinePaths,
     prefix: &Path,
     verb: &str,
+    force: bool,
 ) -> Result<(), WineError> {
     tracing::info!("Running winetricks {}", verb);
 
     let mut cmd = Command::new(&paths.winetricks);
-    cmd.args(["-q", verb]);
+    cmd.arg("-q");
+    // Winetricks skips verbs it has already installed unless forced
+    if force {
+        cmd.arg("--force");
+    }
+    cmd.arg(verb);
     cmd.env("WINEPREFIX", prefix);
 
     for (key, value) in paths.get_winetricks_env_vars() {
@@ -778,20 +1422,43 @@ fn kill_wine_process_with_paths(
     Ok(())
 }
 
-/// Initialize the Wine prefix with all required dependencies
+/// Initialize the Wine prefix games run in with all required dependencies
 pub async fn initialize_prefix(
     app: &AppHandle,
     pipeline: RenderingPipeline,
 ) -> Result<(), WineError> {
-    let prefix = get_wine_prefix(app)?;
+    let settings = crate::settings::load_settings(app).unwrap_or_default();
+    let id = active_profile(&settings).id;
+    migrate_legacy_prefix(app, &id)?;
+    initialize_prefix_with_id(app, &id, pipeline).await
+}
+
+/// Initialize the prefix with the given id for `pipeline`, skipping the
+/// steps its manifest records as done
+async fn initialize_prefix_with_id(
+    app: &AppHandle,
+    id: &str,
+    pipeline: RenderingPipeline,
+) -> Result<(), WineError> {
+    let Some(_guard) = SetupGuard::acquire(id) else {
+        return Err(WineError::Other(format!(
+            "Wine prefix {id} is already being set up"
+        )));
+    };
+    let prefix = get_prefix_path(app, id)?;
 
     emit_progress(
         app,
+        id,
         WineSetupStage::InProgress,
         0,
         "Checking Wine installation...",
     );
 
+    if let Err(e) = import_bundled_asset_pack(app) {
+        tracing::warn!("Failed to import bundled Wine asset pack: {}", e);
+    }
+
     let paths = resolve_wine_paths(app)?;
 
     let (version, meets_min) = check_wine_installed_with_paths(&paths)?;
@@ -803,17 +1470,101 @@ pub async fn initialize_prefix(
 
     fs::create_dir_all(&prefix)?;
 
+    let mut manifest = load_prefix_manifest(&prefix, pipeline);
+    manifest.migrate();
+    manifest.pipeline = Some(pipeline);
+
+    let steps = get_setup_steps(pipeline);
+    let step_count = steps.len();
+    if let Some(failed) = manifest.failed_step.take() {
+        tracing::info!("Resuming Wine prefix {} setup after {} failed", id, failed);
+    }
+
+    for (i, step) in steps.into_iter().enumerate() {
+        let step_id = step.id();
+        if manifest.completed.contains(&step_id) {
+            tracing::debug!("Skipping completed setup step {}", step_id);
+            continue;
+        }
+
+        let progress = 5 + (i * 90 / step_count) as u8;
+        emit_progress(
+            app,
+            id,
+            WineSetupStage::InProgress,
+            progress,
+            &step.description(),
+        );
+
+        if let Err(e) = run_setup_step(&paths, &prefix, pipeline, step).await {
+            manifest.failed_step = Some(step_id);
+            save_prefix_manifest(&prefix, &manifest)?;
+            return Err(e);
+        }
+
+        manifest.completed.insert(step_id);
+        save_prefix_manifest(&prefix, &manifest)?;
+    }
+
+    save_prefix_manifest(&prefix, &manifest)?;
+
+    // Older launchers only look at the marker file
+    let marker_path = prefix.join(INIT_MARKER_FILE);
+    fs::write(&marker_path, INIT_VERSION.to_string())?;
+
     emit_progress(
         app,
-        WineSetupStage::InProgress,
-        5,
-        "Creating Wine prefix...",
+        id,
+        WineSetupStage::Complete,
+        100,
+        "Wine environment setup complete!",
     );
 
+    tracing::info!("Wine prefix {} initialization complete", id);
+    Ok(())
+}
+
+/// Run one setup step in the prefix
+async fn run_setup_step(
+    paths: &WinePaths,
+    prefix: &Path,
+    pipeline: RenderingPipeline,
+    step: SetupStep,
+) -> Result<(), WineError> {
+    match step {
+        SetupStep::Wineboot => run_wineboot(paths, prefix).await,
+        SetupStep::Winetricks(verb, _) => run_winetricks_with_paths(paths, prefix, verb, false),
+        SetupStep::WebView2Compat => set_registry_key_with_paths(
+            paths,
+            prefix,
+            "HKEY_CURRENT_USER\\Software\\Wine\\AppDefaults\\msedgewebview2.exe",
+            "version",
+            "win7",
+            "REG_SZ",
+        ),
+        SetupStep::Direct3d => {
+            for (name, value, reg_type) in get_direct3d_registry_values(pipeline) {
+                set_registry_key_with_paths(
+                    paths,
+                    prefix,
+                    "HKEY_CURRENT_USER\\Software\\Wine\\Direct3D",
+                    name,
+                    value,
+                    reg_type,
+                )?;
+            }
+            Ok(())
+        }
+        SetupStep::WebView2 => install_webview2(paths, prefix).await,
+    }
+}
+
+/// Create the prefix with wineboot
+async fn run_wineboot(paths: &WinePaths, prefix: &Path) -> Result<(), WineError> {
     let output = {
         let mut cmd = Command::new(&paths.wine);
         cmd.args(["wineboot", "--init"]);
-        cmd.env("WINEPREFIX", &prefix);
+        cmd.env("WINEPREFIX", prefix);
         cmd.env("WINEDLLOVERRIDES", "mscoree=d;mshtml=d");
         for (key, value) in paths.get_env_vars() {
             cmd.env(key, value);
@@ -839,58 +1590,59 @@ pub async fn initialize_prefix(
     }
 
     tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+    Ok(())
+}
 
-    let verbs = get_winetricks_verbs(pipeline);
-    let verb_count = verbs.len();
-    for (i, (verb, description)) in verbs.iter().enumerate() {
-        let progress = 10 + ((i as u8 * 40) / verb_count as u8);
-        emit_progress(
-            app,
-            WineSetupStage::InProgress,
-            progress,
-            &format!("Installing {}...", description),
-        );
-        run_winetricks_with_paths(&paths, &prefix, verb)?;
+/// Download and run the `WebView2` installer, waiting until it registers
+async fn install_webview2(paths: &WinePaths, prefix: &Path) -> Result<(), WineError> {
+    let webview2_installer = paths.asset_cache.join(WEBVIEW2_CACHE_FILE);
+    if is_valid_installer(&webview2_installer) {
+        tracing::info!("Using cached WebView2 installer {:?}", webview2_installer);
+    } else {
+        if webview2_installer.exists() {
+            tracing::warn!(
+                "Cached WebView2 installer {:?} is not a valid installer, downloading again",
+                webview2_installer
+            );
+            fs::remove_file(&webview2_installer)?;
+        }
+        download_webview2(&webview2_installer).await?;
     }
 
-    emit_progress(
-        app,
-        WineSetupStage::InProgress,
-        55,
-        "Configuring WebView2 compatibility...",
-    );
-
-    set_registry_key_with_paths(
-        &paths,
-        &prefix,
-        "HKEY_CURRENT_USER\\Software\\Wine\\AppDefaults\\msedgewebview2.exe",
-        "version",
-        "win7",
-        "REG_SZ",
-    )?;
-
-    emit_progress(
-        app,
-        WineSetupStage::InProgress,
-        60,
-        "Downloading WebView2 installer...",
-    );
+    let result = run_webview2_installer(paths, prefix, &webview2_installer).await;
+    if result.is_err() {
+        // Don't let a bad installer fail every later setup too
+        tracing::warn!("Removing cached WebView2 installer after a failed install");
+        let _ = fs::remove_file(&webview2_installer);
+    }
+    result
+}
 
-    let webview2_installer = prefix.join("webview2_installer.exe");
-    download_webview2(&webview2_installer).await?;
+/// Whether `path` looks like a Windows executable of a plausible size for
+/// the `WebView2` installer, rather than a truncated download or an error page
+fn is_valid_installer(path: &Path) -> bool {
+    use std::io::Read;
 
-    emit_progress(
-        app,
-        WineSetupStage::InProgress,
-        80,
-        "Installing WebView2 (this may take a while)...",
-    );
+    let Ok(mut file) = fs::File::open(path) else {
+        return false;
+    };
+    let size_ok = file
+        .metadata()
+        .is_ok_and(|meta| WEBVIEW2_INSTALLER_SIZE.contains(&meta.len()));
+    let mut magic = [0u8; 2];
+    size_ok && file.read_exact(&mut magic).is_ok() && magic == *b"MZ"
+}
 
+async fn run_webview2_installer(
+    paths: &WinePaths,
+    prefix: &Path,
+    webview2_installer: &Path,
+) -> Result<(), WineError> {
     let installer_path = webview2_installer.to_string_lossy().to_string();
 
     let mut cmd = Command::new(&paths.wine);
     cmd.args([installer_path.as_str(), "/silent", "/install"]);
-    cmd.env("WINEPREFIX", &prefix);
+    cmd.env("WINEPREFIX", prefix);
     for (key, value) in paths.get_env_vars() {
         cmd.env(key, value);
     }
@@ -927,7 +1679,7 @@ pub async fn initialize_prefix(
     let start = std::time::Instant::now();
 
     loop {
-        if check_registry_key_exists(&paths, &prefix, webview2_reg_key, "pv") {
+        if check_registry_key_exists(paths, prefix, webview2_reg_key, "pv") {
             tracing::info!("WebView2 installation detected via registry");
             break;
         }
@@ -953,26 +1705,19 @@ pub async fn initialize_prefix(
         "MicrosoftEdgeWebView2Setup.exe",
         "setup.exe",
     ] {
-        let _ = kill_wine_process_with_paths(&paths, &prefix, process);
+        let _ = kill_wine_process_with_paths(paths, prefix, process);
     }
 
-    let _ = fs::remove_file(&webview2_installer);
-
-    let marker_path = prefix.join(INIT_MARKER_FILE);
-    fs::write(&marker_path, INIT_VERSION.to_string())?;
-
-    emit_progress(
-        app,
-        WineSetupStage::Complete,
-        100,
-        "Wine environment setup complete!",
-    );
-
-    tracing::info!("Wine prefix initialization complete");
-    Ok(())
+    if check_webview2_installed(prefix) {
+        Ok(())
+    } else {
+        Err(WineError::WebView2InstallFailed(
+            "WebView2 was not found in the prefix after installing".to_string(),
+        ))
+    }
 }
 
-/// Download the WebView2 installer
+/// Download the `WebView2` installer into the asset cache
 async fn download_webview2(dest: &Path) -> Result<(), WineError> {
     tracing::info!("Downloading WebView2 from {}", WEBVIEW2_DOWNLOAD_URL);
 
@@ -992,12 +1737,710 @@ async fn download_webview2(dest: &Path) -> Result<(), WineError> {
         .await
         .map_err(|e| WineError::WebView2DownloadFailed(e.to_string()))?;
 
-    fs::write(dest, &bytes).map_err(|e| WineError::WebView2DownloadFailed(e.to_string()))?;
-
+    // Written under another name first so an interrupted download isn't
+    // mistaken for a cached installer
+    let partial = dest.with_extension("part");
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&partial, &bytes).map_err(|e| WineError::WebView2DownloadFailed(e.to_string()))?;
+    if !is_valid_installer(&partial) {
+        let _ = fs::remove_file(&partial);
+        return Err(WineError::WebView2DownloadFailed(format!(
+            "the download ({} bytes) is not a Windows installer",
+            bytes.len()
+        )));
+    }
+    fs::rename(&partial, dest)?;
+
     tracing::info!("WebView2 installer downloaded to {:?}", dest);
     Ok(())
 }
 
+/// What the asset cache holds
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct WineAssetCache {
+    pub path: String,
+    pub webview2_cached: bool,
+    /// Winetricks verbs with cached downloads
+    pub winetricks_verbs: Vec<String>,
+    pub size_mb: u32,
+}
+
+fn get_asset_cache_dir(app: &AppHandle) -> Result<PathBuf, WineError> {
+    Ok(get_app_data_dir(app)?.join(ASSET_CACHE_DIR))
+}
+
+fn dir_size(path: &Path) -> u64 {
+    fs::read_dir(path).map_or(0, |entries| {
+        entries
+            .filter_map(Result::ok)
+            .map(|entry| match entry.metadata() {
+                Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+                Ok(meta) => meta.len(),
+                Err(_) => 0,
+            })
+            .sum()
+    })
+}
+
+fn describe_asset_cache(cache: &Path) -> WineAssetCache {
+    let mut winetricks_verbs: Vec<String> = fs::read_dir(cache.join(WINETRICKS_CACHE_DIR))
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    winetricks_verbs.sort();
+
+    WineAssetCache {
+        path: cache.to_string_lossy().to_string(),
+        webview2_cached: is_valid_installer(&cache.join(WEBVIEW2_CACHE_FILE)),
+        winetricks_verbs,
+        size_mb: u32::try_from(dir_size(cache) / (1024 * 1024)).unwrap_or(u32::MAX),
+    }
+}
+
+/// Unpack an asset pack into the asset cache. Packs are zips or tarballs,
+/// optionally gzip or zstd compressed, laid out like the cache itself:
+/// `webview2/` and `winetricks/<verb>/`.
+fn import_asset_pack(pack: &Path, cache: &Path) -> Result<(), WineError> {
+    let name = pack
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    tracing::info!("Importing Wine asset pack {:?} into {:?}", pack, cache);
+    fs::create_dir_all(cache)?;
+
+    let file = fs::File::open(pack)?;
+    // `name` is already lowercase
+    let has_suffix = |suffixes: &[&str]| suffixes.iter().any(|suffix| name.ends_with(suffix));
+    let unpack_tar = |reader: Box<dyn std::io::Read>| {
+        tar::Archive::new(reader)
+            .unpack(cache)
+            .map_err(|e| WineError::Other(format!("Failed to extract asset pack: {e}")))
+    };
+
+    if has_suffix(&[".zip"]) {
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| WineError::Other(format!("Asset pack is not a valid zip: {e}")))?;
+        for i in 0..archive.len() {
+            let mut entry = archive
+                .by_index(i)
+                .map_err(|e| WineError::Other(format!("Corrupt entry in asset pack: {e}")))?;
+            let Some(outpath) = entry.enclosed_name().map(|path| cache.join(path)) else {
+                continue;
+            };
+            if entry.is_dir() {
+                fs::create_dir_all(&outpath)?;
+                continue;
+            }
+            if let Some(parent) = outpath.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut outfile = fs::File::create(&outpath)?;
+            std::io::copy(&mut entry, &mut outfile)?;
+        }
+        Ok(())
+    } else if has_suffix(&[".tar.zst", ".tzst"]) {
+        let decoder = zstd::stream::Decoder::new(file)
+            .map_err(|e| WineError::Other(format!("Failed to create zstd decoder: {e}")))?;
+        unpack_tar(Box::new(decoder))
+    } else if has_suffix(&[".tar.gz", ".tgz"]) {
+        unpack_tar(Box::new(flate2::read::GzDecoder::new(file)))
+    } else if has_suffix(&[".tar"]) {
+        unpack_tar(Box::new(file))
+    } else {
+        Err(WineError::Other(format!(
+            "Unsupported asset pack format: {name} (expected .zip, .tar, .tar.gz or .tar.zst)"
+        )))
+    }
+}
+
+/// Import the asset pack shipped in resources, once
+fn import_bundled_asset_pack(app: &AppHandle) -> Result<(), WineError> {
+    let Ok(resource_dir) = app.path().resource_dir() else {
+        return Ok(());
+    };
+    let pack = resource_dir.join(ASSET_PACK_RESOURCE);
+    let cache = get_asset_cache_dir(app)?;
+    let marker = cache.join(ASSET_PACK_IMPORTED_MARKER);
+    if !pack.exists() || marker.exists() {
+        return Ok(());
+    }
+
+    import_asset_pack(&pack, &cache)?;
+    fs::write(marker, "")?;
+    Ok(())
+}
+
+/// Write the asset cache to a zstd tarball that can be imported elsewhere
+fn export_asset_pack(cache: &Path, dest: &Path) -> Result<(), WineError> {
+    let file = fs::File::create(dest)?;
+    let encoder = zstd::stream::Encoder::new(file, 3)
+        .map_err(|e| WineError::Other(format!("Failed to create zstd encoder: {e}")))?;
+    let mut builder = tar::Builder::new(encoder);
+    for dir in ["webview2", WINETRICKS_CACHE_DIR] {
+        let path = cache.join(dir);
+        if path.exists() {
+            builder.append_dir_all(dir, &path)?;
+        }
+    }
+    builder.into_inner()?.finish()?;
+    tracing::info!("Exported Wine asset pack to {:?}", dest);
+    Ok(())
+}
+
+/// Describes a prefix snapshot, stored first in the archive
+const SNAPSHOT_INFO_FILE: &str = "snapshot.json";
+/// Directory in a snapshot archive holding the prefix itself
+const SNAPSHOT_PREFIX_DIR: &str = "prefix";
+/// Bumped when the snapshot layout changes incompatibly
+const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// Where a prefix snapshot was taken from
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct WinePrefixSnapshot {
+    pub format_version: u32,
+    pub prefix: WinePrefixProfile,
+    /// Id of the Wine runner the prefix was used with
+    pub runner: String,
+    pub wine_version: Option<String>,
+    /// RFC 3339 time the snapshot was taken
+    pub created_at: String,
+    /// Manifest `INIT_VERSION` and setup steps the prefix had completed
+    pub init_version: u32,
+    pub completed_steps: Vec<String>,
+}
+
+/// Whether the game or another program is running in a prefix. Wine's own
+/// background processes and stuck installers don't count.
+fn prefix_in_use(prefix: &Path) -> bool {
+    let system = sysinfo::System::new_all();
+    prefix_processes(&system, prefix).iter().any(|process| {
+        let name = process_exe_name(process);
+        !WINE_SYSTEM_PROCESS_NAMES.contains(&name.as_str())
+            && !STUCK_PROCESS_NAMES.contains(&name.as_str())
+    })
+}
+
+/// Write a prefix to a zstd tarball: `snapshot.json`, then the prefix under
+/// `prefix/`. Symlinks such as `dosdevices` are stored as links.
+fn export_prefix_snapshot(
+    prefix: &Path,
+    info: &WinePrefixSnapshot,
+    dest: &Path,
+) -> Result<(), WineError> {
+    let partial = dest.with_extension("part");
+    let file = fs::File::create(&partial)?;
+    let encoder = zstd::stream::Encoder::new(file, 3)
+        .map_err(|e| WineError::Other(format!("Failed to create zstd encoder: {e}")))?;
+    let mut builder = tar::Builder::new(encoder);
+    builder.follow_symlinks(false);
+
+    let json = serde_json::to_vec_pretty(info)
+        .map_err(|e| WineError::Other(format!("Failed to serialize snapshot info: {e}")))?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(u64::try_from(chrono::Utc::now().timestamp()).unwrap_or_default());
+    header.set_cksum();
+    builder.append_data(&mut header, SNAPSHOT_INFO_FILE, json.as_slice())?;
+    builder.append_dir_all(SNAPSHOT_PREFIX_DIR, prefix)?;
+    builder.into_inner()?.finish()?;
+
+    fs::rename(&partial, dest)?;
+    tracing::info!("Exported Wine prefix {} to {:?}", info.prefix.id, dest);
+    Ok(())
+}
+
+/// Read a snapshot's info without unpacking the prefix
+fn read_snapshot_info(snapshot: &Path) -> Result<WinePrefixSnapshot, WineError> {
+    let invalid =
+        |reason: String| WineError::Other(format!("Not a Wine prefix snapshot: {reason}"));
+    let decoder = zstd::stream::Decoder::new(fs::File::open(snapshot)?)
+        .map_err(|e| invalid(e.to_string()))?;
+    let mut archive = tar::Archive::new(decoder);
+    let entry = archive
+        .entries()?
+        .next()
+        .ok_or_else(|| invalid("archive is empty".to_string()))??;
+    if entry.path()?.as_ref() != Path::new(SNAPSHOT_INFO_FILE) {
+        return Err(invalid(format!("{SNAPSHOT_INFO_FILE} is missing")));
+    }
+
+    let info: WinePrefixSnapshot =
+        serde_json::from_reader(entry).map_err(|e| invalid(e.to_string()))?;
+    if !is_valid_prefix_id(&info.prefix.id) {
+        return Err(invalid(format!("invalid prefix id {:?}", info.prefix.id)));
+    }
+    if info.format_version > SNAPSHOT_FORMAT_VERSION {
+        return Err(WineError::Other(format!(
+            "Wine prefix snapshot format {} is newer than this launcher supports",
+            info.format_version
+        )));
+    }
+    Ok(info)
+}
+
+/// Replace `prefix` with the one in a snapshot. The old prefix is only
+/// removed once the new one is in place.
+fn restore_prefix_snapshot(snapshot: &Path, prefix: &Path) -> Result<(), WineError> {
+    let name = prefix
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let staging = prefix.with_file_name(format!("{name}.restore"));
+    let backup = prefix.with_file_name(format!("{name}.old"));
+    for dir in [&staging, &backup] {
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+    }
+    tracing::info!("Restoring Wine prefix {:?} from {:?}", prefix, snapshot);
+
+    let result = (|| {
+        fs::create_dir_all(&staging)?;
+        let decoder = zstd::stream::Decoder::new(fs::File::open(snapshot)?)
+            .map_err(|e| WineError::Other(format!("Failed to create zstd decoder: {e}")))?;
+        tar::Archive::new(decoder)
+            .unpack(&staging)
+            .map_err(|e| WineError::Other(format!("Failed to extract prefix snapshot: {e}")))?;
+
+        let restored = staging.join(SNAPSHOT_PREFIX_DIR);
+        if !restored.join("drive_c").is_dir() {
+            return Err(WineError::Other(
+                "Prefix snapshot does not contain a Wine prefix".to_string(),
+            ));
+        }
+
+        let had_prefix = prefix.exists();
+        if had_prefix {
+            fs::rename(prefix, &backup)?;
+        }
+        if let Err(e) = fs::rename(&restored, prefix) {
+            if had_prefix {
+                let _ = fs::rename(&backup, prefix);
+            }
+            return Err(e.into());
+        }
+        Ok(())
+    })();
+
+    let _ = fs::remove_dir_all(&staging);
+    if result.is_ok() && backup.exists() {
+        fs::remove_dir_all(&backup)?;
+    }
+    result
+}
+
+/// DLLs DXVK replaces, which must be overridden to native
+const DXVK_DLLS: &[&str] = &["d3d9", "d3d10core", "d3d11", "dxgi"];
+
+/// Installers that are sometimes left running after setup
+const STUCK_PROCESS_NAMES: &[&str] = &[
+    "microsoftedgeupdate.exe",
+    "microsoftedgewebview2setup.exe",
+    "setup.exe",
+    "dxsetup.exe",
+    "wineboot.exe",
+];
+
+/// Processes Wine keeps running in a prefix by itself
+const WINE_SYSTEM_PROCESS_NAMES: &[&str] = &[
+    "wineserver",
+    "services.exe",
+    "explorer.exe",
+    "winedevice.exe",
+    "plugplay.exe",
+    "rpcss.exe",
+    "svchost.exe",
+    "conhost.exe",
+];
+
+/// Shared libraries that provide the Vulkan loader
+const VULKAN_LOADER_PATHS: &[&str] = &[
+    "/usr/lib/x86_64-linux-gnu/libvulkan.so.1",
+    "/usr/lib64/libvulkan.so.1",
+    "/usr/lib/libvulkan.so.1",
+];
+
+/// Directories holding Vulkan driver manifests
+const VULKAN_ICD_DIRS: &[&str] = &[
+    "/usr/share/vulkan/icd.d",
+    "/etc/vulkan/icd.d",
+    "/usr/local/share/vulkan/icd.d",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum PrefixCheckId {
+    Setup,
+    DxvkOverrides,
+    WebView2Runtime,
+    Wineserver,
+    StuckProcesses,
+    Vulkan,
+}
+
+/// Result of one prefix health check
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct PrefixCheck {
+    pub id: PrefixCheckId,
+    pub passed: bool,
+    pub detail: String,
+    /// Whether `repair_wine_prefix` can fix it
+    pub repairable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct PrefixDiagnosis {
+    pub prefix_id: String,
+    pub pipeline: RenderingPipeline,
+    pub healthy: bool,
+    pub checks: Vec<PrefixCheck>,
+}
+
+fn check_result(id: PrefixCheckId, failure: Option<String>, ok: &str) -> PrefixCheck {
+    PrefixCheck {
+        id,
+        passed: failure.is_none(),
+        repairable: failure.is_some() && id != PrefixCheckId::Vulkan,
+        detail: failure.unwrap_or_else(|| ok.to_string()),
+    }
+}
+
+/// Read a registry value from the prefix
+fn query_registry_value(
+    paths: &WinePaths,
+    prefix: &Path,
+    path: &str,
+    value_name: &str,
+) -> Option<String> {
+    let mut cmd = Command::new(&paths.wine);
+    cmd.args(["reg", "query", path, "/v", value_name]);
+    cmd.env("WINEPREFIX", prefix);
+
+    for (key, value) in paths.get_env_vars() {
+        cmd.env(key, value);
+    }
+
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::null());
+
+    let output = cmd.output().ok().filter(|o| o.status.success())?;
+    // Lines look like "    d3d11    REG_SZ    native"
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| {
+            line.split_whitespace()
+                .next()
+                .is_some_and(|name| name.eq_ignore_ascii_case(value_name))
+        })
+        .and_then(|line| line.split_whitespace().nth(2).map(str::to_string))
+}
+
+/// Processes running in `prefix`, found by their `WINEPREFIX`
+fn prefix_processes<'a>(system: &'a sysinfo::System, prefix: &Path) -> Vec<&'a sysinfo::Process> {
+    let wineprefix = format!("WINEPREFIX={}", prefix.display());
+    system
+        .processes()
+        .values()
+        .filter(|process| process.environ().iter().any(|var| *var == *wineprefix))
+        .collect()
+}
+
+/// File name of a process's executable, lowercase. Wine processes show their
+/// Windows path as the first argument.
+fn process_exe_name(process: &sysinfo::Process) -> String {
+    process
+        .cmd()
+        .first()
+        .map(|arg| arg.to_string_lossy().to_string())
+        .and_then(|arg| arg.rsplit(['\\', '/']).next().map(str::to_lowercase))
+        .unwrap_or_else(|| process.name().to_string_lossy().to_lowercase())
+}
+
+fn stuck_processes<'a>(system: &'a sysinfo::System, prefix: &Path) -> Vec<&'a sysinfo::Process> {
+    prefix_processes(system, prefix)
+        .into_iter()
+        .filter(|process| STUCK_PROCESS_NAMES.contains(&process_exe_name(process).as_str()))
+        .collect()
+}
+
+fn check_setup(prefix: &Path, pipeline: RenderingPipeline) -> Option<String> {
+    let mut manifest = load_prefix_manifest(prefix, pipeline);
+    manifest.migrate();
+    let missing: Vec<String> = get_setup_steps(pipeline)
+        .into_iter()
+        .map(SetupStep::id)
+        .filter(|id| !manifest.completed.contains(id))
+        .collect();
+    if missing.is_empty() {
+        None
+    } else {
+        Some(format!("Setup steps not completed: {}", missing.join(", ")))
+    }
+}
+
+fn check_dxvk_overrides(
+    paths: &WinePaths,
+    prefix: &Path,
+    pipeline: RenderingPipeline,
+) -> Option<String> {
+    if pipeline != RenderingPipeline::Dxvk {
+        return None;
+    }
+
+    let system32 = prefix.join("drive_c").join("windows").join("system32");
+    let broken: Vec<&str> = DXVK_DLLS
+        .iter()
+        .copied()
+        .filter(|dll| {
+            let mode = query_registry_value(
+                paths,
+                prefix,
+                "HKEY_CURRENT_USER\\Software\\Wine\\DllOverrides",
+                dll,
+            );
+            !mode.is_some_and(|mode| mode.starts_with("native"))
+                || !system32.join(format!("{dll}.dll")).exists()
+        })
+        .collect();
+    if broken.is_empty() {
+        None
+    } else {
+        Some(format!("DXVK is not overriding: {}", broken.join(", ")))
+    }
+}
+
+fn check_webview2_runtime(prefix: &Path) -> Option<String> {
+    let application = prefix
+        .join("drive_c")
+        .join("Program Files (x86)")
+        .join("Microsoft")
+        .join("EdgeWebView")
+        .join("Application");
+    let has_runtime = fs::read_dir(&application).is_ok_and(|entries| {
+        entries
+            .filter_map(Result::ok)
+            .any(|entry| entry.path().join("msedgewebview2.exe").exists())
+    });
+    if has_runtime {
+        None
+    } else {
+        Some("msedgewebview2.exe is missing from the WebView2 runtime".to_string())
+    }
+}
+
+fn check_wineserver(system: &sysinfo::System, prefix: &Path) -> Option<String> {
+    let servers: Vec<&sysinfo::Process> = prefix_processes(system, prefix)
+        .into_iter()
+        .filter(|process| process.name() == "wineserver")
+        .collect();
+    if servers.len() > 1 {
+        return Some(format!("{} wineservers are running", servers.len()));
+    }
+    servers
+        .iter()
+        .find(|process| {
+            matches!(
+                process.status(),
+                sysinfo::ProcessStatus::Zombie
+                    | sysinfo::ProcessStatus::Stop
+                    | sysinfo::ProcessStatus::Dead
+            )
+        })
+        .map(|process| format!("wineserver is {}", process.status()))
+}
+
+fn check_stuck_processes(system: &sysinfo::System, prefix: &Path) -> Option<String> {
+    let stuck: Vec<String> = stuck_processes(system, prefix)
+        .into_iter()
+        .map(process_exe_name)
+        .collect();
+    if stuck.is_empty() {
+        None
+    } else {
+        Some(format!("Left running: {}", stuck.join(", ")))
+    }
+}
+
+fn check_vulkan(pipeline: RenderingPipeline) -> Option<String> {
+    if !matches!(
+        pipeline,
+        RenderingPipeline::Dxvk | RenderingPipeline::Wined3dVulkan
+    ) {
+        return None;
+    }
+
+    if !VULKAN_LOADER_PATHS
+        .iter()
+        .any(|path| Path::new(path).exists())
+    {
+        return Some(
+            "The Vulkan loader (libvulkan.so.1) is not installed. Install it or switch to an OpenGL pipeline."
+                .to_string(),
+        );
+    }
+    let has_driver = VULKAN_ICD_DIRS.iter().any(|dir| {
+        fs::read_dir(dir).is_ok_and(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .any(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        })
+    });
+    if has_driver {
+        None
+    } else {
+        Some(
+            "No Vulkan driver is installed. Install one for your GPU or switch to an OpenGL pipeline."
+                .to_string(),
+        )
+    }
+}
+
+fn diagnose_prefix(app: &AppHandle, id: Option<String>) -> Result<PrefixDiagnosis, WineError> {
+    let settings = crate::settings::load_settings(app).unwrap_or_default();
+    let profile = match id {
+        Some(id) => find_profile(&settings, &id)?,
+        None => active_profile(&settings),
+    };
+    migrate_legacy_prefix(app, &active_profile(&settings).id)?;
+    let prefix = get_prefix_path(app, &profile.id)?;
+    let paths = resolve_wine_paths(app)?;
+    let system = sysinfo::System::new_all();
+    let pipeline = profile.pipeline;
+
+    let checks = vec![
+        check_result(
+            PrefixCheckId::Setup,
+            check_setup(&prefix, pipeline),
+            "Every setup step has completed",
+        ),
+        check_result(
+            PrefixCheckId::DxvkOverrides,
+            check_dxvk_overrides(&paths, &prefix, pipeline),
+            "DXVK DLL overrides are in place, or not needed",
+        ),
+        check_result(
+            PrefixCheckId::WebView2Runtime,
+            check_webview2_runtime(&prefix),
+            "WebView2 runtime files are present",
+        ),
+        check_result(
+            PrefixCheckId::Wineserver,
+            check_wineserver(&system, &prefix),
+            "wineserver is stopped or running normally",
+        ),
+        check_result(
+            PrefixCheckId::StuckProcesses,
+            check_stuck_processes(&system, &prefix),
+            "No installers left running",
+        ),
+        check_result(
+            PrefixCheckId::Vulkan,
+            check_vulkan(pipeline),
+            "Vulkan is available, or not needed",
+        ),
+    ];
+
+    Ok(PrefixDiagnosis {
+        healthy: checks.iter().all(|check| check.passed),
+        prefix_id: profile.id,
+        pipeline,
+        checks,
+    })
+}
+
+/// Stop the prefix's wineserver, along with everything running in it
+fn kill_wineserver(paths: &WinePaths, prefix: &Path) -> Result<(), WineError> {
+    let output = Command::new(&paths.wineserver)
+        .arg("-k")
+        .env("WINEPREFIX", prefix)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()?;
+    if !output.status.success() {
+        tracing::debug!(
+            "wineserver -k exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(())
+}
+
+/// Fix what one failed check found. The caller makes sure nothing is
+/// running in the prefix, since every repair changes it underneath.
+async fn repair_prefix(
+    app: &AppHandle,
+    profile: &WinePrefixProfile,
+    check: PrefixCheckId,
+) -> Result<(), WineError> {
+    let prefix = get_prefix_path(app, &profile.id)?;
+    let paths = resolve_wine_paths(app)?;
+    tracing::info!("Repairing Wine prefix {}: {:?}", profile.id, check);
+
+    // Steps re-run through setup are forgotten first, then setup resumes
+    let rerun_step = match check {
+        PrefixCheckId::Setup => None,
+        PrefixCheckId::WebView2Runtime => Some(SetupStep::WebView2.id()),
+        // Installing DXVK into any other pipeline's prefix would break it
+        PrefixCheckId::DxvkOverrides if profile.pipeline != RenderingPipeline::Dxvk => {
+            return Err(WineError::Other(format!(
+                "{} prefixes don't use DXVK",
+                profile.pipeline.display_name()
+            )))
+        }
+        PrefixCheckId::Vulkan => {
+            return Err(WineError::Other(
+                "Vulkan has to be installed outside the launcher".to_string(),
+            ))
+        }
+        PrefixCheckId::DxvkOverrides
+        | PrefixCheckId::Wineserver
+        | PrefixCheckId::StuckProcesses => {
+            let Some(guard) = SetupGuard::acquire(&profile.id) else {
+                return Err(WineError::Other(format!(
+                    "Wine prefix {} is already being set up",
+                    profile.id
+                )));
+            };
+            return tokio::task::spawn_blocking(move || {
+                let _guard = guard;
+                match check {
+                    PrefixCheckId::DxvkOverrides => {
+                        run_winetricks_with_paths(&paths, &prefix, "dxvk", true)
+                    }
+                    PrefixCheckId::Wineserver => kill_wineserver(&paths, &prefix),
+                    _ => {
+                        let system = sysinfo::System::new_all();
+                        for process in stuck_processes(&system, &prefix) {
+                            tracing::info!("Killing stuck process {}", process_exe_name(process));
+                            process.kill();
+                        }
+                        Ok(())
+                    }
+                }
+            })
+            .await
+            .map_err(|e| WineError::Other(e.to_string()))?;
+        }
+    };
+
+    if let Some(step) = rerun_step {
+        let mut manifest = load_prefix_manifest(&prefix, profile.pipeline);
+        manifest.completed.remove(&step);
+        save_prefix_manifest(&prefix, &manifest)?;
+    }
+    initialize_prefix_with_id(app, &profile.id, profile.pipeline).await
+}
+
 /// Reset the Wine prefix by deleting and recreating it
 pub async fn reset_prefix(app: &AppHandle, pipeline: RenderingPipeline) -> Result<(), WineError> {
     let prefix = get_wine_prefix(app)?;
@@ -1011,12 +2454,72 @@ pub async fn reset_prefix(app: &AppHandle, pipeline: RenderingPipeline) -> Resul
     initialize_prefix(app, pipeline).await
 }
 
+/// Find a prefix by id
+fn find_profile(settings: &AppSettings, id: &str) -> Result<WinePrefixProfile, WineError> {
+    prefix_profiles(settings)
+        .into_iter()
+        .find(|profile| profile.id == id)
+        .ok_or_else(|| WineError::Other(format!("Unknown Wine prefix: {id}")))
+}
+
+/// Set up a prefix in the background. Progress and failure are reported
+/// through `wine-setup-progress` events.
+fn spawn_prefix_setup(app: &AppHandle, profile: WinePrefixProfile) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = initialize_prefix_with_id(&app, &profile.id, profile.pipeline).await {
+            tracing::error!("Failed to set up Wine prefix {}: {}", profile.id, e);
+            emit_progress(&app, &profile.id, WineSetupStage::Error, 0, &e.to_string());
+        }
+        if let Ok(prefixes) = list_prefixes(&app) {
+            let _ = app.emit("wine-prefixes-updated", &prefixes);
+        }
+    });
+}
+
+/// CPUs the kernel has online, `None` if that can't be read.
+fn online_cpus() -> Option<Vec<usize>> {
+    fs::read_to_string("/sys/devices/system/cpu/online")
+        .ok()
+        .and_then(|list| crate::settings::parse_cpu_list(list.trim()))
+}
+
+/// `cpus` without the ones that are offline, which `sched_setaffinity`
+/// would refuse. `None` if none are left, so the game runs unpinned.
+fn online_affinity(cpus: Vec<usize>) -> Option<Vec<usize>> {
+    let Some(online) = online_cpus() else {
+        return Some(cpus);
+    };
+    let (usable, offline): (Vec<usize>, Vec<usize>) =
+        cpus.into_iter().partition(|cpu| online.contains(cpu));
+    if !offline.is_empty() {
+        tracing::warn!(
+            "Ignoring offline CPUs {:?} in the launch profile's affinity",
+            offline
+        );
+    }
+    if usable.is_empty() {
+        tracing::warn!("None of the launch profile's CPUs are online, not pinning the game");
+        return None;
+    }
+    Some(usable)
+}
+
+/// The launch profile the player assigned to `server_name`, or the default one.
+pub fn launch_profile_for(app: &AppHandle, server_name: &str) -> Option<LaunchProfile> {
+    crate::settings::load_settings(app)
+        .ok()?
+        .launch_profile_for(server_name)
+        .cloned()
+}
+
 /// Launch an executable using Wine.
 pub fn launch_with_wine(
     app: &AppHandle,
     exe_path: &Path,
     args: &[&str],
     env_vars: &[(&str, &str)],
+    profile: Option<&LaunchProfile>,
 ) -> Result<std::process::Child, WineError> {
     use std::io::{BufRead, BufReader};
     use std::os::unix::process::CommandExt;
@@ -1024,7 +2527,17 @@ pub fn launch_with_wine(
     let prefix = get_wine_prefix(app)?;
     let paths = resolve_wine_paths(app)?;
 
-    let mut cmd = Command::new(&paths.wine);
+    // Wrappers go in front of wine, e.g. `gamemoderun mangohud wine game.exe`
+    let wrappers = profile.map(LaunchProfile::wrapper_args).unwrap_or_default();
+    let mut cmd = match wrappers.split_first() {
+        Some((program, wrapper_args)) => {
+            let mut cmd = Command::new(program);
+            cmd.args(wrapper_args);
+            cmd.arg(&paths.wine);
+            cmd
+        }
+        None => Command::new(&paths.wine),
+    };
     cmd.arg(exe_path);
     cmd.args(args);
     cmd.env("WINEPREFIX", &prefix);
@@ -1033,6 +2546,16 @@ pub fn launch_with_wine(
         cmd.env(key, value);
     }
 
+    if let Some(profile) = profile {
+        // Profiles saved before these were reserved may still have them
+        cmd.envs(
+            profile
+                .env
+                .iter()
+                .filter(|(key, _)| !crate::settings::RESERVED_ENV_KEYS.contains(&key.as_str())),
+        );
+    }
+
     for (key, value) in env_vars {
         cmd.env(key, value);
     }
@@ -1040,10 +2563,27 @@ pub fn launch_with_wine(
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
-    // SAFETY: prctl is a simple syscall that only affects this process's children
+    let cpus = profile
+        .and_then(LaunchProfile::cpus)
+        .and_then(online_affinity);
+
+    // SAFETY: cpu_set_t is a plain bitmask that parse_cpu_list keeps indices
+    // within; prctl and sched_setaffinity only affect the child being spawned
     unsafe {
-        cmd.pre_exec(|| {
+        let cpu_set = cpus.map(|cpus| {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            for cpu in cpus {
+                libc::CPU_SET(cpu, &mut set);
+            }
+            set
+        });
+        cmd.pre_exec(move || {
             libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+            if let Some(set) = &cpu_set {
+                if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), set) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
             Ok(())
         });
     }
@@ -1053,18 +2593,30 @@ pub fn launch_with_wine(
         .map(|n| n.to_string_lossy().to_string())
         .unwrap_or_else(|| "unknown".to_string());
 
-    tracing::info!("Launching via Wine: {:?} {:?}", exe_path, args);
+    tracing::info!(
+        "Launching via Wine: {:?} {:?} (profile: {:?})",
+        exe_path,
+        args,
+        profile.map(|p| &p.name)
+    );
 
     let mut child = cmd
         .spawn()
         .map_err(|e| WineError::LaunchFailed(e.to_string()))?;
 
+    let pid = child.id();
+    let diagnostics = app
+        .try_state::<Arc<crate::diagnostics::DiagnosticsState>>()
+        .map(|d| Arc::clone(&d));
+
     if let Some(stdout) = child.stdout.take() {
+        let app_handle = app.clone();
         let name = exe_name.clone();
+        let diagnostics = diagnostics.clone();
         std::thread::spawn(move || {
             let reader = BufReader::new(stdout);
             for line in reader.lines().map_while(Result::ok) {
-                tracing::debug!(target: "wine", "[{}] {}", name, line);
+                handle_output_line(&app_handle, diagnostics.as_deref(), pid, &name, &line);
             }
         });
     }
@@ -1072,15 +2624,9 @@ pub fn launch_with_wine(
     if let Some(stderr) = child.stderr.take() {
         let app_handle = app.clone();
         std::thread::spawn(move || {
-            let mut lines = Vec::new();
             let reader = BufReader::new(stderr);
             for line in reader.lines().map_while(Result::ok) {
-                tracing::warn!(target: "wine", "[{}] {}", exe_name, line);
-                lines.push(line);
-            }
-            if !lines.is_empty() {
-                let output = lines.join("\n");
-                let _ = app_handle.emit("wine-error", &output);
+                handle_output_line(&app_handle, diagnostics.as_deref(), pid, &exe_name, &line);
             }
         });
     }
@@ -1088,6 +2634,34 @@ pub fn launch_with_wine(
     Ok(child)
 }
 
+/// Record a line of Wine output. Most of it is noise and only logged at
+/// debug level; lines showing a known failure are logged as warnings and
+/// sent to the frontend, a few at a time.
+fn handle_output_line(
+    app: &AppHandle,
+    diagnostics: Option<&crate::diagnostics::DiagnosticsState>,
+    pid: u32,
+    exe_name: &str,
+    line: &str,
+) {
+    let diagnostic = diagnostics
+        .and_then(|diagnostics| diagnostics.record_classified_output(pid, exe_name, line));
+    let Some(diagnostic) = diagnostic else {
+        tracing::debug!(target: "wine", "[{}] {}", exe_name, line);
+        return;
+    };
+
+    tracing::warn!(
+        target: "wine",
+        "[{}] {:?} ({} times): {}",
+        exe_name,
+        diagnostic.kind,
+        diagnostic.occurrences,
+        line
+    );
+    let _ = app.emit("game-output-diagnostic", &diagnostic);
+}
+
 // Tauri commands
 
 #[tauri::command]
@@ -1118,6 +2692,548 @@ pub async fn reset_wine_prefix(app: AppHandle) -> crate::error::CommandResult<()
         .map_err(|e| crate::error::CommandError::Io(e.to_string()))
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn list_wine_prefixes(
+    app: AppHandle,
+) -> crate::error::CommandResult<Vec<WinePrefixInfo>> {
+    list_prefixes(&app).map_err(|e| crate::error::CommandError::Io(e.to_string()))
+}
+
+/// Add a named prefix for `pipeline` and start setting it up in the
+/// background.
+#[tauri::command]
+#[specta::specta]
+pub async fn create_wine_prefix(
+    app: AppHandle,
+    name: String,
+    pipeline: RenderingPipeline,
+) -> crate::error::CommandResult<Vec<WinePrefixInfo>> {
+    let name = name.trim().to_string();
+    let slug: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    if slug.trim_matches('-').is_empty() {
+        return Err(crate::error::CommandError::InvalidInput(
+            "Wine prefix name must contain letters or digits".to_string(),
+        ));
+    }
+
+    let mut settings = crate::settings::load_settings(&app)?;
+    let profile = WinePrefixProfile {
+        id: format!("profile-{}", slug.trim_matches('-')),
+        name,
+        pipeline,
+    };
+    if prefix_profiles(&settings)
+        .iter()
+        .any(|p| p.id == profile.id)
+    {
+        return Err(crate::error::CommandError::InvalidInput(format!(
+            "A Wine prefix named {} already exists",
+            profile.name
+        )));
+    }
+    settings.wine_prefixes.push(profile.clone());
+    crate::settings::save_settings(&app, &settings)?;
+    tracing::info!("Created Wine prefix {} ({:?})", profile.id, pipeline);
+
+    spawn_prefix_setup(&app, profile);
+    list_prefixes(&app).map_err(|e| crate::error::CommandError::Io(e.to_string()))
+}
+
+/// Set up a prefix other than the active one in the background, so it can
+/// be switched to later without waiting.
+#[tauri::command]
+#[specta::specta]
+pub async fn prepare_wine_prefix(
+    app: AppHandle,
+    id: String,
+) -> crate::error::CommandResult<Vec<WinePrefixInfo>> {
+    let settings = crate::settings::load_settings(&app)?;
+    let profile = find_profile(&settings, &id)
+        .map_err(|e| crate::error::CommandError::NotFound(e.to_string()))?;
+    if is_prefix_in_setup(&id) {
+        return Err(crate::error::CommandError::Busy {
+            operation: format!("setting up Wine prefix {id}"),
+        });
+    }
+
+    spawn_prefix_setup(&app, profile);
+    list_prefixes(&app).map_err(|e| crate::error::CommandError::Io(e.to_string()))
+}
+
+/// Run games in another prefix. The prefix must already be set up.
+#[tauri::command]
+#[specta::specta]
+pub async fn switch_wine_prefix(
+    app: AppHandle,
+    id: String,
+) -> crate::error::CommandResult<Vec<WinePrefixInfo>> {
+    let mut settings = crate::settings::load_settings(&app)?;
+    let profile = find_profile(&settings, &id)
+        .map_err(|e| crate::error::CommandError::NotFound(e.to_string()))?;
+
+    let path =
+        get_prefix_path(&app, &id).map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+    if is_prefix_in_setup(&id)
+        || !check_prefix_initialized(&path, profile.pipeline)
+        || !check_webview2_installed(&path)
+    {
+        return Err(crate::error::CommandError::NotConfigured {
+            feature: "wine_prefix".into(),
+        });
+    }
+
+    settings.active_wine_prefix = Some(profile.id.clone());
+    settings.rendering_pipeline = profile.pipeline;
+    crate::settings::save_settings(&app, &settings)?;
+    tracing::info!("Switched to Wine prefix {}", profile.id);
+
+    let prefixes =
+        list_prefixes(&app).map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+    let _ = app.emit("wine-prefixes-updated", &prefixes);
+    Ok(prefixes)
+}
+
+/// Delete a prefix's files. A prefix the player created is forgotten too;
+/// a pipeline's own prefix is only emptied.
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_wine_prefix(
+    app: AppHandle,
+    id: String,
+) -> crate::error::CommandResult<Vec<WinePrefixInfo>> {
+    let mut settings = crate::settings::load_settings(&app)?;
+    find_profile(&settings, &id)
+        .map_err(|e| crate::error::CommandError::NotFound(e.to_string()))?;
+    if active_profile(&settings).id == id {
+        return Err(crate::error::CommandError::InvalidInput(
+            "The active Wine prefix can't be deleted".to_string(),
+        ));
+    }
+    if is_prefix_in_setup(&id) {
+        return Err(crate::error::CommandError::Busy {
+            operation: format!("setting up Wine prefix {id}"),
+        });
+    }
+
+    let path =
+        get_prefix_path(&app, &id).map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+    if path.exists() {
+        fs::remove_dir_all(&path)?;
+    }
+    settings.wine_prefixes.retain(|profile| profile.id != id);
+    crate::settings::save_settings(&app, &settings)?;
+    tracing::info!("Deleted Wine prefix {}", id);
+
+    list_prefixes(&app).map_err(|e| crate::error::CommandError::Io(e.to_string()))
+}
+
+/// Run every health check on a prefix, the active one if `id` is unset.
+#[tauri::command]
+#[specta::specta]
+pub async fn diagnose_wine_prefix(
+    app: AppHandle,
+    id: Option<String>,
+) -> crate::error::CommandResult<PrefixDiagnosis> {
+    diagnose_prefix_blocking(app, id).await
+}
+
+/// `diagnose_prefix` off the async runtime, as it scans every process.
+async fn diagnose_prefix_blocking(
+    app: AppHandle,
+    id: Option<String>,
+) -> crate::error::CommandResult<PrefixDiagnosis> {
+    tokio::task::spawn_blocking(move || diagnose_prefix(&app, id))
+        .await
+        .map_err(|e| crate::error::CommandError::Internal(e.to_string()))?
+        .map_err(|e| crate::error::CommandError::Io(e.to_string()))
+}
+
+/// Fix what a failed health check found, then check the prefix again.
+#[tauri::command]
+#[specta::specta]
+pub async fn repair_wine_prefix(
+    app: AppHandle,
+    id: Option<String>,
+    check: PrefixCheckId,
+) -> crate::error::CommandResult<PrefixDiagnosis> {
+    let settings = crate::settings::load_settings(&app)?;
+    let profile = match &id {
+        Some(id) => find_profile(&settings, id)
+            .map_err(|e| crate::error::CommandError::NotFound(e.to_string()))?,
+        None => active_profile(&settings),
+    };
+
+    // Only repair what is actually broken, and only in ways that suit the
+    // prefix's pipeline
+    if is_prefix_in_setup(&profile.id) {
+        return Err(crate::error::CommandError::Busy {
+            operation: format!("setting up Wine prefix {}", profile.id),
+        });
+    }
+    let diagnosis = diagnose_prefix_blocking(app.clone(), Some(profile.id.clone())).await?;
+    match diagnosis.checks.iter().find(|c| c.id == check) {
+        Some(found) if found.passed => {
+            return Err(crate::error::CommandError::InvalidInput(format!(
+                "{check:?} check is not failing for Wine prefix {}",
+                profile.id
+            )));
+        }
+        Some(found) if found.repairable => {}
+        _ => {
+            return Err(crate::error::CommandError::InvalidInput(format!(
+                "{check:?} can't be repaired by the launcher"
+            )));
+        }
+    }
+
+    let prefix = get_prefix_path(&app, &profile.id)
+        .map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+    let in_use = tokio::task::spawn_blocking(move || prefix_in_use(&prefix))
+        .await
+        .map_err(|e| crate::error::CommandError::Internal(e.to_string()))?;
+    if in_use {
+        return Err(crate::error::CommandError::Busy {
+            operation: format!("using Wine prefix {}", profile.id),
+        });
+    }
+
+    repair_prefix(&app, &profile, check)
+        .await
+        .map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+    diagnose_prefix_blocking(app, Some(profile.id)).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_wine_asset_cache(app: AppHandle) -> crate::error::CommandResult<WineAssetCache> {
+    let cache =
+        get_asset_cache_dir(&app).map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+    Ok(describe_asset_cache(&cache))
+}
+
+/// Fill the asset cache from a pack prepared on another machine, so prefixes
+/// can be set up without downloading.
+#[tauri::command]
+#[specta::specta]
+pub async fn import_wine_asset_pack(
+    app: AppHandle,
+    path: String,
+) -> crate::error::CommandResult<WineAssetCache> {
+    let pack = PathBuf::from(path);
+    if !pack.is_file() {
+        return Err(crate::error::CommandError::NotFound(format!(
+            "asset pack {}",
+            pack.display()
+        )));
+    }
+    let cache =
+        get_asset_cache_dir(&app).map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+
+    let import_cache = cache.clone();
+    tokio::task::spawn_blocking(move || import_asset_pack(&pack, &import_cache))
+        .await
+        .map_err(|e| crate::error::CommandError::Internal(e.to_string()))?
+        .map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+
+    Ok(describe_asset_cache(&cache))
+}
+
+/// Save the asset cache as a pack for `import_wine_asset_pack`.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_wine_asset_pack(
+    app: AppHandle,
+    path: String,
+) -> crate::error::CommandResult<WineAssetCache> {
+    let cache =
+        get_asset_cache_dir(&app).map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+
+    let export_cache = cache.clone();
+    tokio::task::spawn_blocking(move || export_asset_pack(&export_cache, Path::new(&path)))
+        .await
+        .map_err(|e| crate::error::CommandError::Internal(e.to_string()))?
+        .map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+
+    Ok(describe_asset_cache(&cache))
+}
+
+/// Save a prefix, the active one if `id` is unset, as a snapshot that
+/// `import_wine_prefix` can restore here or on another machine.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_wine_prefix(
+    app: AppHandle,
+    id: Option<String>,
+    path: String,
+) -> crate::error::CommandResult<WinePrefixSnapshot> {
+    let settings = crate::settings::load_settings(&app)?;
+    let profile = match &id {
+        Some(id) => find_profile(&settings, id)
+            .map_err(|e| crate::error::CommandError::NotFound(e.to_string()))?,
+        None => active_profile(&settings),
+    };
+    migrate_legacy_prefix(&app, &active_profile(&settings).id)
+        .map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+    let prefix = get_prefix_path(&app, &profile.id)
+        .map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+    if !prefix.is_dir() {
+        return Err(crate::error::CommandError::NotFound(format!(
+            "Wine prefix {}",
+            profile.id
+        )));
+    }
+    if is_prefix_in_setup(&profile.id) || prefix_in_use(&prefix) {
+        return Err(crate::error::CommandError::Busy {
+            operation: format!("using Wine prefix {}", profile.id),
+        });
+    }
+
+    let paths =
+        resolve_wine_paths(&app).map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+    // Stopping wineserver writes the registry out to the prefix
+    kill_wineserver(&paths, &prefix).map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+
+    let manifest = load_prefix_manifest(&prefix, profile.pipeline);
+    let info = WinePrefixSnapshot {
+        format_version: SNAPSHOT_FORMAT_VERSION,
+        runner: active_runner_id(&settings).to_string(),
+        wine_version: check_wine_installed_with_paths(&paths)
+            .ok()
+            .map(|(version, _)| version),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        init_version: manifest.init_version,
+        completed_steps: manifest.completed.into_iter().collect(),
+        prefix: profile,
+    };
+
+    let export_info = info.clone();
+    tokio::task::spawn_blocking(move || {
+        export_prefix_snapshot(&prefix, &export_info, Path::new(&path))
+    })
+    .await
+    .map_err(|e| crate::error::CommandError::Internal(e.to_string()))?
+    .map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+
+    Ok(info)
+}
+
+/// Replace a prefix with a snapshot from `export_wine_prefix`. Without an
+/// `id` the snapshot's own prefix is restored, and added if it was created
+/// on another machine. Snapshots taken under another runner are refused
+/// unless `allow_runner_mismatch` is set.
+#[tauri::command]
+#[specta::specta]
+pub async fn import_wine_prefix(
+    app: AppHandle,
+    path: String,
+    id: Option<String>,
+    allow_runner_mismatch: bool,
+) -> crate::error::CommandResult<Vec<WinePrefixInfo>> {
+    let snapshot = PathBuf::from(path);
+    if !snapshot.is_file() {
+        return Err(crate::error::CommandError::NotFound(format!(
+            "prefix snapshot {}",
+            snapshot.display()
+        )));
+    }
+    let info = read_snapshot_info(&snapshot)
+        .map_err(|e| crate::error::CommandError::InvalidInput(e.to_string()))?;
+
+    let mut settings = crate::settings::load_settings(&app)?;
+    let runner = active_runner_id(&settings);
+    if info.runner != runner {
+        if !allow_runner_mismatch {
+            return Err(crate::error::CommandError::InvalidInput(format!(
+                "Snapshot was taken with the {} runner, but {} is selected",
+                info.runner, runner
+            )));
+        }
+        tracing::warn!(
+            "Restoring Wine prefix snapshot taken with runner {} under {}",
+            info.runner,
+            runner
+        );
+    }
+    let target_id = id.unwrap_or_else(|| info.prefix.id.clone());
+    let (profile, is_new) = match find_profile(&settings, &target_id) {
+        Ok(profile) => (profile, false),
+        Err(_) if target_id == info.prefix.id => (info.prefix.clone(), true),
+        Err(e) => return Err(crate::error::CommandError::NotFound(e.to_string())),
+    };
+    if profile.pipeline != info.prefix.pipeline {
+        return Err(crate::error::CommandError::InvalidInput(format!(
+            "Snapshot is of a {} prefix, but {} uses {}",
+            info.prefix.pipeline.display_name(),
+            profile.name,
+            profile.pipeline.display_name()
+        )));
+    }
+
+    let Some(_guard) = SetupGuard::acquire(&profile.id) else {
+        return Err(crate::error::CommandError::Busy {
+            operation: format!("setting up Wine prefix {}", profile.id),
+        });
+    };
+    let prefix = get_prefix_path(&app, &profile.id)
+        .map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+    let paths =
+        resolve_wine_paths(&app).map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+    if prefix.exists() {
+        if prefix_in_use(&prefix) {
+            return Err(crate::error::CommandError::Busy {
+                operation: format!("using Wine prefix {}", profile.id),
+            });
+        }
+        kill_wineserver(&paths, &prefix)
+            .map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+    }
+
+    let wine_version = check_wine_installed_with_paths(&paths)
+        .ok()
+        .map(|(version, _)| version);
+    if info.wine_version.is_some() && info.wine_version != wine_version {
+        // Wine updates the prefix itself on its next start
+        tracing::warn!(
+            "Wine prefix snapshot was taken with {:?}, restoring for {:?}",
+            info.wine_version,
+            wine_version
+        );
+    }
+
+    let restore_prefix = prefix.clone();
+    tokio::task::spawn_blocking(move || restore_prefix_snapshot(&snapshot, &restore_prefix))
+        .await
+        .map_err(|e| crate::error::CommandError::Internal(e.to_string()))?
+        .map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+
+    if is_new {
+        settings.wine_prefixes.push(profile.clone());
+        crate::settings::save_settings(&app, &settings)?;
+    }
+    tracing::info!(
+        "Restored Wine prefix {} from snapshot taken {}",
+        profile.id,
+        info.created_at
+    );
+
+    let prefixes =
+        list_prefixes(&app).map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+    let _ = app.emit("wine-prefixes-updated", &prefixes);
+    Ok(prefixes)
+}
+
+/// Delete everything in the asset cache, so installers and winetricks
+/// downloads are fetched again on the next setup.
+#[tauri::command]
+#[specta::specta]
+pub async fn clear_wine_asset_cache(app: AppHandle) -> crate::error::CommandResult<WineAssetCache> {
+    let cache =
+        get_asset_cache_dir(&app).map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+    if is_any_prefix_in_setup() {
+        return Err(crate::error::CommandError::Busy {
+            operation: "setting up a Wine prefix".to_string(),
+        });
+    }
+    if cache.exists() {
+        fs::remove_dir_all(&cache)?;
+    }
+    tracing::info!("Cleared Wine asset cache {:?}", cache);
+    Ok(describe_asset_cache(&cache))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_wine_runners(app: AppHandle) -> crate::error::CommandResult<Vec<WineRunner>> {
+    Ok(list_runners(&app))
+}
+
+/// Run games with another Wine build, the bundled one if `id` is unset.
+/// Prefixes are kept per runner, so the active prefix is set up in the
+/// background if this runner doesn't have it yet.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_wine_runner(
+    app: AppHandle,
+    id: Option<String>,
+) -> crate::error::CommandResult<Vec<WineRunner>> {
+    let mut settings = crate::settings::load_settings(&app)?;
+    let id = id.filter(|id| id != BUNDLED_RUNNER_ID);
+
+    if let Some(id) = &id {
+        let runner = discover_runners(&app, &settings)
+            .into_iter()
+            .find(|runner| runner.id == *id)
+            .ok_or_else(|| crate::error::CommandError::NotFound(format!("Wine runner {id}")))?;
+        let paths = runner_paths(&app, &runner)
+            .map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+        let (version, meets_min) = check_wine_installed_with_paths(&paths)
+            .map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+        if !meets_min {
+            return Err(crate::error::CommandError::InvalidInput(format!(
+                "{version} is older than the required Wine {}.{}",
+                MIN_WINE_VERSION.0, MIN_WINE_VERSION.1
+            )));
+        }
+    }
+
+    settings.wine_runner = id;
+    crate::settings::save_settings(&app, &settings)?;
+    tracing::info!("Wine runner set to {}", active_runner_id(&settings));
+
+    let profile = active_profile(&settings);
+    let prefix = get_prefix_path(&app, &profile.id)
+        .map_err(|e| crate::error::CommandError::Io(e.to_string()))?;
+    if !is_prefix_in_setup(&profile.id) && !check_prefix_initialized(&prefix, profile.pipeline) {
+        tracing::info!("Setting up Wine prefix {} for the new runner", profile.id);
+        spawn_prefix_setup(&app, profile);
+    }
+    Ok(list_runners(&app))
+}
+
+/// Add a directory holding a Wine build.
+#[tauri::command]
+#[specta::specta]
+pub async fn add_custom_wine_runner(
+    app: AppHandle,
+    path: String,
+) -> crate::error::CommandResult<Vec<WineRunner>> {
+    let dir = PathBuf::from(path.trim());
+    if custom_runner_bin_dir(&dir).is_none() {
+        return Err(crate::error::CommandError::InvalidInput(format!(
+            "No Wine binaries found in {}",
+            dir.display()
+        )));
+    }
+
+    let mut settings = crate::settings::load_settings(&app)?;
+    let dir = dir.to_string_lossy().to_string();
+    if !settings.custom_wine_runners.contains(&dir) {
+        settings.custom_wine_runners.push(dir);
+        crate::settings::save_settings(&app, &settings)?;
+    }
+    Ok(list_runners(&app))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_custom_wine_runner(
+    app: AppHandle,
+    path: String,
+) -> crate::error::CommandResult<Vec<WineRunner>> {
+    let mut settings = crate::settings::load_settings(&app)?;
+    settings.custom_wine_runners.retain(|dir| *dir != path);
+    if settings.wine_runner.as_deref() == Some(format!("custom:{path}").as_str()) {
+        settings.wine_runner = None;
+    }
+    crate::settings::save_settings(&app, &settings)?;
+    Ok(list_runners(&app))
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn get_platform() -> String {
@@ -1133,3 +3249,99 @@ pub fn get_platform() -> String {
     #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
     return "unknown".to_string();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        is_valid_prefix_id, load_prefix_manifest, runner_dir_name, INIT_MARKER_FILE,
+        PREFIX_MANIFEST_FILE,
+    };
+    use crate::settings::RenderingPipeline;
+
+    /// A prefix directory holding only the given marker contents.
+    fn prefix_with_marker(marker: &str) -> std::path::PathBuf {
+        let prefix = std::env::temp_dir().join(format!("cm-prefix-test-{}", uuid::Uuid::new_v4()));
+        assert!(std::fs::create_dir_all(&prefix).is_ok());
+        assert!(std::fs::write(prefix.join(INIT_MARKER_FILE), marker).is_ok());
+        prefix
+    }
+
+    #[test]
+    fn current_marker_migrates_to_a_complete_manifest() {
+        let prefix = prefix_with_marker("2");
+        let manifest = load_prefix_manifest(&prefix, RenderingPipeline::Dxvk);
+        std::fs::remove_dir_all(&prefix).ok();
+
+        assert!(manifest.is_complete(RenderingPipeline::Dxvk));
+        assert!(manifest.completed.contains("webview2"));
+    }
+
+    #[test]
+    fn old_marker_migrates_to_an_empty_manifest() {
+        let prefix = prefix_with_marker("1");
+        let manifest = load_prefix_manifest(&prefix, RenderingPipeline::Dxvk);
+        std::fs::remove_dir_all(&prefix).ok();
+
+        assert!(manifest.completed.is_empty());
+        assert!(!manifest.is_complete(RenderingPipeline::Dxvk));
+    }
+
+    #[test]
+    fn manifest_is_preferred_over_marker() {
+        let prefix = prefix_with_marker("2");
+        let written = std::fs::write(
+            prefix.join(PREFIX_MANIFEST_FILE),
+            r#"{"init_version": 2, "pipeline": "dxvk", "completed": ["wineboot"], "failed_step": "webview2"}"#,
+        );
+        let manifest = load_prefix_manifest(&prefix, RenderingPipeline::Dxvk);
+        std::fs::remove_dir_all(&prefix).ok();
+
+        assert!(written.is_ok());
+        assert_eq!(manifest.completed.len(), 1);
+        assert_eq!(manifest.failed_step.as_deref(), Some("webview2"));
+    }
+
+    #[test]
+    fn accepts_launcher_prefix_ids() {
+        assert!(is_valid_prefix_id(
+            crate::settings::RenderingPipeline::Dxvk.prefix_id()
+        ));
+        assert!(is_valid_prefix_id("profile-my-prefix-2"));
+    }
+
+    #[test]
+    fn rejects_other_prefix_ids() {
+        for id in [
+            "",
+            "..",
+            "../../..",
+            "/home/player",
+            "profile-",
+            "profile-../x",
+            "profile-A",
+            "profile-a/b",
+            "other",
+        ] {
+            assert!(!is_valid_prefix_id(id), "{id:?}");
+        }
+    }
+
+    #[test]
+    fn runner_dirs_differ_for_ids_that_sanitize_alike() {
+        assert_ne!(
+            runner_dir_name("custom:/opt/wine-a"),
+            runner_dir_name("custom:/opt/wine_a")
+        );
+    }
+
+    #[test]
+    fn runner_dirs_are_stable_single_components() {
+        let name = runner_dir_name("proton:/home/player/.steam/Proton 9.0");
+        assert_eq!(
+            name,
+            runner_dir_name("proton:/home/player/.steam/Proton 9.0")
+        );
+        assert!(!name.contains('/'));
+        assert!(name.starts_with("proton__home_player_.steam_Proton_9.0-"));
+    }
+}
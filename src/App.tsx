@@ -11,6 +11,7 @@ import {
   HomePage,
   RelayDropdown,
   ServerFilterPanel,
+  ServerHopModal,
   ServerItem,
   SettingsModal,
   SinglePlayerPanel,
@@ -239,6 +240,7 @@ const AppContent = () => {
         onResetWinePrefix={resetWinePrefix}
         onClose={() => setSettingsVisible(false)}
       />
+      <ServerHopModal />
       <GameConnectionModal
         visible={showGameConnectionModal}
         state={gameConnectionState}
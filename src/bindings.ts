@@ -231,6 +231,42 @@ async setRichPresence(enabled: boolean) : Promise<Result<AppSettings, CommandErr
     else return { status: "error", error: e  as any };
 }
 },
+async setPresencePrivacy(privacy: PresencePrivacy) : Promise<Result<AppSettings, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_presence_privacy", { privacy }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getHwidInfo() : Promise<Result<HwidInfo, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_hwid_info") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setHwidEnabled(enabled: boolean) : Promise<Result<HwidInfo, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_hwid_enabled", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Give every server a new hardware ID for this machine. Only allowed where
+ * the servers let players opt out, since a fresh ID evades bans just as well.
+ */
+async rotateHwid() : Promise<Result<HwidInfo, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rotate_hwid") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async setLastPlayedServer(serverId: string) : Promise<Result<AppSettings, CommandError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("set_last_played_server", { serverId }) };
@@ -271,11 +307,168 @@ async saveFilterSettings(filters: FilterSettings) : Promise<Result<AppSettings,
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Force a BYOND version for `server_name`, or globally when no server is given.
+ * Passing no version clears the override.
+ */
+async setByondVersionOverride(serverName: string | null, version: string | null) : Promise<Result<AppSettings, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_byond_version_override", { serverName, version }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Use `relay_id` for `server_name` instead of the selected relay. Passing
+ * no relay clears the preference.
+ */
+async setServerRelay(serverName: string, relayId: string | null) : Promise<Result<AppSettings, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_server_relay", { serverName, relayId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Add a launch profile, or replace the one with the same id.
+ */
+async saveLaunchProfile(profile: LaunchProfile) : Promise<Result<AppSettings, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_launch_profile", { profile }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Remove a launch profile and any server or default assignments to it.
+ */
+async deleteLaunchProfile(id: string) : Promise<Result<AppSettings, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_launch_profile", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Use launch profile `profile_id` for `server_name`, or for every server
+ * when no server is given. Passing no profile clears the assignment.
+ */
+async setLaunchProfile(serverName: string | null, profileId: string | null) : Promise<Result<AppSettings, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_launch_profile", { serverName, profileId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setAutoReconnect(enabled: boolean, maxAttempts: number | null) : Promise<Result<AppSettings, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_auto_reconnect", { enabled, maxAttempts }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async getControlServerPort() : Promise<number> {
     return await TAURI_INVOKE("get_control_server_port");
 },
-async killGame() : Promise<boolean> {
-    return await TAURI_INVOKE("kill_game");
+async killGame(pid: number | null) : Promise<boolean> {
+    return await TAURI_INVOKE("kill_game", { pid });
+},
+async getGameSessions() : Promise<GameSession[]> {
+    return await TAURI_INVOKE("get_game_sessions");
+},
+/**
+ * Most recent game exits, newest first.
+ */
+async getRecentGameExits() : Promise<GameExitRecord[]> {
+    return await TAURI_INVOKE("get_recent_game_exits");
+},
+/**
+ * Zip up logs, settings, Wine status, installed BYOND versions and recent
+ * game exits for a bug report. Secrets are redacted. Returns the zip's path.
+ */
+async createDiagnosticBundle() : Promise<Result<string, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_diagnostic_bundle") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Played sessions, newest first.
+ */
+async getPlayHistory(limit: number | null) : Promise<Result<PlayRecord[], CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_play_history", { limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Playtime totals per server, per week and per map.
+ */
+async getPlayStats() : Promise<Result<PlayStats, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_play_stats") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Servers played on, most recent first.
+ */
+async getRecentServers(limit: number | null) : Promise<Result<RecentServer[], CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_recent_servers", { limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async clearPlayHistory() : Promise<Result<null, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("clear_play_history") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getConnectionState() : Promise<ConnectionStatus> {
+    return await TAURI_INVOKE("get_connection_state");
+},
+/**
+ * Cancel the current connection attempt. If the game has already been
+ * launched but hasn't reached the control server yet, it is killed. Other
+ * running clients are left alone.
+ */
+async cancelConnection() : Promise<boolean> {
+    return await TAURI_INVOKE("cancel_connection");
+},
+/**
+ * Stop any pending automatic reconnects.
+ */
+async cancelAutoReconnect() : Promise<boolean> {
+    return await TAURI_INVOKE("cancel_auto_reconnect");
+},
+/**
+ * Answer a `game-hop-confirm` prompt. With `trust_address` the target is
+ * remembered like a trusted direct connect address.
+ */
+async respondToServerHop(hopId: string, accepted: boolean, trustAddress: boolean) : Promise<Result<null, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("respond_to_server_hop", { hopId, accepted, trustAddress }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
 },
 async getServers() : Promise<Result<Server[], CommandError>> {
     try {
@@ -309,6 +502,44 @@ async setSelectedRelay(id: string) : Promise<Result<null, CommandError>> {
     else return { status: "error", error: e  as any };
 }
 },
+async setRelaySelection(policy: RelaySelection, hysteresisMs: number | null) : Promise<Result<AppSettings, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_relay_selection", { policy, hysteresisMs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Add a relay of the player's own and start checking it.
+ */
+async addCustomRelay(name: string, host: string) : Promise<Result<RelayWithPing[], CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_custom_relay", { name, host }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async removeCustomRelay(id: string) : Promise<Result<RelayWithPing[], CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_custom_relay", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Switch how relays are checked and check them all again.
+ */
+async setRelayProbe(probe: RelayProbe, serverName: string | null) : Promise<Result<AppSettings, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_relay_probe", { probe, serverName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async getSteamUserInfo() : Promise<Result<SteamUserInfo, CommandError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_steam_user_info") };
@@ -376,6 +607,192 @@ async resetWinePrefix() : Promise<Result<null, CommandError>> {
     else return { status: "error", error: e  as any };
 }
 },
+async listWinePrefixes() : Promise<Result<WinePrefixInfo[], CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_wine_prefixes") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Add a named prefix for `pipeline` and start setting it up in the
+ * background.
+ */
+async createWinePrefix(name: string, pipeline: RenderingPipeline) : Promise<Result<WinePrefixInfo[], CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_wine_prefix", { name, pipeline }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Set up a prefix other than the active one in the background, so it can
+ * be switched to later without waiting.
+ */
+async prepareWinePrefix(id: string) : Promise<Result<WinePrefixInfo[], CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("prepare_wine_prefix", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Run games in another prefix. The prefix must already be set up.
+ */
+async switchWinePrefix(id: string) : Promise<Result<WinePrefixInfo[], CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("switch_wine_prefix", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Delete a prefix's files. A prefix the player created is forgotten too;
+ * a pipeline's own prefix is only emptied.
+ */
+async deleteWinePrefix(id: string) : Promise<Result<WinePrefixInfo[], CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_wine_prefix", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getWineAssetCache() : Promise<Result<WineAssetCache, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_wine_asset_cache") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Delete everything in the asset cache, so installers and winetricks
+ * downloads are fetched again on the next setup.
+ */
+async clearWineAssetCache() : Promise<Result<WineAssetCache, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("clear_wine_asset_cache") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Fill the asset cache from a pack prepared on another machine, so prefixes
+ * can be set up without downloading.
+ */
+async importWineAssetPack(path: string) : Promise<Result<WineAssetCache, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_wine_asset_pack", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Save the asset cache as a pack for `import_wine_asset_pack`.
+ */
+async exportWineAssetPack(path: string) : Promise<Result<WineAssetCache, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_wine_asset_pack", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Run every health check on a prefix, the active one if `id` is unset.
+ */
+async diagnoseWinePrefix(id: string | null) : Promise<Result<PrefixDiagnosis, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("diagnose_wine_prefix", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Fix what a failed health check found, then check the prefix again.
+ */
+async repairWinePrefix(id: string | null, check: PrefixCheckId) : Promise<Result<PrefixDiagnosis, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("repair_wine_prefix", { id, check }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listWineRunners() : Promise<Result<WineRunner[], CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_wine_runners") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Run games with another Wine build, the bundled one if `id` is unset.
+ * Prefixes are kept per runner, so the active prefix is set up in the
+ * background if this runner doesn't have it yet.
+ */
+async setWineRunner(id: string | null) : Promise<Result<WineRunner[], CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_wine_runner", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Add a directory holding a Wine build.
+ */
+async addCustomWineRunner(path: string) : Promise<Result<WineRunner[], CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_custom_wine_runner", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async removeCustomWineRunner(path: string) : Promise<Result<WineRunner[], CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_custom_wine_runner", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Save a prefix, the active one if `id` is unset, as a snapshot that
+ * `import_wine_prefix` can restore here or on another machine.
+ */
+async exportWinePrefix(id: string | null, path: string) : Promise<Result<WinePrefixSnapshot, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_wine_prefix", { id, path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Replace a prefix with a snapshot from `export_wine_prefix`. Without an
+ * `id` the snapshot's own prefix is restored, and added if it was created
+ * on another machine. Snapshots taken under another runner are refused
+ * unless `allow_runner_mismatch` is set.
+ */
+async importWinePrefix(path: string, id: string | null, allowRunnerMismatch: boolean) : Promise<Result<WinePrefixInfo[], CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_wine_prefix", { path, id, allowRunnerMismatch }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async openUrl(url: string) : Promise<Result<null, CommandError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("open_url", { url }) };
@@ -514,7 +931,74 @@ async byondSessionCheckComplete(webId: string | null, username: string | null) :
 
 /** user-defined types **/
 
-export type AppSettings = { auth_mode: AuthMode; theme?: Theme; notification_servers?: string[]; age_verified?: boolean; locale?: string | null; rendering_pipeline?: RenderingPipeline; last_played_server?: string | null; favorite_servers?: string[]; filter_tags?: string[]; filter_show_18_plus?: boolean; filter_show_offline?: boolean | null; filter_show_hub_status?: boolean; filter_regions?: string[]; filter_languages?: string[]; last_view_mode?: string | null; search_query?: string | null; trusted_direct_connect_addresses?: string[]; rich_presence_enabled?: boolean }
+export type AppSettings = { auth_mode: AuthMode; theme?: Theme; notification_servers?: string[]; age_verified?: boolean; locale?: string | null; rendering_pipeline?: RenderingPipeline; 
+/**
+ * Wine prefixes created by the player, on top of one per pipeline.
+ */
+wine_prefixes?: WinePrefixProfile[]; 
+/**
+ * Id of the Wine prefix games run in. Unset uses the prefix of
+ * `rendering_pipeline`.
+ */
+active_wine_prefix?: string | null; 
+/**
+ * Id of the Wine build games run with. Unset uses the bundled Wine.
+ */
+wine_runner?: string | null; 
+/**
+ * Wine build directories added by the player.
+ */
+custom_wine_runners?: string[]; last_played_server?: string | null; favorite_servers?: string[]; filter_tags?: string[]; filter_show_18_plus?: boolean; filter_show_offline?: boolean | null; filter_show_hub_status?: boolean; filter_regions?: string[]; filter_languages?: string[]; last_view_mode?: string | null; search_query?: string | null; trusted_direct_connect_addresses?: string[]; rich_presence_enabled?: boolean; presence_privacy?: PresencePrivacy; 
+/**
+ * BYOND version forced for every server, unless a per-server override is set.
+ */
+byond_version_override?: string | null; 
+/**
+ * BYOND versions forced for specific servers, keyed by server name.
+ */
+server_byond_versions?: Partial<{ [key in string]: string }>; 
+/**
+ * Relaunch the game automatically when it crashes.
+ */
+auto_reconnect?: boolean; auto_reconnect_max_attempts?: number; relay_selection?: RelaySelection; 
+/**
+ * How much faster another relay must be before `Sticky` switches to it.
+ */
+relay_hysteresis_ms?: number; relay_probe?: RelayProbe; 
+/**
+ * Server whose game port `GamePort` probes, by name. Unset uses the
+ * server the player is on or last joined.
+ */
+relay_probe_server?: string | null; 
+/**
+ * Relay the user last picked themselves, restored on startup.
+ */
+selected_relay?: string | null; 
+/**
+ * Relays added by the player.
+ */
+custom_relays?: Relay[]; 
+/**
+ * Relay ids to use for specific servers, keyed by server name.
+ */
+server_relays?: Partial<{ [key in string]: string }>; 
+/**
+ * Launch profiles defined by the player.
+ */
+launch_profiles?: LaunchProfile[]; 
+/**
+ * Launch profile used for every server, unless a per-server one is set.
+ */
+default_launch_profile?: string | null; 
+/**
+ * Launch profile ids to use for specific servers, keyed by server name.
+ * Singleplayer uses [`crate::singleplayer::SINGLEPLAYER_SERVER_NAME`].
+ */
+server_launch_profiles?: Partial<{ [key in string]: string }>; 
+/**
+ * Send a hardware ID to servers, where the launcher lets players opt out.
+ */
+hwid_enabled?: boolean }
 export type AuthError = { code: string; message: string; linking_url: string | null }
 export type AuthMode = "oidc" | "hub" | "byond" | "steam"
 export type AuthState = { logged_in: boolean; user: UserInfo | null; loading: boolean; error: string | null }
@@ -524,24 +1008,260 @@ export type AuthState = { logged_in: boolean; user: UserInfo | null; loading: bo
 export type ByondLoginResult = { username: string | null }
 export type ByondSessionCheck = { logged_in: boolean; username: string | null; web_id: string | null }
 export type ByondVersionInfo = { version: string; installed: boolean; path: string | null; last_used: string | null }
+export type ByondVersionOverrideWarning = { server_name: string; version: string; reason: string }
 export type CommandError = { type: "network"; data: string } | { type: "not_authenticated" } | { type: "token_expired" } | { type: "requires_2fa" } | { type: "invalid_credentials" } | { type: "account_locked" } | { type: "requires_linking"; data: { url: string } } | { type: "not_found"; data: string } | { type: "io"; data: string } | { type: "not_configured"; data: { feature: string } } | { type: "unsupported_platform"; data: { feature: string; platform: string } } | { type: "busy"; data: { operation: string } } | { type: "cancelled"; data: { operation: string } } | { type: "timeout"; data: { operation: string } } | { type: "internal"; data: string } | { type: "webview"; data: string } | { type: "invalid_response"; data: string } | { type: "invalid_input"; data: string }
 export type ConnectionResult = { success: boolean; message: string; auth_error: AuthError | null }
+export type ConnectionStage = "idle" | "resolving" | "installing" | "authenticating" | "launching" | "waiting_for_handshake" | "connected" | "failed" | "cancelled"
+export type ConnectionStatus = { attempt_id: number; stage: ConnectionStage; server_name: string | null; message: string | null }
 export type DirectConnectInfo = { hostname: string; port: number; server_id: string | null; trust: DirectConnectTrust; verified_domain?: string | null; server_name?: string | null }
 export type DirectConnectTrust = "HubVerified" | "HubKnown" | "DomainAttested" | "SelfReported" | "ByondOnly" | "Unreachable"
 export type EngineRequirements = { min_version?: string | null; max_version?: string | null; blacklisted_versions?: string[] }
+/**
+ * Failures we can recognise from game output.
+ */
+export type FailureKind = "missing_webview_2" | "dxvk_init_failed" | "auth_rejected" | "missing_dll" | "vulkan_unavailable" | "webview_2_crashed" | "gpu_out_of_memory"
 export type FilterSettings = { tags: string[]; show_18_plus: boolean; show_offline: boolean | null; show_hub_status: boolean; regions: string[]; languages: string[]; search_query: string | null }
-export type LauncherConfig = { variant: string; product_name: string; logo: string; default_theme: string; app_identifier: string; default_byond_version: string | null; server_api: ServerApiType; features: LauncherFeatures; urls: LauncherUrls; strings: LauncherStrings; singleplayer: SingleplayerConfig; oidc: OidcConfig | null; social_links: SocialLink[] }
-export type LauncherFeatures = { relay_selector: boolean; singleplayer: boolean; server_search: boolean; server_filters: boolean; show_offline_servers: boolean; server_stats: boolean; auto_launch_byond: boolean; connection_timeout_fallback: boolean; connect_logo: boolean; favorites: boolean; direct_connect: boolean }
+export type GameExitRecord = { server_name: string; pid: number; reason: SessionEndReason; exit_code: number | null; duration_secs: number; 
+/**
+ * RFC 3339 timestamp.
+ */
+ended_at: string; failure: FailureKind | null; output_tail: string[] }
+export type GameSession = { 
+/**
+ * PID of the tracked game process; sessions are keyed by this.
+ */
+pid: number; server_name: string; map_name: string | null; 
+/**
+ * Reported by the game in its handshake.
+ */
+ckey: string | null; round_id: string | null; 
+/**
+ * Latest status pushed by the game over the WebSocket channel.
+ */
+status: GameStatus | null }
+/**
+ * What the game reports about itself. Every field is optional so the game
+ * can send only what changed since its last update.
+ */
+export type GameStatus = { ckey: string | null; round_id: string | null; 
+/**
+ * Server-defined round state, e.g. `pregame`, `playing`, `finished`.
+ */
+round_state: string | null; in_lobby: boolean | null; 
+/**
+ * Name of the character being played.
+ */
+character: string | null; 
+/**
+ * Seconds since the round started.
+ */
+round_time: number | null; role: string | null; faction: string | null; observer: boolean | null }
+/**
+ * Where a hop is going, as shown to the user.
+ */
+export type HopDestination = { hop_id: string; from_server: string; server_name: string; 
+/**
+ * `host:port` of the target.
+ */
+address: string; server_id: string | null; trust: DirectConnectTrust; verified_domain: string | null; 
+/**
+ * Why the server sent the player here, if it said.
+ */
+reason: string | null }
+/**
+ * What the settings screen shows about hardware IDs.
+ */
+export type HwidInfo = { scheme_version: number; enabled: boolean; 
+/**
+ * Whether this launcher's servers allow turning the ID off.
+ */
+opt_out_allowed: boolean; sources: HwidSource[]; 
+/**
+ * RFC 3339 timestamps, unset until an ID has been generated.
+ */
+created_at: string | null; rotated_at: string | null }
+/**
+ * One kind of identifier that may go into the machine key.
+ */
+export type HwidSource = { name: string; description: string; 
+/**
+ * Whether it could be read on this machine.
+ */
+available: boolean }
+/**
+ * Extra environment, wrapper commands and CPU affinity applied when the game
+ * is launched through Wine.
+ */
+export type LaunchProfile = { id: string; name: string; 
+/**
+ * Set after the launcher's own Wine environment, so these win, except
+ * for the variables in [`RESERVED_ENV_KEYS`].
+ */
+env?: Partial<{ [key in string]: string }>; 
+/**
+ * Commands the game is started through, outermost first, each given as a
+ * command line such as `gamemoderun` or `nice -n 5`.
+ */
+wrappers?: string[]; 
+/**
+ * CPUs the game may run on, as a list like `0-3,6`.
+ */
+cpu_affinity?: string | null }
+export type LauncherConfig = { variant: string; product_name: string; logo: string; default_theme: string; app_identifier: string; default_byond_version: string | null; server_api: ServerApiType; features: LauncherFeatures; urls: LauncherUrls; strings: LauncherStrings; singleplayer: SingleplayerConfig; oidc: OidcConfig | null; social_links: SocialLink[]; 
+/**
+ * Relays shipped with the launcher.
+ */
+relays: RelayConfig[] }
+export type LauncherFeatures = { relay_selector: boolean; singleplayer: boolean; server_search: boolean; server_filters: boolean; show_offline_servers: boolean; server_stats: boolean; auto_launch_byond: boolean; connection_timeout_fallback: boolean; connect_logo: boolean; favorites: boolean; direct_connect: boolean; 
+/**
+ * Players may turn off the hardware ID sent to servers.
+ */
+hwid_opt_out: boolean }
 export type LauncherStrings = { auth_provider_name: string; login_prompt: string; discord_game_name: string }
-export type LauncherUrls = { server_api: string; hub_api: string | null; auth_base: string | null; steam_auth: string | null; byond_hash_api: string | null; register_url: string | null; help_url: string }
+export type LauncherUrls = { server_api: string; hub_api: string | null; auth_base: string | null; steam_auth: string | null; byond_hash_api: string | null; register_url: string | null; help_url: string; 
+/**
+ * JSON list of extra relays, fetched at startup and on every relay check.
+ */
+relay_manifest: string | null }
 export type OidcConfig = { client_id: string; auth_url: string; token_url: string; userinfo_url: string }
-export type RelayWithPing = ({ id: string; name: string; host: string }) & { ping: number | null; checking: boolean }
+export type PlayRecord = { server_name: string; map_name: string | null; 
+/**
+ * RFC 3339 timestamps.
+ */
+started_at: string; ended_at: string; duration_secs: number; byond_version: string | null; auth_mode: string | null }
+export type PlayStats = { total_secs: number; session_count: number; 
+/**
+ * Most played first.
+ */
+per_server: PlaytimeTotal[]; 
+/**
+ * Oldest week first.
+ */
+per_week: PlaytimeTotal[]; 
+/**
+ * Most played first.
+ */
+per_map: PlaytimeTotal[] }
+export type PlaytimeTotal = { 
+/**
+ * Server name, map name, or ISO week (`2025-W07`).
+ */
+key: string; total_secs: number; session_count: number }
+/**
+ * Result of one prefix health check
+ */
+export type PrefixCheck = { id: PrefixCheckId; passed: boolean; detail: string; 
+/**
+ * Whether `repair_wine_prefix` can fix it
+ */
+repairable: boolean }
+export type PrefixCheckId = "setup" | "dxvk_overrides" | "web_view_2_runtime" | "wineserver" | "stuck_processes" | "vulkan"
+export type PrefixDiagnosis = { prefix_id: string; pipeline: RenderingPipeline; healthy: boolean; checks: PrefixCheck[] }
+/**
+ * Which details of the game's own status are shared through rich presence.
+ */
+export type PresencePrivacy = { show_round_time: boolean; show_lobby_status: boolean; show_role: boolean; show_faction: boolean; show_observer: boolean }
+export type RecentServer = { server_name: string; last_played: string; session_count: number }
+export type Relay = { id: string; name: string; host: string; source?: RelaySource }
+export type RelayConfig = { id: string; name: string; host: string }
+/**
+ * Availability and latency over a relay's recent checks.
+ */
+export type RelayHealth = { checks: number; 
+/**
+ * Share of checks that reached the relay, from 0 to 1.
+ */
+availability: number; 
+/**
+ * Percentiles of the per-check medians, in ms.
+ */
+p50: number | null; p95: number | null }
+/**
+ * How relays are checked.
+ */
+export type RelayProbe = 
+/**
+ * WebSocket echo on the relay's ping port.
+ */
+"web_socket" | 
+/**
+ * TCP connect and topic round trip on a server's game port through the
+ * relay, the same path the game uses.
+ */
+"game_port"
+/**
+ * How the relay used for connections is chosen.
+ */
+export type RelaySelection = 
+/**
+ * Keep the relay the player picked.
+ */
+"manual" | "lowest_median" | "lowest_p95" | 
+/**
+ * Keep the current relay until another is faster by more than the
+ * hysteresis margin.
+ */
+"sticky"
+/**
+ * Where a relay came from.
+ */
+export type RelaySource = "builtin" | "manifest" | "custom"
+export type RelayWithPing = ({ id: string; name: string; host: string; source?: RelaySource }) & { 
+/**
+ * Median round trip of the last check in ms, `None` if unreachable.
+ * For the game port probe this is the connect time.
+ */
+ping: number | null; 
+/**
+ * 95th percentile round trip of the last check in ms.
+ */
+p95: number | null; 
+/**
+ * Median TCP connect time to the game port in ms, from the game port
+ * probe only.
+ */
+connect: number | null; 
+/**
+ * Median topic round trip through the relay to the game server in ms,
+ * from the game port probe only.
+ */
+topic: number | null; checking: boolean; health: RelayHealth }
 export type ReleaseInfo = { tag_name: string; name: string; published_at: string; download_url: string | null; size: number }
-export type RenderingPipeline = "dxvk" | "wined3d"
+export type RenderingPipeline = "dxvk" | "wined3d" | 
+/**
+ * `WineD3D` with its Vulkan renderer.
+ */
+"wined3d_vulkan" | 
+/**
+ * `WineD3D` on plain OpenGL without command stream threading, for
+ * drivers the other pipelines don't work with.
+ */
+"gl_fallback"
 export type Server = { id: string | null; name: string; url: string; status: string; hub_status?: string; players?: number; data?: ServerData | null; is_18_plus?: boolean; version?: string | null; engine?: EngineRequirements | null; tags?: string[]; auth_methods?: string[]; engine_type?: string | null; description?: string | null; links?: ServerLink[]; verified_domain?: string | null; region?: string | null; language?: string | null }
 export type ServerApiType = "hub_api" | "cm_api"
 export type ServerData = { round_id: number; mode: string; map_name: string; round_duration: number; gamestate: number; players: number; admins?: number | null; popcap?: number | null; security_level?: string | null }
 export type ServerLink = { link: string; type: string }
+/**
+ * Why a game session stopped being tracked.
+ */
+export type SessionEndReason = 
+/**
+ * Killed by the launcher (`kill_game`, a cancelled connection, a restart).
+ */
+"killed" | 
+/**
+ * Exited with a success code.
+ */
+"exited" | 
+/**
+ * Exited with a non-zero code.
+ */
+"crashed" | 
+/**
+ * The process went away and its exit code couldn't be read, which is
+ * the case for clients we track by PID rather than by `Child` handle.
+ */
+"lost"
 export type SinglePlayerStatus = { installed: boolean; version: string | null; release_tag: string | null; path: string | null }
 export type SingleplayerConfig = { github_repo: string | null; build_asset_name: string | null; dmb_name: string | null }
 export type SocialLink = { name: string; url: string; icon: string }
@@ -550,6 +1270,51 @@ export type SteamLaunchOptions = { raw: string; server_name: string | null }
 export type SteamUserInfo = { steam_id: string; display_name: string }
 export type Theme = "tgui" | "crt"
 export type UserInfo = { sub: string; name: string | null; preferred_username: string | null; email: string | null; email_verified: boolean | null }
+/**
+ * What the asset cache holds
+ */
+export type WineAssetCache = { path: string; webview2_cached: boolean; 
+/**
+ * Winetricks verbs with cached downloads
+ */
+winetricks_verbs: string[]; size_mb: number }
+/**
+ * A Wine prefix as shown in the settings screen
+ */
+export type WinePrefixInfo = ({ id: string; name: string; pipeline: RenderingPipeline }) & { 
+/**
+ * Whether the player created it, as opposed to a pipeline's own prefix
+ */
+custom: boolean; path: string; initialized: boolean; active: boolean; setting_up: boolean }
+/**
+ * A named Wine prefix set up for one rendering pipeline.
+ */
+export type WinePrefixProfile = { id: string; name: string; pipeline: RenderingPipeline }
+/**
+ * Where a prefix snapshot was taken from
+ */
+export type WinePrefixSnapshot = { format_version: number; prefix: WinePrefixProfile; 
+/**
+ * Id of the Wine runner the prefix was used with
+ */
+runner: string; wine_version: string | null; 
+/**
+ * RFC 3339 time the snapshot was taken
+ */
+created_at: string; 
+/**
+ * Manifest `INIT_VERSION` and setup steps the prefix had completed
+ */
+init_version: number; completed_steps: string[] }
+/**
+ * A Wine build games can run with
+ */
+export type WineRunner = { id: string; name: string; kind: WineRunnerKind; 
+/**
+ * Directory holding the Wine binaries
+ */
+path: string; version: string | null; meets_minimum_version: boolean; active: boolean; error: string | null }
+export type WineRunnerKind = "bundled" | "system" | "proton" | "custom"
 export type WineStatus = { installed: boolean; version: string | null; meets_minimum_version: boolean; winetricks_installed: boolean; prefix_initialized: boolean; webview2_installed: boolean; error: string | null }
 
 /** tauri-specta globals **/
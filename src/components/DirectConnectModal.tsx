@@ -8,7 +8,11 @@ import { useConnect, useError } from "../hooks";
 import { useSettingsStore } from "../stores";
 import { Modal } from "./Modal";
 
-function TrustInfo({ connectInfo }: { connectInfo: DirectConnectInfo }) {
+export function TrustInfo({
+  connectInfo,
+}: {
+  connectInfo: Pick<DirectConnectInfo, "trust" | "verified_domain">;
+}) {
   const { t } = useTranslation();
 
   if (connectInfo.trust === "DomainAttested" && connectInfo.verified_domain) {
@@ -40,7 +40,7 @@ export const GameConnectionModal = ({
   const handleCloseGame = async () => {
     setClosing(true);
     try {
-      await commands.killGame();
+      await commands.killGame(null);
       onClose();
     } catch (err) {
       console.error("Failed to close game:", err);
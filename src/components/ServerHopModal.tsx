@@ -0,0 +1,106 @@
+import { listen } from "@tauri-apps/api/event";
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import { commands, HopDestination } from "../bindings";
+import { formatCommandError } from "../lib/formatCommandError";
+import { useError } from "../hooks";
+import { useSettingsStore } from "../stores";
+import { TrustInfo } from "./DirectConnectModal";
+import { Modal } from "./Modal";
+
+interface HopFailedEvent {
+  server_name: string;
+  message: string;
+}
+
+/** Asks the user to confirm a hop the game requested to an untrusted server. */
+export const ServerHopModal = () => {
+  const { t } = useTranslation();
+  const { showError } = useError();
+  const [hop, setHop] = useState<HopDestination | null>(null);
+  const [trustAddress, setTrustAddress] = useState(false);
+
+  useEffect(() => {
+    const unlistenConfirm = listen<HopDestination>("game-hop-confirm", (event) => {
+      setHop(event.payload);
+      setTrustAddress(false);
+    });
+
+    const unlistenFailed = listen<HopFailedEvent>("game-hop-failed", (event) => {
+      showError(
+        t("serverHop.failed", {
+          server: event.payload.server_name,
+          message: event.payload.message,
+        })
+      );
+    });
+
+    return () => {
+      unlistenConfirm.then((unlisten) => unlisten());
+      unlistenFailed.then((unlisten) => unlisten());
+    };
+  }, [showError, t]);
+
+  const respond = async (accepted: boolean) => {
+    if (!hop) return;
+    setHop(null);
+
+    const trust = accepted && trustAddress;
+    const result = await commands.respondToServerHop(hop.hop_id, accepted, trust);
+    if (result.status === "error") {
+      showError(formatCommandError(result.error));
+      return;
+    }
+    if (trust) {
+      useSettingsStore.setState((s) => ({
+        trustedAddresses: new Set(s.trustedAddresses).add(hop.address.toLowerCase()),
+      }));
+    }
+  };
+
+  if (!hop) return null;
+
+  return (
+    <Modal
+      visible
+      onClose={() => respond(false)}
+      className="settings-modal"
+      title={t("serverHop.title")}
+    >
+      <div className="modal-body">
+        <div className="settings-section">
+          <p className="settings-description">
+            {t("serverHop.description", {
+              from: hop.from_server,
+              address: hop.address,
+            })}
+          </p>
+          {hop.reason && (
+            <p className="settings-description settings-description-hint">
+              {t("serverHop.reason", { reason: hop.reason })}
+            </p>
+          )}
+          <TrustInfo connectInfo={hop} />
+        </div>
+      </div>
+      <div className="modal-footer" style={{ justifyContent: "space-between" }}>
+        <label className="styled-checkbox">
+          <input
+            type="checkbox"
+            checked={trustAddress}
+            onChange={(e) => setTrustAddress(e.target.checked)}
+          />
+          {t("directConnect.rememberTrust")}
+        </label>
+        <div>
+          <button type="button" className="button-secondary" onClick={() => respond(false)}>
+            {t("common.cancel")}
+          </button>{" "}
+          <button type="button" className="button" onClick={() => respond(true)}>
+            {t("common.connect")}
+          </button>
+        </div>
+      </div>
+    </Modal>
+  );
+};
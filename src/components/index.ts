@@ -10,6 +10,7 @@ export { HomePage } from "./HomePage";
 export { Modal, ModalContent, ModalSpinner } from "./Modal";
 export { RelayDropdown } from "./RelayDropdown";
 export { ServerFilterPanel } from "./ServerFilterPanel";
+export { ServerHopModal } from "./ServerHopModal";
 export { ServerItem } from "./ServerItem";
 export { SettingsModal } from "./SettingsModal";
 export { SinglePlayerPanel } from "./SinglePlayerPanel";